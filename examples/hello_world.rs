@@ -34,10 +34,12 @@ struct StringGuess {
 }
 
 impl Phenotype for StringGuess {
+    type Fitness = f64;
+
     fn fitness(&self) -> f64 {
         // Hamming distance
         if self.target.len() != self.guess.len() {
-            std::f64::INFINITY
+            f64::INFINITY
         } else {
             self.target.chars().zip(self.guess.chars()).filter(|&(a, b)| a != b).count() as f64
         }
@@ -89,22 +91,22 @@ impl Phenotype for StringGuess {
 
 fn main() {
     let input = "HelloWorld";
-    let mut population: Vec<Box<StringGuess>> = Vec::with_capacity(500);
+    let mut population: Vec<StringGuess> = Vec::with_capacity(500);
     let mut rng = ::rand::thread_rng();
     for _ in 0..500 {
         // Generate a random string
         let guess = rng.gen_ascii_chars().take(input.len()).collect::<String>();
-        population.push(Box::new(StringGuess {
+        population.push(StringGuess {
             target: String::from(input),
-            guess: guess,
-        }));
+            guess,
+        });
     }
-    let mut s = *Simulator::builder()
-                     .set_population(&population)
-                     .set_selector(Box::new(RouletteSelector::new(40)))
-                     .set_max_iters(1000)
-                     .set_fitness_type(FitnessType::Minimize)
-                     .build();
+    let mut s = Simulator::builder()
+                    .set_population(population)
+                    .set_selector(Box::new(RouletteSelector::new(40)))
+                    .set_max_iters(1000)
+                    .set_fitness_type(FitnessType::Minimize)
+                    .build().unwrap();
     let mut index = 1;
     while let StepResult::Success = s.step() {
         let result = s.get().unwrap();
@@ -112,7 +114,7 @@ fn main() {
                  index,
                  result.fitness(),
                  result.guess);
-        if (*result).guess == input {
+        if result.guess == input {
             break;
         }
         index += 1;
@@ -30,6 +30,8 @@ struct MyData {
 }
 
 impl Phenotype for MyData {
+    type Fitness = f64;
+
     fn fitness(&self) -> f64 {
         // Calculate the function here, because it's what we wish to maximize.
         10.0 - ((self.x + 3.0) * (self.x + 3.0))
@@ -66,12 +68,12 @@ impl Clone for MyData {
 }
 
 fn main() {
-    let population = (-300..300).map(|i| Box::new(MyData{ x: i as f64 })).collect();
-    let mut s = *Simulator::builder()
-                            .set_population(&population)
-                            .set_selector(Box::new(StochasticSelector::new(10)))
-                            .set_max_iters(50)
-                            .build();
+    let population: Vec<MyData> = (-300..300).map(|i| MyData{ x: i as f64 }).collect();
+    let mut s = Simulator::builder()
+                           .set_population(population)
+                           .set_selector(Box::new(StochasticSelector::new(10)))
+                           .set_max_iters(50)
+                           .build().unwrap();
     s.run();
     let result = s.get().unwrap();
     let time = s.time();
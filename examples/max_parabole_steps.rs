@@ -32,6 +32,8 @@ struct MyData {
 }
 
 impl Phenotype for MyData {
+    type Fitness = f64;
+
     fn fitness(&self) -> f64 {
         // Calculate the function here, because it's what we wish to maximize.
         10.0 - ((self.x + 3.0) * (self.x + 3.0))
@@ -68,12 +70,12 @@ impl Clone for MyData {
 }
 
 fn main() {
-    let population = (-300..300).map(|i| Box::new(MyData{ x: i as f64 })).collect();
-    let mut s = *Simulator::builder()
-                            .set_population(&population)
-                            .set_selector(Box::new(StochasticSelector::new(10)))
-                            .set_max_iters(50)
-                            .build();
+    let population: Vec<MyData> = (-300..300).map(|i| MyData{ x: i as f64 }).collect();
+    let mut s = Simulator::builder()
+                           .set_population(population)
+                           .set_selector(Box::new(StochasticSelector::new(10)))
+                           .set_max_iters(50)
+                           .build().unwrap();
     while let StepResult::Success = s.step() {
         let result = s.get().unwrap();
         println!("Intermediate result: ({}, {}).", result.x, result.fitness());
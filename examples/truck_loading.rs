@@ -36,13 +36,15 @@ type Scheme = Vec<(TruckIndex, PackageSize)>;
 
 const NUM_TRUCKS: usize = 5;
 const CAPACITY: i32 = 10;
-const PACKAGES: &'static [i32] = &[3, 8, 2, 7, 6, 1, 3];
+const PACKAGES: &[i32] = &[3, 8, 2, 7, 6, 1, 3];
 
 struct LoadingScheme {
     scheme: Scheme,
 }
 
 impl Phenotype for LoadingScheme {
+    type Fitness = f64;
+
     fn fitness(&self) -> f64 {
         let mut ret: f64 = 0.0;
         // Calculate for each truck the total load.
@@ -54,7 +56,7 @@ impl Phenotype for LoadingScheme {
             let space_left = CAPACITY - load;
             if space_left < 0 {
                 // We have overfilled a truck: penalize this solution heavily.
-                return std::f64::INFINITY;
+                return f64::INFINITY;
             }
             if space_left == CAPACITY {
                 // We have an empty truck: give this solution a little boost.
@@ -75,15 +77,10 @@ impl Phenotype for LoadingScheme {
         let mut rng = ::rand::thread_rng();
         let crossover_indices = (rng.gen::<usize>() % PACKAGES.len(),
                                  rng.gen::<usize>() % PACKAGES.len());
-        let mut crossed_over: Scheme = vec![(0, 0); PACKAGES.len()];
-        for i in 0..crossover_indices.0 {
-            crossed_over[i] = self.scheme[i];
-        }
-        for i in crossover_indices.0..crossover_indices.1 {
-            crossed_over[i] = other.scheme[i];
-        }
-        for i in crossover_indices.1..PACKAGES.len() {
-            crossed_over[i] = self.scheme[i];
+        let mut crossed_over: Scheme = self.scheme.clone();
+        if crossover_indices.0 < crossover_indices.1 {
+            let (start, end) = crossover_indices;
+            crossed_over[start..end].copy_from_slice(&other.scheme[start..end]);
         }
         LoadingScheme { scheme: crossed_over }
     }
@@ -109,22 +106,22 @@ impl Clone for LoadingScheme {
 }
 
 fn main() {
-    let mut population: Vec<Box<LoadingScheme>> = Vec::with_capacity(300);
+    let mut population: Vec<LoadingScheme> = Vec::with_capacity(300);
     let mut rng = ::rand::thread_rng();
     for _ in 0..300 {
         let mut pheno: Scheme = Vec::with_capacity(PACKAGES.len());
-        for j in 0..PACKAGES.len() {
+        for package in PACKAGES {
             let index = rng.gen::<usize>() % NUM_TRUCKS;
-            pheno.push((index, PACKAGES[j]));
+            pheno.push((index, *package));
         }
-        population.push(Box::new(LoadingScheme { scheme: pheno }));
+        population.push(LoadingScheme { scheme: pheno });
     }
-    let mut s = *Simulator::builder()
-                     .set_population(&population)
-                     .set_selector(Box::new(RouletteSelector::new(10)))
-                     .set_max_iters(50)
-                     .set_fitness_type(FitnessType::Minimize)
-                     .build();
+    let mut s = Simulator::builder()
+                    .set_population(population)
+                    .set_selector(Box::new(RouletteSelector::new(10)))
+                    .set_max_iters(50)
+                    .set_fitness_type(FitnessType::Minimize)
+                    .build().unwrap();
     s.run();
     let result = s.get().unwrap();
     let time = s.time();
@@ -0,0 +1,217 @@
+// file: bitstring.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bit strings, with one-point, two-point and uniform crossover and bit flip mutation.
+//!
+//! A `BitString` is the backbone of a `Phenotype`: wrap it in your own type, calculate the
+//! fitness value from its bits, and delegate crossover and mutation to it.
+//!
+//! ```ignore
+//! #[derive(Clone)]
+//! struct OneMax(BitString);
+//!
+//! impl Phenotype for OneMax {
+//!     type Fitness = usize;
+//!
+//!     fn fitness(&self) -> usize {
+//!         self.0.count_ones()
+//!     }
+//!
+//!     fn crossover(&self, other: &OneMax) -> OneMax {
+//!         self.crossover_with_rng(other, &mut rand::thread_rng())
+//!     }
+//!
+//!     fn mutate(&self) -> OneMax {
+//!         self.mutate_with_rng(&mut rand::thread_rng())
+//!     }
+//!
+//!     fn crossover_with_rng<R: Rng>(&self, other: &OneMax, rng: &mut R) -> OneMax {
+//!         OneMax(self.0.crossover(&other.0, Crossover::TwoPoint, rng))
+//!     }
+//!
+//!     fn mutate_with_rng<R: Rng>(&self, rng: &mut R) -> OneMax {
+//!         OneMax(self.0.mutate(1.0 / self.0.len() as f64, rng))
+//!     }
+//! }
+//! ```
+
+use pheno::Distance;
+use rand::Rng;
+
+/// A crossover operator for bit strings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Crossover {
+    /// Take the bits before a random point from the first parent, and the rest from the
+    /// second.
+    OnePoint,
+    /// Take the bits between two random points from the second parent, and the rest from
+    /// the first.
+    TwoPoint,
+    /// Take every bit from the second parent with the given probability, and from the
+    /// first otherwise. A probability of 0.5 is most common.
+    Uniform(f64),
+}
+
+/// A string of bits.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BitString {
+    bits: Vec<bool>,
+}
+
+impl BitString {
+    /// Create a bit string from `bits`.
+    pub fn new(bits: Vec<bool>) -> BitString {
+        BitString { bits }
+    }
+
+    /// Create a bit string of `len` random bits.
+    pub fn random<R: Rng>(len: usize, rng: &mut R) -> BitString {
+        BitString { bits: (0..len).map(|_| rng.gen()).collect() }
+    }
+
+    /// Get the bits.
+    pub fn bits(&self) -> &[bool] {
+        &self.bits
+    }
+
+    /// Get the number of bits.
+    pub fn len(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Check whether there are no bits.
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// Count the bits that are set.
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().filter(|&&bit| bit).count()
+    }
+
+    /// Interpret the bits as an unsigned integer, the first bit being the most significant.
+    /// Bits beyond the 64th are ignored.
+    pub fn to_u64(&self) -> u64 {
+        self.bits.iter().take(64).fold(0, |n, &bit| (n << 1) | bit as u64)
+    }
+
+    /// Create a child of this bit string and `other` by `crossover`. Both must have the same
+    /// length.
+    pub fn crossover<R: Rng>(&self, other: &BitString, crossover: Crossover, rng: &mut R)
+                             -> BitString {
+        let len = self.len().min(other.len());
+        let bits = match crossover {
+            Crossover::OnePoint => {
+                let point = rng.gen_range(0, len + 1);
+                self.bits[..point].iter().chain(&other.bits[point..len]).cloned().collect()
+            }
+            Crossover::TwoPoint => {
+                let x = rng.gen_range(0, len + 1);
+                let y = rng.gen_range(0, len + 1);
+                let (start, end) = if x < y { (x, y) } else { (y, x) };
+                self.bits[..start].iter()
+                                  .chain(&other.bits[start..end])
+                                  .chain(&self.bits[end..len])
+                                  .cloned()
+                                  .collect()
+            }
+            Crossover::Uniform(probability) => {
+                self.bits[..len].iter()
+                                .zip(&other.bits)
+                                .map(|(&x, &y)| if rng.gen::<f64>() < probability { y } else { x })
+                                .collect()
+            }
+        };
+        BitString { bits }
+    }
+
+    /// Create a mutated copy of this bit string, in which every bit is flipped with
+    /// probability `rate`. A rate of one over the length is most common.
+    pub fn mutate<R: Rng>(&self, rate: f64, rng: &mut R) -> BitString {
+        BitString { bits: self.bits.iter().map(|&bit| bit != (rng.gen::<f64>() < rate)).collect() }
+    }
+}
+
+impl Distance for BitString {
+    /// The Hamming distance: the number of positions at which the bits differ.
+    fn distance(&self, other: &BitString) -> f64 {
+        self.bits.iter().zip(&other.bits).filter(|&(x, y)| x != y).count() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pheno::Distance;
+
+    fn zeros() -> BitString {
+        BitString::new(vec![false; 20])
+    }
+
+    fn ones() -> BitString {
+        BitString::new(vec![true; 20])
+    }
+
+    /// Count the number of runs of equal bits.
+    fn runs(x: &BitString) -> usize {
+        1 + x.bits().windows(2).filter(|pair| pair[0] != pair[1]).count()
+    }
+
+    #[test]
+    fn test_one_point() {
+        let mut rng = ::rand::thread_rng();
+        for _ in 0..100 {
+            let child = zeros().crossover(&ones(), Crossover::OnePoint, &mut rng);
+            assert_eq!(child.len(), 20);
+            assert!(runs(&child) <= 2);
+            // Zeros come first.
+            assert!(child.bits().windows(2).all(|pair| pair[0] <= pair[1]));
+        }
+    }
+
+    #[test]
+    fn test_two_point() {
+        let mut rng = ::rand::thread_rng();
+        for _ in 0..100 {
+            let child = zeros().crossover(&ones(), Crossover::TwoPoint, &mut rng);
+            assert!(runs(&child) <= 3);
+            // The ends come from the first parent, unless a point is at an end.
+            assert!(!child.bits()[0] || !child.bits()[19] || child == ones());
+        }
+    }
+
+    #[test]
+    fn test_uniform() {
+        let mut rng = ::rand::thread_rng();
+        assert_eq!(zeros().crossover(&ones(), Crossover::Uniform(1.0), &mut rng), ones());
+        assert_eq!(zeros().crossover(&ones(), Crossover::Uniform(0.0), &mut rng), zeros());
+        let child = BitString::new(vec![false; 1000])
+                        .crossover(&BitString::new(vec![true; 1000]),
+                                   Crossover::Uniform(0.5),
+                                   &mut rng);
+        assert!(child.count_ones() > 400 && child.count_ones() < 600);
+    }
+
+    #[test]
+    fn test_mutate() {
+        let mut rng = ::rand::thread_rng();
+        assert_eq!(zeros().mutate(0.0, &mut rng), zeros());
+        assert_eq!(zeros().mutate(1.0, &mut rng), ones());
+        assert_eq!(zeros().distance(&ones()), 20.0);
+        assert_eq!(BitString::new(vec![true, false, true]).to_u64(), 5);
+    }
+}
@@ -0,0 +1,250 @@
+// file: permutation.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Permutations, for ordering problems such as the traveling salesman problem and
+//! scheduling, with partially mapped (PMX), order (OX) and cycle (CX) crossover, and swap
+//! and insert mutation.
+//!
+//! All operators keep permutations valid: every element occurs exactly once. Wrap a
+//! `Permutation` in your own `Phenotype`, like `genotypes::bitstring::BitString`.
+
+use pheno::Distance;
+use rand::Rng;
+
+/// A crossover operator for permutations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Crossover {
+    /// Partially mapped crossover (PMX): take a random segment from the first parent, and
+    /// the other positions from the second, mapping elements that already occur in the
+    /// segment through it.
+    PartiallyMapped,
+    /// Order crossover (OX): take a random segment from the first parent, and fill the
+    /// other positions with the remaining elements in the order of the second, starting
+    /// after the segment.
+    Order,
+    /// Cycle crossover (CX): split the positions into cycles, and take the cycles
+    /// alternately from the first and the second parent, so every element keeps the
+    /// position it has in one of the parents.
+    Cycle,
+}
+
+/// A mutation operator for permutations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mutation {
+    /// Swap two random elements.
+    Swap,
+    /// Move a random element to a random position, shifting the elements in between.
+    Insert,
+}
+
+/// A permutation of the numbers from 0 up to its length.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Permutation {
+    order: Vec<usize>,
+}
+
+impl Permutation {
+    /// Create a permutation from `order`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` does not contain every number from 0 up to its length exactly
+    /// once.
+    pub fn new(order: Vec<usize>) -> Permutation {
+        let mut seen = vec![false; order.len()];
+        for &x in &order {
+            assert!(x < order.len() && !seen[x], "Not a permutation: {:?}", order);
+            seen[x] = true;
+        }
+        Permutation { order }
+    }
+
+    /// Create the permutation of `len` numbers in increasing order.
+    pub fn identity(len: usize) -> Permutation {
+        Permutation { order: (0..len).collect() }
+    }
+
+    /// Create a random permutation of `len` numbers.
+    pub fn random<R: Rng>(len: usize, rng: &mut R) -> Permutation {
+        let mut order: Vec<usize> = (0..len).collect();
+        rng.shuffle(&mut order);
+        Permutation { order }
+    }
+
+    /// Get the order of the numbers.
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// Get the number of elements.
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Check whether there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Create a child of this permutation and `other` by `crossover`. Both must have the
+    /// same length.
+    pub fn crossover<R: Rng>(&self, other: &Permutation, crossover: Crossover, rng: &mut R)
+                             -> Permutation {
+        let len = self.len();
+        if len < 2 {
+            return self.clone();
+        }
+        let x = rng.gen_range(0, len + 1);
+        let y = rng.gen_range(0, len + 1);
+        let (start, end) = if x < y { (x, y) } else { (y, x) };
+        let order = match crossover {
+            Crossover::PartiallyMapped => pmx(&self.order, &other.order, start, end),
+            Crossover::Order => ox(&self.order, &other.order, start, end),
+            Crossover::Cycle => cx(&self.order, &other.order),
+        };
+        Permutation { order }
+    }
+
+    /// Create a mutated copy of this permutation by `mutation`.
+    pub fn mutate<R: Rng>(&self, mutation: Mutation, rng: &mut R) -> Permutation {
+        let mut order = self.order.clone();
+        if order.len() < 2 {
+            return Permutation { order };
+        }
+        let x = rng.gen_range(0, order.len());
+        let y = rng.gen_range(0, order.len());
+        match mutation {
+            Mutation::Swap => order.swap(x, y),
+            Mutation::Insert => {
+                let element = order.remove(x);
+                order.insert(y, element);
+            }
+        }
+        Permutation { order }
+    }
+}
+
+impl Distance for Permutation {
+    /// The number of positions at which the elements differ.
+    fn distance(&self, other: &Permutation) -> f64 {
+        self.order.iter().zip(&other.order).filter(|&(x, y)| x != y).count() as f64
+    }
+}
+
+/// Partially mapped crossover of `x` and `y`, with the segment from `start` to `end`.
+fn pmx(x: &[usize], y: &[usize], start: usize, end: usize) -> Vec<usize> {
+    // The position of every element in `x`.
+    let mut positions = vec![0; x.len()];
+    for (i, &element) in x.iter().enumerate() {
+        positions[element] = i;
+    }
+    let in_segment = |element: usize| positions[element] >= start && positions[element] < end;
+    (0..x.len())
+        .map(|i| {
+            if i >= start && i < end {
+                return x[i];
+            }
+            let mut element = y[i];
+            while in_segment(element) {
+                element = y[positions[element]];
+            }
+            element
+        })
+        .collect()
+}
+
+/// Order crossover of `x` and `y`, with the segment from `start` to `end`.
+fn ox(x: &[usize], y: &[usize], start: usize, end: usize) -> Vec<usize> {
+    let len = x.len();
+    let mut taken = vec![false; len];
+    for &element in &x[start..end] {
+        taken[element] = true;
+    }
+    let mut child = x.to_vec();
+    let mut remaining = (0..len).map(|i| y[(end + i) % len]).filter(|&element| !taken[element]);
+    for i in 0..(len - (end - start)) {
+        child[(end + i) % len] = remaining.next().unwrap();
+    }
+    child
+}
+
+/// Cycle crossover of `x` and `y`.
+fn cx(x: &[usize], y: &[usize]) -> Vec<usize> {
+    let mut positions = vec![0; x.len()];
+    for (i, &element) in x.iter().enumerate() {
+        positions[element] = i;
+    }
+    let mut child = vec![usize::MAX; x.len()];
+    let mut from_x = true;
+    for first in 0..x.len() {
+        if child[first] != usize::MAX {
+            continue;
+        }
+        let mut i = first;
+        loop {
+            child[i] = if from_x { x[i] } else { y[i] };
+            i = positions[y[i]];
+            if i == first {
+                break;
+            }
+        }
+        from_x = !from_x;
+    }
+    child
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::{cx, ox, pmx};
+
+    fn is_permutation(x: &Permutation) -> bool {
+        let mut order = x.order().to_vec();
+        order.sort();
+        order == (0..x.len()).collect::<Vec<usize>>()
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_invalid() {
+        Permutation::new(vec![0, 1, 1]);
+    }
+
+    #[test]
+    fn test_operators() {
+                let x = [0, 1, 2, 3, 4, 5, 6, 7, 8];
+        let y = [8, 2, 6, 7, 1, 5, 4, 0, 3];
+        assert_eq!(pmx(&x, &y, 3, 7), vec![8, 2, 1, 3, 4, 5, 6, 0, 7]);
+        assert_eq!(ox(&x, &y, 3, 7), vec![2, 7, 1, 3, 4, 5, 6, 0, 8]);
+        assert_eq!(cx(&x, &y), vec![0, 2, 6, 3, 1, 5, 4, 7, 8]);
+    }
+
+    #[test]
+    fn test_valid() {
+        let mut rng = ::rand::thread_rng();
+        for _ in 0..100 {
+            let x = Permutation::random(20, &mut rng);
+            let y = Permutation::random(20, &mut rng);
+            for &crossover in &[Crossover::PartiallyMapped, Crossover::Order, Crossover::Cycle] {
+                assert!(is_permutation(&x.crossover(&y, crossover, &mut rng)));
+            }
+            assert!(is_permutation(&x.mutate(Mutation::Swap, &mut rng)));
+            assert!(is_permutation(&x.mutate(Mutation::Insert, &mut rng)));
+        }
+        assert_eq!(Permutation::identity(3).distance(&Permutation::new(vec![0, 2, 1])), 2.0);
+    }
+}
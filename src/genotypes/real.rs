@@ -0,0 +1,383 @@
+// file: real.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Vectors of real numbers within bounds, with arithmetic, blend (BLX-α) and simulated
+//! binary (SBX) crossover, and gaussian, uniform and polynomial mutation.
+//!
+//! A `RealVector` is the backbone of a `Phenotype` for continuous optimization: wrap it in
+//! your own type, calculate the fitness value from its genes, and delegate crossover and
+//! mutation to it, like with `genotypes::bitstring::BitString`. Every gene has its own
+//! bounds, which crossover and mutation respect.
+//!
+//! `RealVector::mutate_gaussian` adds noise with a standard deviation per gene, and handles
+//! values outside the bounds by any `Bounding`.
+
+use pheno::Distance;
+use rand::Rng;
+use rand::distributions::{IndependentSample, Normal};
+
+/// A crossover operator for real vectors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Crossover {
+    /// Take a random weighted average of the parents, with the same weight for every gene.
+    Arithmetic,
+    /// Blend crossover (BLX-α): draw every gene uniformly from the interval spanned by the
+    /// parents, extended by the given α times its length on both sides. An α of 0.5 is
+    /// most common.
+    Blend(f64),
+    /// Simulated binary crossover (SBX), with the given distribution index η. Children
+    /// stay closer to their parents for larger η; 2 to 20 is common.
+    Simulated(f64),
+}
+
+/// A mutation operator for real vectors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mutation {
+    /// Add normally distributed noise with standard deviation `sigma` to every gene with
+    /// probability `rate`.
+    Gaussian {
+        /// The probability that a gene is mutated.
+        rate: f64,
+        /// The standard deviation of the noise.
+        sigma: f64,
+    },
+    /// Replace every gene with probability `rate` by a random value within its bounds.
+    Uniform {
+        /// The probability that a gene is mutated.
+        rate: f64,
+    },
+    /// Deb's polynomial mutation: perturb every gene with probability `rate` by a
+    /// polynomial distribution with distribution index `eta`, scaled to its bounds, so it
+    /// never leaves them. Mutated genes stay closer to the original for larger η; 20 is
+    /// common. Together with simulated binary crossover, this is the variation of NSGA-II.
+    Polynomial {
+        /// The probability that a gene is mutated.
+        rate: f64,
+        /// The distribution index η.
+        eta: f64,
+    },
+}
+
+/// How to bring a mutated gene that falls outside its bounds back within them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bounding {
+    /// Move the gene to the nearest bound.
+    Clip,
+    /// Mirror the gene in the bound it crossed, as often as necessary.
+    Reflect,
+    /// Draw new noise until the gene falls within its bounds. After 100 attempts, the gene
+    /// is clipped instead.
+    Resample,
+}
+
+/// A vector of real numbers, each within its own bounds.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RealVector {
+    genes: Vec<f64>,
+    bounds: Vec<(f64, f64)>,
+}
+
+impl RealVector {
+    /// Create a real vector from `genes`, with the lower and upper `bounds` of every gene.
+    /// Genes outside their bounds are clipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `genes` and `bounds` differ in length.
+    pub fn new(genes: Vec<f64>, bounds: Vec<(f64, f64)>) -> RealVector {
+        assert_eq!(genes.len(), bounds.len(), "Every gene needs bounds.");
+        let genes = genes.into_iter().zip(&bounds).map(|(x, &b)| clip(x, b)).collect();
+        RealVector { genes, bounds }
+    }
+
+    /// Create a real vector with a random gene within each of `bounds`.
+    pub fn random<R: Rng>(bounds: Vec<(f64, f64)>, rng: &mut R) -> RealVector {
+        let genes = bounds.iter().map(|&b| uniform(b, rng)).collect();
+        RealVector { genes, bounds }
+    }
+
+    /// Get the genes.
+    pub fn genes(&self) -> &[f64] {
+        &self.genes
+    }
+
+    /// Get the lower and upper bounds of every gene.
+    pub fn bounds(&self) -> &[(f64, f64)] {
+        &self.bounds
+    }
+
+    /// Get the number of genes.
+    pub fn len(&self) -> usize {
+        self.genes.len()
+    }
+
+    /// Check whether there are no genes.
+    pub fn is_empty(&self) -> bool {
+        self.genes.is_empty()
+    }
+
+    /// Create a child of this real vector and `other` by `crossover`. Both must have the same
+    /// bounds.
+    pub fn crossover<R: Rng>(&self, other: &RealVector, crossover: Crossover, rng: &mut R)
+                             -> RealVector {
+        let weight = rng.gen::<f64>();
+        let genes = self.genes
+                        .iter()
+                        .zip(&other.genes)
+                        .zip(&self.bounds)
+                        .map(|((&x, &y), &b)| {
+                            let child = match crossover {
+                                Crossover::Arithmetic => weight * x + (1.0 - weight) * y,
+                                Crossover::Blend(alpha) => {
+                                    let extension = alpha * (x - y).abs();
+                                    uniform((x.min(y) - extension, x.max(y) + extension), rng)
+                                }
+                                Crossover::Simulated(eta) => sbx(x, y, eta, rng),
+                            };
+                            clip(child, b)
+                        })
+                        .collect();
+        RealVector {
+            genes,
+            bounds: self.bounds.clone(),
+        }
+    }
+
+    /// Create a mutated copy of this real vector by `mutation`. Mutated genes are clipped to
+    /// their bounds.
+    pub fn mutate<R: Rng>(&self, mutation: Mutation, rng: &mut R) -> RealVector {
+        let genes = self.genes
+                        .iter()
+                        .zip(&self.bounds)
+                        .map(|(&x, &b)| match mutation {
+                            Mutation::Gaussian { rate, sigma } if rng.gen::<f64>() < rate => {
+                                gaussian(x, sigma, b, Bounding::Clip, rng)
+                            }
+                            Mutation::Uniform { rate } if rng.gen::<f64>() < rate => {
+                                uniform(b, rng)
+                            }
+                            Mutation::Polynomial { rate, eta } if rng.gen::<f64>() < rate => {
+                                polynomial(x, eta, b, rng)
+                            }
+                            _ => x,
+                        })
+                        .collect();
+        RealVector {
+            genes,
+            bounds: self.bounds.clone(),
+        }
+    }
+
+    /// Create a mutated copy of this real vector by adding normally distributed noise to
+    /// every gene with probability `rate`. The noise of every gene has its own standard
+    /// deviation in `sigmas`, so genes on different scales can be mutated together. Mutated
+    /// genes outside their bounds are handled by `bounding`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sigmas` does not have a standard deviation for every gene.
+    pub fn mutate_gaussian<R: Rng>(&self,
+                                   rate: f64,
+                                   sigmas: &[f64],
+                                   bounding: Bounding,
+                                   rng: &mut R)
+                                   -> RealVector {
+        assert_eq!(sigmas.len(), self.len(), "Every gene needs a standard deviation.");
+        let genes = self.genes
+                        .iter()
+                        .zip(sigmas)
+                        .zip(&self.bounds)
+                        .map(|((&x, &sigma), &b)| if rng.gen::<f64>() < rate {
+                            gaussian(x, sigma, b, bounding, rng)
+                        } else {
+                            x
+                        })
+                        .collect();
+        RealVector {
+            genes,
+            bounds: self.bounds.clone(),
+        }
+    }
+}
+
+impl Distance for RealVector {
+    /// The Euclidean distance between the genes.
+    fn distance(&self, other: &RealVector) -> f64 {
+        self.genes
+            .iter()
+            .zip(&other.genes)
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+/// Clip `x` to `bounds`.
+fn clip(x: f64, bounds: (f64, f64)) -> f64 {
+    x.max(bounds.0).min(bounds.1)
+}
+
+/// Add normally distributed noise with standard deviation `sigma` to `x`, and bring the
+/// result within `bounds` by `bounding`.
+fn gaussian<R: Rng>(x: f64, sigma: f64, bounds: (f64, f64), bounding: Bounding, rng: &mut R)
+                    -> f64 {
+    let normal = Normal::new(0.0, sigma);
+    let (low, high) = bounds;
+    match bounding {
+        Bounding::Clip => clip(x + normal.ind_sample(rng), bounds),
+        Bounding::Reflect => {
+            let width = high - low;
+            if width <= 0.0 {
+                return low;
+            }
+            // Reflecting in both bounds repeats with a period of twice the width.
+            let offset = (x + normal.ind_sample(rng) - low).rem_euclid(2.0 * width);
+            if offset <= width { low + offset } else { high - (offset - width) }
+        }
+        Bounding::Resample => {
+            for _ in 0..100 {
+                let y = x + normal.ind_sample(rng);
+                if y >= low && y <= high {
+                    return y;
+                }
+            }
+            clip(x + normal.ind_sample(rng), bounds)
+        }
+    }
+}
+
+/// Draw a random value within `bounds`.
+fn uniform<R: Rng>(bounds: (f64, f64), rng: &mut R) -> f64 {
+    bounds.0 + rng.gen::<f64>() * (bounds.1 - bounds.0)
+}
+
+/// Polynomial mutation of `x` within `bounds`, with distribution index `eta`.
+fn polynomial<R: Rng>(x: f64, eta: f64, bounds: (f64, f64), rng: &mut R) -> f64 {
+    let (low, high) = bounds;
+    let width = high - low;
+    if width <= 0.0 {
+        return x;
+    }
+    let u = rng.gen::<f64>();
+    let power = 1.0 / (eta + 1.0);
+    let delta = if u < 0.5 {
+        let rest = 1.0 - (x - low) / width;
+        (2.0 * u + (1.0 - 2.0 * u) * rest.powf(eta + 1.0)).powf(power) - 1.0
+    } else {
+        let rest = 1.0 - (high - x) / width;
+        1.0 - (2.0 * (1.0 - u) + 2.0 * (u - 0.5) * rest.powf(eta + 1.0)).powf(power)
+    };
+    clip(x + delta * width, bounds)
+}
+
+/// Draw one of the two children of simulated binary crossover of `x` and `y`, with
+/// distribution index `eta`.
+fn sbx<R: Rng>(x: f64, y: f64, eta: f64, rng: &mut R) -> f64 {
+    let u = rng.gen::<f64>();
+    let beta = if u <= 0.5 {
+        (2.0 * u).powf(1.0 / (eta + 1.0))
+    } else {
+        (1.0 / (2.0 * (1.0 - u))).powf(1.0 / (eta + 1.0))
+    };
+    let sign = if rng.gen() { 1.0 } else { -1.0 };
+    0.5 * ((x + y) + sign * beta * (x - y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::gaussian;
+
+    fn bounds() -> Vec<(f64, f64)> {
+        vec![(-1.0, 1.0); 10]
+    }
+
+    fn within_bounds(x: &RealVector) -> bool {
+        x.genes().iter().zip(x.bounds()).all(|(&g, &(low, high))| g >= low && g <= high)
+    }
+
+    #[test]
+    fn test_new_clips() {
+        let x = RealVector::new(vec![-2.0, 0.5, 2.0], vec![(-1.0, 1.0); 3]);
+        assert_eq!(x.genes(), &[-1.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_crossover() {
+        let mut rng = ::rand::thread_rng();
+        let x = RealVector::new(vec![-0.5; 10], bounds());
+        let y = RealVector::new(vec![0.5; 10], bounds());
+        for _ in 0..100 {
+            // Arithmetic crossover uses the same weight for every gene.
+            let child = x.crossover(&y, Crossover::Arithmetic, &mut rng);
+            assert!(child.genes().iter().all(|&g| g == child.genes()[0]));
+            assert!((-0.5..=0.5).contains(&child.genes()[0]));
+            let child = x.crossover(&y, Crossover::Blend(0.5), &mut rng);
+            assert!(child.genes().iter().all(|g| (-1.0..=1.0).contains(g)));
+            let child = x.crossover(&y, Crossover::Blend(0.0), &mut rng);
+            assert!(child.genes().iter().all(|g| (-0.5..=0.5).contains(g)));
+            let child = x.crossover(&y, Crossover::Simulated(2.0), &mut rng);
+            assert!(within_bounds(&child));
+        }
+        // With a large distribution index, children of SBX are close to the parents.
+        let child = x.crossover(&y, Crossover::Simulated(1000.0), &mut rng);
+        assert!(child.genes().iter().all(|&g| (g.abs() - 0.5).abs() < 0.05));
+    }
+
+    #[test]
+    fn test_mutate() {
+        let mut rng = ::rand::thread_rng();
+        let x = RealVector::new(vec![0.9; 10], bounds());
+        let unchanged = x.mutate(Mutation::Gaussian { rate: 0.0, sigma: 1.0 }, &mut rng);
+        assert_eq!(unchanged, x);
+        for _ in 0..100 {
+            let mutated = x.mutate(Mutation::Gaussian { rate: 1.0, sigma: 1.0 }, &mut rng);
+            assert!(within_bounds(&mutated));
+            let mutated = x.mutate(Mutation::Uniform { rate: 1.0 }, &mut rng);
+            assert!(within_bounds(&mutated));
+            let mutated = x.mutate(Mutation::Polynomial { rate: 1.0, eta: 20.0 }, &mut rng);
+            assert!(within_bounds(&mutated));
+        }
+        // With a large distribution index, polynomial mutation stays close to the original.
+        let mutated = x.mutate(Mutation::Polynomial { rate: 1.0, eta: 1000.0 }, &mut rng);
+        assert!(mutated.genes().iter().all(|&g| (g - 0.9).abs() < 0.05));
+        assert_eq!(x.distance(&RealVector::new(vec![0.9; 10], bounds())), 0.0);
+    }
+
+    #[test]
+    fn test_mutate_gaussian() {
+        let mut rng = ::rand::thread_rng();
+        let x = RealVector::new(vec![0.9; 10], bounds());
+        let mut sigmas = vec![0.0; 10];
+        sigmas[0] = 1.0;
+        for &bounding in &[Bounding::Clip, Bounding::Reflect, Bounding::Resample] {
+            for _ in 0..100 {
+                let mutated = x.mutate_gaussian(1.0, &sigmas, bounding, &mut rng);
+                assert!(within_bounds(&mutated));
+                // Only the gene with a standard deviation changes.
+                assert!(mutated.genes()[1..].iter().all(|&g| (g - 0.9).abs() < 1e-9));
+            }
+        }
+        // Genes are mirrored in the bounds they cross, as often as necessary.
+        assert!((gaussian(0.9, 0.0, (-1.0, 1.0), Bounding::Reflect, &mut rng) - 0.9).abs()
+                < 1e-9);
+        assert!((gaussian(1.5, 0.0, (-1.0, 1.0), Bounding::Reflect, &mut rng) - 0.5).abs()
+                < 1e-9);
+        assert!((gaussian(-3.5, 0.0, (-1.0, 1.0), Bounding::Reflect, &mut rng) - 0.5).abs()
+                < 1e-9);
+    }
+}
@@ -35,31 +35,67 @@
 //! # Features
 //! ## Available Simulators
 //!
-//! There is currently only one, sequential, simulator. This simulator will run
-//! the genetic algorithm on a single thread.
+//! There are two simulators available:
+//!
+//! * `seq::Simulator` runs the genetic algorithm on a single thread.
+//! * `par::Simulator` evaluates fitness values, runs selection tournaments and creates
+//!   offspring on multiple threads. It has the same builder API as the sequential
+//!   simulator, so switching between the two is a one line change. Your `Phenotype` needs
+//!   to be `Send + Sync` to use it. The Tournament, Truncation and Crowded Tournament
+//!   selectors run in parallel, and other selectors select on a single thread.
+//!
+//! If only fitness evaluation is expensive, you can also keep the sequential simulator
+//! and call `set_parallel_evaluation()` on its builder.
 //!
 //! ## Available Selection Types
 //!
-//! There are currently four selection types available:
+//! There are currently twenty-three selection types available:
 //!
 //! * Maximize
 //! * Tournament
 //! * Stochastic
 //! * Roulette
+//! * Linear Rank
+//! * Exponential Rank
+//! * Boltzmann
+//! * Truncation
+//! * Random
+//! * Lexicase
+//! * Sigma Scaling
+//! * Linear Scaling
+//! * Combined
+//! * Stochastic Remainder
+//! * Age-Fitness
+//! * Pareto
+//! * Crowded Tournament
+//! * Strength
+//! * Decomposition
+//! * Sharing
+//! * Clearing
+//! * Species
+//! * Disassortative
 //!
 //! There is a short explanation for each of these below. For more information, look at the
-//! documentation of individual selectors.
+//! documentation of individual selectors. Your own selectors must implement `Clone` as well
+//! as `sim::select::Selector`.
 //!
 //! ### Maximize
 //!
 //! Maximize takes 1 parameter: the count. This is half the number of parents
 //! that will be selected. Selection happens by taking the top `count` individuals,
 //! ranked by fitness. The resulting number of parents is `count`.
+//! Despite its name, it takes the lowest fitness values when minimizing.
 //!
 //! ### Tournament
 //!
 //! Tournament takes 2 parameters: the number of tournaments (`count`) and `participators`, which indicates how
 //! many phenotypes participate in a tournament. The resulting number of parents is `count`.
+//! `with_probability` lets weaker participants win some tournaments, for a lower
+//! selection pressure.
+//! `without_replacement` draws distinct participants for every tournament.
+//! `with_separate_tournaments` runs a tournament for every parent, instead of mating the best
+//! two participants of a single tournament.
+//! `in_parallel` runs the tournaments on multiple threads.
 //!
 //! ### Stochastic
 //!
@@ -68,12 +104,332 @@
 //! ### Roulette
 //!
 //! Roulette takes 1 parameter: the count. The resulting number of parents is `count`.
+//! Parents are selected with a probability proportional to their fitness value. Negative
+//! fitness values are shifted, and fitness values are inverted when minimizing. Infinitely
+//! bad fitness values, such as penalties, are never selected.
+//!
+//! ### Linear Rank
+//!
+//! Linear Rank takes 2 parameters: the count and the selection `pressure`, between 1 and 2.
+//! Parents are selected with a probability that increases linearly with their rank, from
+//! `2 - pressure` times the average for the worst to `pressure` times it for the best.
+//! The resulting number of parents is `count`.
+//!
+//! ### Exponential Rank
+//!
+//! Exponential Rank takes 2 parameters: the count and a `base`, at most 1. Every parent is
+//! selected with `base` times the probability of the next better one, which favours the
+//! best phenotypes more than Linear Rank. The resulting number of parents is `count`.
+//!
+//! ### Boltzmann
+//!
+//! Boltzmann takes 2 parameters: the count and a `Temperature` schedule. Parents are
+//! selected with a probability proportional to `exp(fitness / temperature)`. Lowering the
+//! temperature over the generations, linearly, exponentially or by your own function,
+//! shifts from exploration to exploitation. The schedule follows the generations of the
+//! `Simulator`. The resulting number of parents is `count`.
+//!
+//! ### Truncation
+//!
+//! Truncation takes 2 parameters: the count and a `fraction`. It mates random pairs from the
+//! best performing `fraction` of the population, following the `FitnessType`. The resulting
+//! number of parents is `count`. `in_parallel` draws the pairs on multiple threads.
+//!
+//! ### Random
+//!
+//! Random takes 1 parameter: the count. It mates random pairs of phenotypes, regardless of
+//! their fitness values, which makes it a baseline for other selectors. The resulting number
+//! of parents is `count`.
+//!
+//! ### Lexicase
+//!
+//! Lexicase takes 1 parameter: the count. Every objective of the fitness values is a test
+//! case. Each parent is selected by going through the cases in random order, and keeping
+//! only the best phenotypes on every case. The resulting number of parents is `count`.
+//! `with_epsilon` keeps phenotypes within the median absolute deviation of the best on every
+//! case, which suits continuous errors.
+//!
+//! ### Sigma Scaling
+//!
+//! Sigma Scaling takes 1 parameter: a fitness proportionate selector, such as Roulette. It
+//! rescales fitness values by how many standard deviations they are from the mean before
+//! the other selector selects, which keeps the selection pressure steady during a run.
+//!
+//! ### Linear Scaling
+//!
+//! Linear Scaling takes 2 parameters: a fitness proportionate selector and a `multiple`. It
+//! rescales fitness values linearly, keeping their mean, so that the best phenotype is
+//! expected to be selected `multiple` times as often as an average one.
+//!
+//! ### Combined
+//!
+//! Combined takes 1 parameter: the count. Add selectors with weights by `with_selector`.
+//! Every parent pair is selected by one of them, chosen by weight, such as 80% Tournament
+//! and 20% Random. The resulting number of parents is `count`.
+//!
+//! ### Stochastic Remainder
+//!
+//! Stochastic Remainder takes 1 parameter: the count. Every phenotype is selected as often
+//! as its expected number of copies under Roulette, rounded down, and the remaining parents
+//! are selected by roulette over the fractions. The resulting number of parents is `count`.
+//!
+//! ### Age-Fitness
+//!
+//! Age-Fitness takes 1 parameter: the count. It runs binary tournaments, won by the
+//! phenotype in the better non-dominated front of fitness and age, where younger is better.
+//! This gives new phenotypes a chance against old, well performing ones. The resulting
+//! number of parents is `count`.
+//!
+//! ### Pareto
+//!
+//! Pareto takes 1 parameter: the count. Binary tournaments are won by the phenotype in the
+//! best non-dominated front. The resulting number of parents is `count`.
+//!
+//! ### Crowded Tournament
+//!
+//! Crowded Tournament takes 1 parameter: the count. Like Pareto, but ties within a front
+//! are won by the phenotype with the largest crowding distance. The resulting number of
+//! parents is `count`, which may exceed the population size. With `with_tolerance`, it
+//! preserves diversity for a single objective, by breaking near ties in fitness with the
+//! crowding distance. `in_parallel` runs the tournaments on multiple threads.
+//!
+//! ### Strength
+//!
+//! Strength takes 1 parameter: the count. Binary tournaments are won by the phenotype with
+//! the best strength fitness of SPEA2. The resulting number of parents is `count`, which may
+//! exceed the population size.
+//!
+//! ### Decomposition
+//!
+//! Decomposition takes 1 parameter: a `sim::pareto::Decomposition` into subproblems.
+//! For every subproblem, two parents are selected from its neighbourhood. The resulting
+//! number of parents is twice the number of subproblems.
+//!
+//! ### Sharing
+//!
+//! Sharing takes 3 parameters: another selector, a sharing `radius` and a `distance`
+//! function. It derates the fitness values of phenotypes in crowded niches, and then lets
+//! the other selector select. This helps to find multiple optima of multimodal functions.
+//!
+//! ### Clearing
+//!
+//! Clearing takes 4 parameters: another selector, a niche `radius`, a `capacity` and a
+//! `distance` function. Only the best `capacity` phenotypes of every niche keep their
+//! fitness values before the other selector selects. It is often stronger than Sharing.
+//!
+//! ### Species
+//!
+//! Species takes 3 parameters: the count, a compatibility `threshold` and a `distance`
+//! function. It clusters the population into species with `sim::species`, allocates parent
+//! pairs to every species by its shared fitness, and runs binary tournaments within every
+//! species, as in NEAT. The resulting number of parents is `count`.
+//! `with_interspecies_rate` lets some pairs mate across species.
+//!
+//! ### Disassortative
+//!
+//! Disassortative takes 3 parameters: another selector, a `sample` size and a `distance`
+//! function. It keeps the first parent of every pair selected by the other selector, and
+//! mates it with the most distant of `sample` random selected parents.
+//!
+//! ## Available Replacement Policies
+//!
+//! A replacement policy decides which phenotypes are killed off to make room for
+//! the children of a step. It can be set by calling `set_replacement` on the
+//! `SimulatorBuilder`. There are currently eight replacement policies available:
+//!
+//! * Stochastic (the default): kills off phenotypes using stochastic universal sampling.
+//! * Random: kills off phenotypes chosen uniformly at random.
+//! * Worst: kills off the worst performing phenotypes.
+//! * Age: kills off the phenotypes that have survived the most steps.
+//! * Crowding: keeps the best non-dominated fronts of the population and the children.
+//! * Truncation: keeps a spread out archive of the best non-dominated phenotypes.
+//! * Decomposition: lets children replace the solutions of the subproblems they improve on.
+//! * Restricted Tournament: lets children replace the most similar phenotype of a random
+//!   window if they perform better, which keeps several niches.
+//!
+//! ## Fitness Functions
+//!
+//! The type of the fitness values is chosen with `Phenotype::Fitness`. This is usually
+//! `f64`, but any `pheno::Fitness` type can be used, such as exact integers or tuples,
+//! which are compared lexicographically. `pheno::Lexicographic` compares any number of
+//! objectives in order of priority, such as "minimize errors, then minimize size".
+//!
+//! For problems with several objectives, such as cost versus quality, use
+//! `pheno::Objectives`, which compares its objectives by Pareto dominance. Select parents
+//! with the Pareto selector, and call `pareto_front()` on the `Simulator` instead of
+//! `get()` to retrieve the best trade-offs. `sim::pareto` provides non-dominated sorting.
+//! Calling `set_nsga2()` or `set_spea2()` on a `SimulatorBuilder` that has a population
+//! runs NSGA-II or SPEA2, so the two can be compared on the same problem.
+//! For many objectives, `set_moead(decomposition)` runs MOEA/D instead.
+//! To compare runs quantitatively, `hypervolume(reference)` on the `Simulator` measures
+//! the volume of objective space dominated by the Pareto front.
+//! To use multi-objective phenotypes with any other selector, `pheno::WeightedSum`
+//! scalarizes their objectives by a weighted sum.
+//!
+//! By default, fitness values are calculated by `Phenotype::fitness()`. If calculating
+//! the fitness requires shared context, such as a data set, you can instead implement the
+//! `pheno::FitnessFunction` trait (or use a closure) and pass it to `set_fitness_function`
+//! on the `SimulatorBuilder`. The fitness value of every phenotype is calculated only once.
+//!
+//! To evaluate all phenotypes of a step in one call, for example to vectorize the
+//! calculation, implement `sim::eval::BatchEvaluator` and pass it to `set_batch_evaluator`.
+//! With the `async` feature enabled, `sim::eval::AsyncEvaluator` turns a fitness function
+//! that returns futures into a `BatchEvaluator` that awaits them concurrently, up to a
+//! limit, on the `sim::eval::Executor` of your runtime.
+//! With the `serde` feature enabled, `sim::eval::ProcessEvaluator` evaluates phenotypes in
+//! a child process, exchanging JSON lines over its standard input and output.
+//! With the `distributed` feature enabled, `sim::eval::DistributedEvaluator` farms out
+//! evaluations to worker nodes over TCP, retrying on another worker when one fails.
+//! `sim::eval::serve_worker` runs such a worker.
+//!
+//! For problems with constraints, implement `pheno::Constrained` and call
+//! `set_constrained()` on the `SimulatorBuilder`. Feasible phenotypes then always perform
+//! better than infeasible ones, without adding penalties to the fitness value.
+//! Alternatively, `set_repair` fixes infeasible children before they are evaluated.
+//!
+//! ## Monitoring
+//!
+//! Calling `set_step_callback` on the `SimulatorBuilder` registers a closure that is called
+//! after every step with a `sim::StepInfo`, containing the generation number, the best and
+//! mean fitness values and the population. This allows logging progress while using `run()`.
+//! Passing `sim::print_progress` as the callback shows the generation, the best fitness
+//! value and the estimated time remaining on standard error.
+//! For more detail, `add_observer` registers a `sim::Observer`, which is notified of every
+//! `sim::Event`, such as the start of a generation or the creation of offspring.
+//! Calling `set_collect_stats()` makes the `Simulator` record `sim::GenerationStats`, such as
+//! the best, mean and median fitness values, after every step. They can be retrieved by
+//! calling `stats_history()` on the `Simulator`. The best fitness value of every step is
+//! always recorded, and can be retrieved by calling `history()`.
+//! If your `Phenotype` implements `pheno::Distance`, `set_track_diversity` adds the mean
+//! distance between phenotypes to the statistics, which reveals a collapse of diversity.
+//! With the `csv` feature enabled, `write_stats_csv` writes the statistics in CSV form,
+//! ready to be analyzed with other tools.
+//! With the `log` feature enabled, the `Simulator` emits `log` records when a generation
+//! completes (`debug`), when it stops (`info`) and when it fails (`error`), for example
+//! because of an invalid selector.
+//! With the `tracing` feature enabled, every step runs in a `tracing` span called `step`,
+//! with nested spans for `selection`, `breeding`, `evaluation` and `replacement`, which
+//! shows which phase dominates the running time.
+//! With the `metrics` feature enabled, the `Simulator` publishes the counters
+//! `rsgenetic_generations_total` and `rsgenetic_evaluations_total`, the gauge
+//! `rsgenetic_best_fitness` and the histogram `rsgenetic_step_duration_seconds` through
+//! the `metrics` facade, so that they can be scraped by Prometheus or StatsD.
+//! With the `plotting` feature enabled, `plot_history("run.png")` renders the best fitness
+//! value of every step as a PNG or SVG image, for quick visual feedback on convergence.
+//! Calling `iter_steps()` on a `Simulator` returns an iterator that makes a step for every
+//! item, yielding its `sim::GenerationStats`, so that iterator adapters such as `take` can
+//! be used for control flow. `run_until` runs until a closure over these statistics returns
+//! `true`, for custom stopping rules.
+//! Calling `report()` on a `Simulator` returns a `sim::Report`, summarizing its settings,
+//! seed, number of iterations, running time, stopping reason and best fitness value.
+//! With the `serde` feature enabled, it can be archived as a single JSON document.
 //!
 //! ## Early Stopping
 //!
 //! If you wish, you can stop early if the fitness value of the best performing Phenotype
-//! doesn't improve by a large amount for a number of iterations. This can be done by calling the
-//! `set_early_stop(delta: f64, n_iters: u32)` function on the `SimulatorBuilder`.
+//! doesn't improve on the best value found so far by a large amount for a number of
+//! iterations. This can be done by calling the `set_early_stop(delta: f64, n_iters: u32)`
+//! function on the `SimulatorBuilder`.
+//! If fitness values vary by orders of magnitude, `set_early_stop_relative` interprets
+//! `delta` as a fraction of the best fitness value instead.
+//! To monitor the mean or median fitness value of the population instead, call
+//! `set_early_stop_metric` with a `sim::EarlyStopMetric`.
+//! Calling `set_restart_on_stagnation` restarts the search instead, keeping the best
+//! phenotypes and replacing the rest with new random phenotypes.
+//! Calling `set_hypermutation` temporarily mutates children several times instead,
+//! to escape local optima.
+//!
+//! To stay within a time budget, call `set_max_duration(duration: Duration)`. The
+//! `Simulator` then stops once the time spent running exceeds `duration`.
+//! If the optimal fitness value is known, call `set_target_fitness(target: f64)` to stop
+//! as soon as a phenotype reaches it.
+//!
+//! Other stopping rules can be expressed with the `sim::stop::StoppingCondition` trait,
+//! which is also implemented by closures. Conditions such as `MaxDuration` and `Stagnation`
+//! can be combined with `And` and `Or`, and any number of them can be added by calling
+//! `add_stopping_condition` on the `SimulatorBuilder`. The `Simulator` stops as soon as
+//! one of them is met.
+//!
+//! ## Checkpointing
+//!
+//! With the `serde` feature enabled, `save_checkpoint(path)` on a `Simulator` saves its
+//! population, number of iterations, early-stopping state, history and the state of its
+//! random number generator to a file, if your `Phenotype` implements `Serialize`. Calling
+//! `load_checkpoint(path)` on a `SimulatorBuilder` resumes the run from there, for example
+//! after a crash. A seeded run resumed this way continues exactly like the original one.
+//! `sim::Checkpoint` can also be serialized in any other `serde` format.
+//!
+//! To continue a run in two directions from the same point, call `fork()` on the
+//! `Simulator`. This returns a `SimulatorBuilder` for a copy of it, without its observers,
+//! stopping conditions and step callback.
+//!
+//! ## Elitism
+//!
+//! Phenotypes are killed off at random to make room for children, so the best phenotype
+//! found so far can be lost from the population. `get()` still returns it, but it no longer
+//! takes part in evolution. Calling `set_elitism(n: usize)` on the `SimulatorBuilder`
+//! guarantees that the `n` best performing phenotypes survive every step.
+//! Alternatively, `set_hall_of_fame(size: usize)` keeps an archive of the best distinct
+//! phenotypes ever found, which can be retrieved by calling `hall_of_fame()` on the
+//! `Simulator`. For a shortlist of candidates rather than a single winner, call
+//! `get_n_best(k: usize)` on the `Simulator`.
+//!
+//! ## Steady-State Evolution
+//!
+//! By default, a part of the population is replaced in every step. Calling
+//! `set_steady_state()` on the `SimulatorBuilder` instead creates a single child
+//! per step, which replaces the worst performing phenotype. The selector still selects
+//! all of its parents in every step, so use one with a `count` of 2.
+//!
+//! ## Random Immigrants
+//!
+//! Calling `set_immigrants(fraction: f64, generator)` on the `SimulatorBuilder` replaces
+//! the worst performing part of the population with new phenotypes from `generator` in
+//! every step, which keeps the population diverse.
+//!
+//! ## Duplicate Elimination
+//!
+//! If your `Phenotype` implements `PartialEq`, calling `set_eliminate_duplicates()` on the
+//! `SimulatorBuilder` mutates children that equal another phenotype again before they are
+//! evaluated. `set_eliminate_duplicates_with(generator)` replaces them with new phenotypes.
+//!
+//! ## CHC
+//!
+//! If your `Phenotype` implements `Distance`, calling `set_chc(threshold, cataclysm)` on the
+//! `SimulatorBuilder` runs the CHC algorithm: only parents that differ by more than an
+//! incest threshold are mated, the best of the parents and children survive, and the
+//! population is restarted around its best phenotype when it converges. The `sim::chc`
+//! module provides HUX crossover and this cataclysmic mutation for bit strings.
+//!
+//! ## Reproducible Runs
+//!
+//! Calling `set_seed(seed: u64)` on the `SimulatorBuilder` seeds the random number generator
+//! of the `Simulator`, so that runs with the same seed make the same random decisions.
+//! `set_rng` accepts any other random number generator, such as a fast `XorShiftRng`.
+//! Simulators are generic over the type of their random number generator, so it does
+//! not cost a virtual call for every random number.
+//! If your `Phenotype` needs randomness for crossover or mutation, implement
+//! `crossover_with_rng` and `mutate_with_rng`, which receive the random number generator
+//! of the simulator, instead of using `rand::thread_rng()`.
+//!
+//! ## Genotypes
+//!
+//! The `genotypes` module provides common representations with their crossover and
+//! mutation operators, to build your `Phenotype` on. `genotypes::bitstring::BitString`
+//! supports one-point, two-point and uniform crossover, and bit flip mutation.
+//! `genotypes::real::RealVector` keeps every gene within its bounds, and supports
+//! arithmetic, blend and simulated binary crossover, and gaussian, uniform and polynomial
+//! mutation.
+//! Its `mutate_gaussian` takes a standard deviation per gene, and clips, reflects or
+//! resamples genes that fall outside their bounds.
+//! `genotypes::permutation::Permutation` supports partially mapped, order and cycle
+//! crossover, and swap and insert mutation, which all keep every element exactly once.
+//!
+//! ## Neuroevolution
+//!
+//! With the `neat` feature enabled, the `neat` module evolves neural networks with NEAT.
+//! A `neat::Genome` is a `Phenotype`, so it runs on the regular simulators. Select its
+//! parents with the Species selector, using `neat::distance` as compatibility distance.
 //!
 //! # Examples
 //!
@@ -88,6 +444,8 @@
 //!
 //! // Implement the Phenotype trait.
 //! impl pheno::Phenotype for Test {
+//!     type Fitness = f64;
+//!
 //!     fn fitness(&self) -> f64 {
 //!         (self.i - 0).abs() as f64
 //!     }
@@ -108,25 +466,30 @@
 //!
 //! ## Running a Simulation
 //!
+//! A `SimulatorBuilder` can only `build()` a `Simulator` once a population has been set,
+//! so forgetting the population is a compile error. `build()` checks the other settings
+//! before any step is made, and returns a `BuildError` if, for example, the population
+//! is empty or the selector can not be used with a population of its size.
+//!
 //! ```ignore
 //! // Generate a random population.
-//! let mut tests: Vec<Box<Test>> = Vec::new();
+//! let mut tests: Vec<Test> = Vec::new();
 //! for i in 0..100 {
-//!     tests.push(Box::new(Test { i: i + 10 }));
+//!     tests.push(Test { i: i + 10 });
 //! }
 //! // Create a simulator using a builder.
-//! let mut s = *seq::Simulator::builder()
-//!                   .set_population(&tests)
-//!                   .set_selector(Box::new(sim::select::TournamentSelector::new(4,4)))
-//!                   .set_max_iters(1000)
-//!                   .set_fitness_type(sim::FitnessType::Minimize)
-//!                   .build();
+//! let mut s = seq::Simulator::builder()
+//!                  .set_population(tests)
+//!                  .set_selector(Box::new(sim::select::TournamentSelector::new(4,4)))
+//!                  .set_max_iters(1000)
+//!                  .set_fitness_type(sim::FitnessType::Minimize)
+//!                  .build().unwrap();
 //! // We can now run the simulator.
 //! s.run();
 //! // This will fail if the result was an error:
 //! let best = s.get().unwrap();
 //! // For this simple example, we should always get 0.
-//! assert!((*best).i == 0);
+//! assert!(best.i == 0);
 //! // We can also get the time spent running:
 //! let time = match s.time() {
 //!     Some(x) => x, // Contains the time in ns
@@ -136,10 +499,34 @@
 
 #![warn(missing_docs)]
 
+#[cfg(feature = "csv")]
+extern crate csv;
+#[cfg(feature = "async")]
+extern crate futures;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
+#[cfg(feature = "metrics")]
+extern crate metrics;
+#[cfg(feature = "plotting")]
+extern crate plotters;
 extern crate rand;
+extern crate rayon;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
 extern crate time;
+#[cfg(feature = "tracing")]
+extern crate tracing;
 
 /// Contains the definition of a Phenotype.
 pub mod pheno;
 /// Contains implementations of Simulators, which can run genetic algorithms.
 pub mod sim;
+/// Contains genotypes to build Phenotypes on, with their crossover and mutation operators.
+pub mod genotypes;
+/// Contains NEAT, which evolves the structure and weights of neural networks.
+#[cfg(feature = "neat")]
+pub mod neat;
@@ -0,0 +1,544 @@
+// file: neat.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! NEAT: NeuroEvolution of Augmenting Topologies.
+//!
+//! A `Genome` encodes a feed-forward neural network as a list of connections between
+//! nodes. Networks start out minimal, with every input connected to every output, and
+//! grow through structural mutations that add connections and split connections with new
+//! hidden nodes.
+//!
+//! Every connection carries a historical marking, its innovation number. The same
+//! structural mutation always gets the same innovation number, so crossover can line up
+//! the connections of two genomes, and the compatibility `distance` between genomes can
+//! cluster the population into species with `sim::select::SpeciesSelector`.
+//!
+//! ```ignore
+//! let config = neat::Config::new(2, 1, xor_fitness);
+//! let population = config.population(150, &mut rand::thread_rng());
+//! let mut s = seq::Simulator::builder()
+//!                  .set_population(population)
+//!                  .set_selector(Box::new(SpeciesSelector::new(100, 3.0, neat::distance)))
+//!                  .set_replacement(Box::new(WorstReplacement::new()))
+//!                  .build().unwrap();
+//! s.run();
+//! ```
+
+use pheno::Phenotype;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// The historical markings handed out so far.
+#[derive(Debug)]
+struct Innovations {
+    /// The innovation number of the connection between every pair of nodes.
+    connections: HashMap<(usize, usize), usize>,
+    /// The hidden node that splits the connection with every innovation number.
+    splits: HashMap<usize, usize>,
+    next_node: usize,
+}
+
+impl Innovations {
+    /// Get the innovation number of the connection from `from` to `to`.
+    fn connection(&mut self, from: usize, to: usize) -> usize {
+        let next = self.connections.len();
+        *self.connections.entry((from, to)).or_insert(next)
+    }
+
+    /// Get the hidden node that splits the connection with innovation number `innovation`.
+    fn split(&mut self, innovation: usize) -> usize {
+        if let Some(&node) = self.splits.get(&innovation) {
+            return node;
+        }
+        let node = self.next_node;
+        self.next_node += 1;
+        self.splits.insert(innovation, node);
+        node
+    }
+}
+
+/// The settings of a NEAT run, shared by all genomes.
+///
+/// The nodes of every network are numbered: first the inputs, then a bias node with the
+/// constant value 1, then the outputs, and finally the hidden nodes.
+#[derive(Debug)]
+pub struct Config {
+    inputs: usize,
+    outputs: usize,
+    fitness: fn(&Genome) -> f64,
+    weight_mutation_rate: f64,
+    weight_perturbation: f64,
+    add_connection_rate: f64,
+    add_node_rate: f64,
+    excess_coefficient: f64,
+    disjoint_coefficient: f64,
+    weight_coefficient: f64,
+    innovations: Mutex<Innovations>,
+}
+
+impl Config {
+    /// Create the settings for networks with `inputs` inputs and `outputs` outputs, of
+    /// which the fitness values are calculated by `fitness`.
+    pub fn new(inputs: usize, outputs: usize, fitness: fn(&Genome) -> f64) -> Config {
+        Config {
+            inputs,
+            outputs,
+            fitness,
+            weight_mutation_rate: 0.8,
+            weight_perturbation: 0.5,
+            add_connection_rate: 0.05,
+            add_node_rate: 0.03,
+            excess_coefficient: 1.0,
+            disjoint_coefficient: 1.0,
+            weight_coefficient: 0.4,
+            innovations: Mutex::new(Innovations {
+                connections: HashMap::new(),
+                splits: HashMap::new(),
+                next_node: inputs + 1 + outputs,
+            }),
+        }
+    }
+
+    /// Set the probability that a weight is mutated, and the largest change of a mutated
+    /// weight. One in ten mutated weights is replaced by a new random weight instead.
+    /// The defaults are 0.8 and 0.5.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn with_weight_mutation(mut self, rate: f64, perturbation: f64) -> Config {
+        self.weight_mutation_rate = rate;
+        self.weight_perturbation = perturbation;
+        self
+    }
+
+    /// Set the probabilities that a mutation adds a connection, and that it adds a node.
+    /// The defaults are 0.05 and 0.03.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn with_structural_mutation(mut self, add_connection: f64, add_node: f64) -> Config {
+        self.add_connection_rate = add_connection;
+        self.add_node_rate = add_node;
+        self
+    }
+
+    /// Set the coefficients of the compatibility distance for excess connections, disjoint
+    /// connections and the mean weight difference of matching connections. The defaults
+    /// are 1, 1 and 0.4.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn with_compatibility(mut self, excess: f64, disjoint: f64, weight: f64) -> Config {
+        self.excess_coefficient = excess;
+        self.disjoint_coefficient = disjoint;
+        self.weight_coefficient = weight;
+        self
+    }
+
+    /// Create an initial population of `size` minimal genomes with random weights, which
+    /// connect every input and the bias to every output.
+    pub fn population<R: Rng>(self, size: usize, rng: &mut R) -> Vec<Genome> {
+        let config = Arc::new(self);
+        let mut links = Vec::new();
+        {
+            let mut innovations = config.innovations.lock().unwrap();
+            for from in 0..(config.inputs + 1) {
+                for to in (config.inputs + 1)..(config.inputs + 1 + config.outputs) {
+                    links.push((innovations.connection(from, to), from, to));
+                }
+            }
+        }
+        (0..size).map(|_| {
+                     let connections = links.iter()
+                                            .map(|&(innovation, from, to)| {
+                                                Connection {
+                                                    innovation,
+                                                    from,
+                                                    to,
+                                                    weight: rng.gen_range(-1.0, 1.0),
+                                                    enabled: true,
+                                                }
+                                            })
+                                            .collect();
+                     Genome {
+                         config: config.clone(),
+                         hidden: Vec::new(),
+                         connections,
+                     }
+                 })
+                 .collect()
+    }
+}
+
+/// A weighted connection between two nodes of a network.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Connection {
+    /// The historical marking of this connection.
+    pub innovation: usize,
+    /// The node this connection starts at.
+    pub from: usize,
+    /// The node this connection ends at.
+    pub to: usize,
+    /// The weight of this connection.
+    pub weight: f64,
+    /// Whether this connection is part of the network.
+    pub enabled: bool,
+}
+
+/// A feed-forward neural network, which evolves through NEAT.
+///
+/// Create an initial population with `Config::population`.
+#[derive(Clone, Debug)]
+pub struct Genome {
+    config: Arc<Config>,
+    hidden: Vec<usize>,
+    connections: Vec<Connection>,
+}
+
+impl Genome {
+    /// Get the connections, ordered by innovation number.
+    pub fn connections(&self) -> &[Connection] {
+        &self.connections
+    }
+
+    /// Get the hidden nodes.
+    pub fn hidden(&self) -> &[usize] {
+        &self.hidden
+    }
+
+    /// Calculate the outputs of the network for `inputs`. Every node sums its weighted
+    /// inputs and applies a steepened sigmoid, as in the original NEAT paper.
+    pub fn activate(&self, inputs: &[f64]) -> Vec<f64> {
+        let mut values: HashMap<usize, f64> = HashMap::new();
+        for i in 0..self.config.inputs {
+            values.insert(i, inputs.get(i).cloned().unwrap_or(0.0));
+        }
+        values.insert(self.config.inputs, 1.0);
+        let first_output = self.config.inputs + 1;
+        (first_output..(first_output + self.config.outputs))
+            .map(|node| self.value(node, &mut values))
+            .collect()
+    }
+
+    /// Calculate the value of `node`, remembering the values of all nodes in `values`.
+    fn value(&self, node: usize, values: &mut HashMap<usize, f64>) -> f64 {
+        if let Some(&value) = values.get(&node) {
+            return value;
+        }
+        let mut sum = 0.0;
+        for connection in self.connections.iter().filter(|c| c.enabled && c.to == node) {
+            sum += connection.weight * self.value(connection.from, values);
+        }
+        let value = 1.0 / (1.0 + (-4.9 * sum).exp());
+        values.insert(node, value);
+        value
+    }
+
+    /// Check whether there is a path of connections from `from` to `to`.
+    fn reaches(&self, from: usize, to: usize) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack = vec![from];
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+            if visited.insert(node) {
+                stack.extend(self.connections.iter().filter(|c| c.from == node).map(|c| c.to));
+            }
+        }
+        false
+    }
+
+    /// Add a connection, keeping the connections ordered by innovation number.
+    fn insert(&mut self, connection: Connection) {
+        let index = self.connections
+                        .iter()
+                        .position(|c| c.innovation > connection.innovation)
+                        .unwrap_or(self.connections.len());
+        self.connections.insert(index, connection);
+    }
+
+    /// Connect two random nodes that are not connected yet, without creating a cycle.
+    fn add_connection<R: Rng>(&mut self, rng: &mut R) {
+        let first_output = self.config.inputs + 1;
+        let mut nodes: Vec<usize> = (0..(first_output + self.config.outputs)).collect();
+        nodes.extend(&self.hidden);
+        let targets: Vec<usize> = nodes.iter().cloned().filter(|&n| n >= first_output).collect();
+        for _ in 0..20 {
+            let from = nodes[rng.gen_range(0, nodes.len())];
+            let to = targets[rng.gen_range(0, targets.len())];
+            if from == to || self.connections.iter().any(|c| c.from == from && c.to == to) ||
+               self.reaches(to, from) {
+                continue;
+            }
+            let innovation = self.config.innovations.lock().unwrap().connection(from, to);
+            self.insert(Connection {
+                innovation,
+                from,
+                to,
+                weight: rng.gen_range(-1.0, 1.0),
+                enabled: true,
+            });
+            return;
+        }
+    }
+
+    /// Split a random enabled connection with a new hidden node.
+    fn add_node<R: Rng>(&mut self, rng: &mut R) {
+        let enabled: Vec<usize> = (0..self.connections.len())
+                                      .filter(|&i| self.connections[i].enabled)
+                                      .collect();
+        if enabled.is_empty() {
+            return;
+        }
+        let split = self.connections[enabled[rng.gen_range(0, enabled.len())]].clone();
+        let (node, into, out) = {
+            let mut innovations = self.config.innovations.lock().unwrap();
+            let node = innovations.split(split.innovation);
+            (node, innovations.connection(split.from, node), innovations.connection(node, split.to))
+        };
+        if self.hidden.contains(&node) {
+            return;
+        }
+        for connection in &mut self.connections {
+            if connection.innovation == split.innovation {
+                connection.enabled = false;
+            }
+        }
+        self.hidden.push(node);
+        // The new node passes the signal on unchanged at first.
+        self.insert(Connection {
+            innovation: into,
+            from: split.from,
+            to: node,
+            weight: 1.0,
+            enabled: true,
+        });
+        self.insert(Connection {
+            innovation: out,
+            from: node,
+            to: split.to,
+            weight: split.weight,
+            enabled: true,
+        });
+    }
+}
+
+impl PartialEq for Genome {
+    fn eq(&self, other: &Genome) -> bool {
+        self.hidden == other.hidden && self.connections == other.connections
+    }
+}
+
+impl Phenotype for Genome {
+    type Fitness = f64;
+
+    fn fitness(&self) -> f64 {
+        (self.config.fitness)(self)
+    }
+
+    fn crossover(&self, other: &Genome) -> Genome {
+        self.crossover_with_rng(other, &mut ::rand::thread_rng())
+    }
+
+    fn mutate(&self) -> Genome {
+        self.mutate_with_rng(&mut ::rand::thread_rng())
+    }
+
+    /// Line up the connections of both genomes by innovation number. Matching connections
+    /// are inherited from a random parent, and disabled with a probability of 0.75 if
+    /// either parent disabled them. Disjoint and excess connections are inherited from
+    /// `self`, so the child has the structure of `self`.
+    fn crossover_with_rng<R: Rng>(&self, other: &Genome, rng: &mut R) -> Genome {
+        let matching: HashMap<usize, &Connection> = other.connections
+                                                         .iter()
+                                                         .map(|c| (c.innovation, c))
+                                                         .collect();
+        let inherit = |c: &Connection| match matching.get(&c.innovation) {
+            Some(o) => {
+                let mut child = if rng.gen() { c.clone() } else { (*o).clone() };
+                if !c.enabled || !o.enabled {
+                    child.enabled = rng.gen::<f64>() >= 0.75;
+                }
+                child
+            }
+            None => c.clone(),
+        };
+        let connections = self.connections.iter().map(inherit).collect();
+        Genome {
+            config: self.config.clone(),
+            hidden: self.hidden.clone(),
+            connections,
+        }
+    }
+
+    fn mutate_with_rng<R: Rng>(&self, rng: &mut R) -> Genome {
+        let mut child = self.clone();
+        let perturbation = self.config.weight_perturbation;
+        for connection in &mut child.connections {
+            if rng.gen::<f64>() < self.config.weight_mutation_rate {
+                if rng.gen::<f64>() < 0.9 {
+                    connection.weight += rng.gen_range(-perturbation, perturbation);
+                } else {
+                    connection.weight = rng.gen_range(-1.0, 1.0);
+                }
+            }
+        }
+        if rng.gen::<f64>() < self.config.add_connection_rate {
+            child.add_connection(rng);
+        }
+        if rng.gen::<f64>() < self.config.add_node_rate {
+            child.add_node(rng);
+        }
+        child
+    }
+}
+
+/// Calculate the compatibility distance between two genomes, from the numbers of excess
+/// and disjoint connections and the mean weight difference of matching connections,
+/// weighted by the coefficients of the `Config` of `x`.
+///
+/// Excess connections have innovation numbers beyond those of the other genome, and
+/// disjoint connections are the other connections that do not match.
+pub fn distance(x: &Genome, y: &Genome) -> f64 {
+    let innovations = |g: &Genome| -> HashMap<usize, f64> {
+        g.connections.iter().map(|c| (c.innovation, c.weight)).collect()
+    };
+    let (xs, ys) = (innovations(x), innovations(y));
+    let last = |g: &Genome| g.connections.last().map_or(0, |c| c.innovation);
+    let (last_x, last_y) = (last(x), last(y));
+    let mut excess = 0;
+    let mut disjoint = 0;
+    let mut matching = 0;
+    let mut difference = 0.0;
+    for (innovation, weight) in &xs {
+        match ys.get(innovation) {
+            Some(other) => {
+                matching += 1;
+                difference += (weight - other).abs();
+            }
+            None if *innovation > last_y => excess += 1,
+            None => disjoint += 1,
+        }
+    }
+    for innovation in ys.keys().filter(|i| !xs.contains_key(i)) {
+        if *innovation > last_x {
+            excess += 1;
+        } else {
+            disjoint += 1;
+        }
+    }
+    let size = xs.len().max(ys.len()).max(1) as f64;
+    let config = &x.config;
+    let mean_difference = if matching > 0 { difference / matching as f64 } else { 0.0 };
+    config.excess_coefficient * excess as f64 / size +
+    config.disjoint_coefficient * disjoint as f64 / size +
+    config.weight_coefficient * mean_difference
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::sim::*;
+    use ::sim::replace::*;
+    use ::sim::select::*;
+
+    fn xor(genome: &Genome) -> f64 {
+        let cases = [([0.0, 0.0], 0.0), ([0.0, 1.0], 1.0), ([1.0, 0.0], 1.0), ([1.0, 1.0], 0.0)];
+        let error: f64 = cases.iter()
+                              .map(|&(inputs, output)| {
+                                  (genome.activate(&inputs)[0] - output).powi(2)
+                              })
+                              .sum();
+        4.0 - error
+    }
+
+    #[test]
+    fn test_minimal() {
+        let population = Config::new(2, 1, xor).population(3, &mut ::rand::thread_rng());
+        assert_eq!(population.len(), 3);
+        for genome in &population {
+            let links: Vec<(usize, usize)> =
+                genome.connections().iter().map(|c| (c.from, c.to)).collect();
+            assert_eq!(links, vec![(0, 3), (1, 3), (2, 3)]);
+            assert!(genome.hidden().is_empty());
+        }
+        assert!(distance(&population[0], &population[0]).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_activate() {
+        let mut genome = Config::new(2, 1, xor).population(1, &mut ::rand::thread_rng())
+                                               .remove(0);
+        for connection in &mut genome.connections {
+            connection.weight = 0.0;
+        }
+        assert!((genome.activate(&[1.0, 1.0])[0] - 0.5).abs() < 0.001);
+        genome.connections[0].weight = 1.0;
+        assert!(genome.activate(&[1.0, 0.0])[0] > genome.activate(&[0.0, 0.0])[0]);
+    }
+
+    #[test]
+    fn test_historical_markings() {
+        let mut rng = ::rand::thread_rng();
+        let population = Config::new(1, 1, xor).population(2, &mut rng);
+        let innovations = |g: &Genome| -> Vec<usize> {
+            g.connections.iter().map(|c| c.innovation).collect()
+        };
+        // Only leave the connection from the input to the output to be split.
+        let (mut x, mut y) = (population[0].clone(), population[1].clone());
+        x.connections[1].enabled = false;
+        y.connections[1].enabled = false;
+        x.add_node(&mut rng);
+        y.add_node(&mut rng);
+        // The same structural mutation gets the same node and innovation numbers.
+        assert_eq!(x.hidden(), &[3]);
+        assert_eq!(x.hidden(), y.hidden());
+        assert_eq!(innovations(&x), vec![0, 1, 2, 3]);
+        assert_eq!(innovations(&x), innovations(&y));
+        assert!(!x.connections[0].enabled);
+        // Crossover keeps the structure of the first parent.
+        let child = x.crossover_with_rng(&population[1], &mut rng);
+        assert_eq!(child.hidden(), x.hidden());
+        assert_eq!(innovations(&child), innovations(&x));
+        let other = population[1].crossover_with_rng(&x, &mut rng);
+        assert_eq!(innovations(&other), vec![0, 1]);
+        // Two of four connections are excess.
+        assert!(distance(&x, &population[0]) >= 0.5);
+    }
+
+    #[test]
+    fn test_simulation() {
+        let mut rng = ::rand::thread_rng();
+        let config = Config::new(2, 1, xor).with_structural_mutation(0.3, 0.2);
+        let population = config.population(50, &mut rng);
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(SpeciesSelector::new(20, 3.0, distance)))
+                        .set_replacement(Box::new(WorstReplacement::new()))
+                        .set_elitism(2)
+                        .set_max_iters(30)
+                        .build().unwrap();
+        let before = s.get().unwrap().fitness();
+        s.run();
+        let best = s.get().unwrap();
+        assert!(best.fitness() >= before);
+        // Structural mutations never create cycles.
+        for genome in s.population() {
+            for connection in genome.connections() {
+                assert!(!genome.reaches(connection.to, connection.from));
+            }
+        }
+    }
+}
@@ -14,15 +14,318 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use rand::Rng;
+use std::cmp::Ordering;
+use std::sync::Arc;
+
 /// Defines what a Phenotype is.
 /// A Phenotype can breed with other Phenotypes, resulting in a single child.
 /// A Phenotype can also be mutated.
 /// Finally, a Phenotype has a certain fitness value associated with it.
 pub trait Phenotype : Clone {
+    /// The type of the fitness value of this Phenotype, usually `f64`.
+    type Fitness: Fitness;
+
     /// Calculate the fitness of this Phenotype.
-    fn fitness(&self) -> f64;
+    fn fitness(&self) -> Self::Fitness;
     /// Perform crossover on this Phenotype, returning a new Phenotype.
-    fn crossover(&self, &Self) -> Self;
+    fn crossover(&self, other: &Self) -> Self;
     /// Perform mutation on this Phenotype, returning a new Phenotype.
     fn mutate(&self) -> Self;
+
+    /// Perform crossover on this Phenotype, returning a new Phenotype,
+    /// drawing any randomness from `rng`.
+    ///
+    /// Simulators call this method with their own random number generator, so
+    /// overriding it instead of using `rand::thread_rng()` in `crossover` makes
+    /// seeded runs reproducible. The default implementation calls `crossover`.
+    fn crossover_with_rng<R: Rng>(&self, other: &Self, _rng: &mut R) -> Self {
+        self.crossover(other)
+    }
+
+    /// Perform mutation on this Phenotype, returning a new Phenotype,
+    /// drawing any randomness from `rng`.
+    ///
+    /// See `crossover_with_rng`. The default implementation calls `mutate`.
+    fn mutate_with_rng<R: Rng>(&self, _rng: &mut R) -> Self {
+        self.mutate()
+    }
+}
+
+/// A Phenotype that has to satisfy constraints.
+///
+/// Call `set_constrained()` on a `SimulatorBuilder` to take the constraints into account.
+/// Feasible phenotypes then always perform better than infeasible ones, and infeasible
+/// ones perform better the less they violate their constraints, whatever their fitness
+/// values. This keeps the fitness landscape intact, unlike adding penalties to the fitness
+/// value by hand.
+pub trait Constrained: Phenotype {
+    /// Calculate the total violation of the constraints of this Phenotype. This must be
+    /// zero if it satisfies all of them, and positive otherwise.
+    fn violations(&self) -> f64;
+}
+
+/// A Phenotype of which the distance to other Phenotypes can be measured, for example the
+/// Hamming distance between bit strings.
+///
+/// Call `set_track_diversity` on a `SimulatorBuilder` to record the diversity of the
+/// population in every step, which reveals when the population collapses onto a few
+/// phenotypes.
+pub trait Distance {
+    /// Calculate the distance between this Phenotype and `other`. This must be zero for
+    /// equal Phenotypes, and must not depend on the order of the two.
+    fn distance(&self, other: &Self) -> f64;
+}
+
+/// A fitness value, which can be compared to other fitness values of the same type.
+///
+/// Simulators only compare fitness values to rank phenotypes, so any ordered type can be
+/// used: exact integers, or tuples, which are compared lexicographically. Statistics,
+/// early stopping, target fitness values and fitness proportionate selection need a
+/// number, which they get from `to_f64`.
+///
+/// `Fitness` is implemented for all primitive numbers, and for tuples of up to four
+/// `Fitness` values.
+pub trait Fitness: PartialOrd + Clone + Send + Sync {
+    /// Convert this fitness value to a number. For tuples, this is the number of the
+    /// first element.
+    fn to_f64(&self) -> f64;
+
+    /// Get the objectives of this fitness value as numbers, to measure how far apart
+    /// fitness values are in every objective. The default implementation returns `to_f64`
+    /// as the only objective.
+    fn objectives(&self) -> Vec<f64> {
+        vec![self.to_f64()]
+    }
+}
+
+macro_rules! impl_fitness_number {
+    ($($t:ty),*) => {
+        $(
+            impl Fitness for $t {
+                fn to_f64(&self) -> f64 {
+                    *self as f64
+                }
+            }
+        )*
+    }
+}
+
+impl_fitness_number!(f64, f32, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! impl_fitness_tuple {
+    ($($t:ident),*) => {
+        impl<A: Fitness, $($t: Fitness),*> Fitness for (A, $($t),*) {
+            fn to_f64(&self) -> f64 {
+                self.0.to_f64()
+            }
+        }
+    }
+}
+
+impl_fitness_tuple!(B);
+impl_fitness_tuple!(B, C);
+impl_fitness_tuple!(B, C, D);
+
+/// A fitness value consisting of several objectives, for problems that can not honestly
+/// be reduced to a single number, such as cost versus quality.
+///
+/// `Objectives` are compared by Pareto dominance: one is greater than another if it is at
+/// least as large in every objective and larger in at least one. If neither dominates the
+/// other, they can not be compared, and `partial_cmp` returns `None`. All objectives are
+/// maximized or minimized together, depending on the `FitnessType` of the simulator, so
+/// negate the objectives that should go the other way.
+///
+/// `to_f64` returns the sum of the objectives, and `objectives` returns the objectives.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Objectives(pub Vec<f64>);
+
+impl PartialOrd for Objectives {
+    fn partial_cmp(&self, other: &Objectives) -> Option<Ordering> {
+        if self.0.len() != other.0.len() {
+            return None;
+        }
+        let mut result = Ordering::Equal;
+        for (x, y) in self.0.iter().zip(&other.0) {
+            match x.partial_cmp(y)? {
+                Ordering::Equal => {}
+                ordering if result == Ordering::Equal => result = ordering,
+                ordering if ordering != result => return None,
+                _ => {}
+            }
+        }
+        Some(result)
+    }
+}
+
+impl Fitness for Objectives {
+    fn to_f64(&self) -> f64 {
+        self.0.iter().sum()
+    }
+
+    fn objectives(&self) -> Vec<f64> {
+        self.0.clone()
+    }
+}
+
+/// A fitness value consisting of several objectives in order of priority, such as
+/// "minimize errors, then minimize size".
+///
+/// `Lexicographic` values are compared lexicographically: the first objective decides, and
+/// ties fall through to the next objective. Like tuples, but with any number of
+/// objectives, which are all kept by `objectives`. All objectives are maximized or
+/// minimized together, depending on the `FitnessType` of the simulator, so negate the
+/// objectives that should go the other way.
+///
+/// `to_f64` returns the first objective, or zero if there are none.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Lexicographic(pub Vec<f64>);
+
+impl Fitness for Lexicographic {
+    fn to_f64(&self) -> f64 {
+        self.0.first().cloned().unwrap_or(0.0)
+    }
+
+    fn objectives(&self) -> Vec<f64> {
+        self.0.clone()
+    }
+}
+
+/// Reduces the objectives of a fitness value to a single number, by a weighted sum.
+///
+/// This lets multi-objective phenotypes be used with all single-objective selectors: wrap
+/// the population with `wrap`, and every phenotype becomes a `Scalarized` phenotype with
+/// an `f64` fitness value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeightedSum {
+    weights: Vec<f64>,
+    bounds: Option<Vec<(f64, f64)>>,
+}
+
+impl WeightedSum {
+    /// Create a weighted sum with a weight for every objective. Objectives without a
+    /// weight are ignored.
+    pub fn new(weights: Vec<f64>) -> WeightedSum {
+        WeightedSum {
+            weights,
+            bounds: None,
+        }
+    }
+
+    /// Normalize every objective to the range from zero to one before weighting it, so
+    /// that the weights do not depend on the scales of the objectives. `bounds` contains
+    /// the lowest and highest expected value of every objective.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn normalize(mut self, bounds: Vec<(f64, f64)>) -> WeightedSum {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    /// Calculate the weighted sum of the objectives of `fitness`.
+    pub fn scalarize<F: Fitness>(&self, fitness: &F) -> f64 {
+        fitness.objectives()
+               .iter()
+               .zip(&self.weights)
+               .enumerate()
+               .map(|(i, (&x, &w))| {
+                   match self.bounds.as_ref().and_then(|bounds| bounds.get(i)) {
+                       Some(&(low, high)) if high > low => w * (x - low) / (high - low),
+                       Some(_) => 0.0,
+                       None => w * x,
+                   }
+               })
+               .sum()
+    }
+
+    /// Wrap every phenotype of `population`, so its fitness value is scalarized by this
+    /// weighted sum.
+    pub fn wrap<T: Phenotype>(self, population: Vec<T>) -> Vec<Scalarized<T>> {
+        let weighting = Arc::new(self);
+        population.into_iter()
+                  .map(|phenotype| {
+                      Scalarized {
+                          phenotype,
+                          weighting: weighting.clone(),
+                      }
+                  })
+                  .collect()
+    }
+}
+
+/// A phenotype of which the fitness value is scalarized by a `WeightedSum`.
+///
+/// Crossover and mutation are done by the wrapped phenotype. Create these with
+/// `WeightedSum::wrap`.
+#[derive(Clone, Debug)]
+pub struct Scalarized<T> {
+    phenotype: T,
+    weighting: Arc<WeightedSum>,
+}
+
+impl<T> Scalarized<T> {
+    /// Get the wrapped phenotype.
+    pub fn inner(&self) -> &T {
+        &self.phenotype
+    }
+
+    /// Unwrap the wrapped phenotype.
+    pub fn into_inner(self) -> T {
+        self.phenotype
+    }
+
+    fn with(&self, phenotype: T) -> Scalarized<T> {
+        Scalarized {
+            phenotype,
+            weighting: self.weighting.clone(),
+        }
+    }
+}
+
+impl<T: Phenotype> Phenotype for Scalarized<T> {
+    type Fitness = f64;
+
+    fn fitness(&self) -> f64 {
+        self.weighting.scalarize(&self.phenotype.fitness())
+    }
+
+    fn crossover(&self, other: &Scalarized<T>) -> Scalarized<T> {
+        self.with(self.phenotype.crossover(&other.phenotype))
+    }
+
+    fn mutate(&self) -> Scalarized<T> {
+        self.with(self.phenotype.mutate())
+    }
+
+    fn crossover_with_rng<R: Rng>(&self, other: &Scalarized<T>, rng: &mut R) -> Scalarized<T> {
+        self.with(self.phenotype.crossover_with_rng(&other.phenotype, rng))
+    }
+
+    fn mutate_with_rng<R: Rng>(&self, rng: &mut R) -> Scalarized<T> {
+        self.with(self.phenotype.mutate_with_rng(rng))
+    }
+}
+
+/// Calculates the fitness value of a Phenotype.
+///
+/// A `FitnessFunction` can be set on a `SimulatorBuilder` to evaluate phenotypes
+/// instead of `Phenotype::fitness()`. Shared context, such as a training data set
+/// or configuration, can then live in the `FitnessFunction` instead of being
+/// copied into every Phenotype.
+///
+/// A `FitnessFunction` may be used from multiple threads by parallel simulators,
+/// so it must be `Send` and `Sync`.
+///
+/// Closures of the form `Fn(&T) -> T::Fitness` are `FitnessFunction`s as well.
+pub trait FitnessFunction<T: Phenotype>: Send + Sync {
+    /// Calculate the fitness of `phenotype`.
+    fn fitness(&self, phenotype: &T) -> T::Fitness;
+}
+
+impl<T: Phenotype, F: Fn(&T) -> T::Fitness + Send + Sync> FitnessFunction<T> for F {
+    fn fitness(&self, phenotype: &T) -> T::Fitness {
+        self(phenotype)
+    }
 }
@@ -0,0 +1,287 @@
+// file: chc.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The chc module provides the CHC evolutionary algorithm of Eshelman, for bit strings and
+//! other phenotypes of which the distance can be measured.
+//!
+//! CHC combines cross-generational elitist selection, where the best phenotypes of the
+//! population and the children survive, with incest prevention: only parents that differ
+//! enough are mated. When the population stops producing surviving children, the incest
+//! threshold is lowered, and once it drops below zero, the population is restarted around
+//! its best phenotype by a cataclysmic mutation.
+//!
+//! Call `set_chc` on a `SimulatorBuilder` to run CHC. `hux` and `diverge` implement the
+//! crossover and the cataclysmic mutation of CHC for bit strings.
+
+use pheno::Phenotype;
+use rand::Rng;
+use std::cell::Cell;
+use std::rc::Rc;
+use super::{FitnessType, Individual};
+use super::replace::ReplacementPolicy;
+use super::select::{Parents, Selector};
+
+/// Count the positions at which the bit strings `x` and `y` differ.
+pub fn hamming(x: &[bool], y: &[bool]) -> usize {
+    x.iter().zip(y).filter(|&(a, b)| a != b).count()
+}
+
+/// Half uniform crossover (HUX): copy `x`, and take the bits of `y` at exactly half of the
+/// positions where they differ, chosen at random.
+pub fn hux(x: &[bool], y: &[bool], mut rng: &mut dyn Rng) -> Vec<bool> {
+    let mut child = x.to_vec();
+    let mut differing: Vec<usize> = (0..x.len().min(y.len())).filter(|&i| x[i] != y[i])
+                                                            .collect();
+    // Shuffle the first half of the differing positions into place.
+    let half = differing.len() / 2;
+    for i in 0..half {
+        let j = Rng::gen_range(&mut rng, i, differing.len());
+        differing.swap(i, j);
+        child[differing[i]] = y[differing[i]];
+    }
+    child
+}
+
+/// Flip every bit of `x` with probability `rate`. CHC restarts its population with a rate
+/// of 0.35.
+pub fn diverge(x: &[bool], rate: f64, mut rng: &mut dyn Rng) -> Vec<bool> {
+    x.iter().map(|&bit| bit != (Rng::gen::<f64>(&mut rng) < rate)).collect()
+}
+
+/// The shared state of the CHC selector and replacement policy.
+#[derive(Debug)]
+struct State {
+    /// The initial incest threshold.
+    initial: f64,
+    /// The current incest threshold.
+    threshold: Cell<f64>,
+    /// The number of cataclysmic restarts.
+    restarts: Cell<u64>,
+}
+
+/// The selector and replacement policy of CHC, which share the incest threshold.
+///
+/// As a `Selector`, it pairs up the whole population at random, and keeps the pairs of
+/// which half the distance is above the incest threshold. As a `ReplacementPolicy`, it
+/// keeps the best performing phenotypes of the population and the children. If no child
+/// survives, the threshold is lowered by one, and once it drops below zero, every
+/// phenotype but the best is replaced by a cataclysmic mutation of the best, and the
+/// threshold is reset.
+///
+/// The fitness values of the restarted phenotypes are calculated by `Phenotype::fitness`.
+pub struct Chc<T> {
+    state: Rc<State>,
+    distance: fn(&T, &T) -> f64,
+    cataclysm: fn(&T, &mut dyn Rng) -> T,
+}
+
+impl<T> Chc<T> {
+    /// Create the selector and replacement policy of CHC.
+    ///
+    /// * `threshold`: the initial incest threshold, usually a quarter of the length of the
+    ///   bit strings.
+    /// * `distance`: calculates the distance between two phenotypes, usually `hamming`.
+    /// * `cataclysm`: creates a restarted phenotype from the best one, usually by `diverge`.
+    pub fn new(threshold: f64,
+               distance: fn(&T, &T) -> f64,
+               cataclysm: fn(&T, &mut dyn Rng) -> T)
+               -> Chc<T> {
+        Chc {
+            state: Rc::new(State {
+                initial: threshold,
+                threshold: Cell::new(threshold),
+                restarts: Cell::new(0),
+            }),
+            distance,
+            cataclysm,
+        }
+    }
+
+    /// Get the current incest threshold.
+    pub fn threshold(&self) -> f64 {
+        self.state.threshold.get()
+    }
+
+    /// Get the number of cataclysmic restarts so far.
+    pub fn restarts(&self) -> u64 {
+        self.state.restarts.get()
+    }
+}
+
+impl<T> Clone for Chc<T> {
+    /// Clones share their incest threshold.
+    fn clone(&self) -> Chc<T> {
+        Chc {
+            state: self.state.clone(),
+            distance: self.distance,
+            cataclysm: self.cataclysm,
+        }
+    }
+}
+
+impl<T: Phenotype + 'static> Selector<T> for Chc<T> {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  _: FitnessType,
+                  mut rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        let mut order: Vec<usize> = (0..population.len()).collect();
+        for i in (1..order.len()).rev() {
+            let j = Rng::gen_range(&mut rng, 0, i + 1);
+            order.swap(i, j);
+        }
+        let threshold = self.state.threshold.get();
+        Ok(order.chunks(2)
+                .filter(|pair| pair.len() == 2)
+                .map(|pair| (population[pair[0]].phenotype(), population[pair[1]].phenotype()))
+                .filter(|&(x, y)| (self.distance)(x, y) / 2.0 > threshold)
+                .collect())
+    }
+}
+
+impl<T: Phenotype> ReplacementPolicy<T> for Chc<T> {
+    fn replace(&self,
+               population: &mut Vec<Individual<T>>,
+               children: Vec<Individual<T>>,
+               fitness_type: FitnessType,
+               rng: &mut dyn Rng) {
+        let size = population.len();
+        // Parents come first, so they win ties against children.
+        let mut pool: Vec<(bool, Individual<T>)> = population.drain(..)
+                                                             .map(|x| (false, x))
+                                                             .chain(children.into_iter()
+                                                                            .map(|x| (true, x)))
+                                                             .collect();
+        pool.sort_by(|x, y| y.1.cmp_rank(&x.1, fitness_type));
+        pool.truncate(size);
+        let survived = pool.iter().any(|x| x.0);
+        population.extend(pool.into_iter().map(|x| x.1));
+        if survived || population.is_empty() {
+            return;
+        }
+        let threshold = self.state.threshold.get() - 1.0;
+        if threshold >= 0.0 {
+            self.state.threshold.set(threshold);
+            return;
+        }
+        // Cataclysmic restart around the best phenotype.
+        let restarted: Vec<Individual<T>> = (1..population.len())
+            .map(|_| Individual::new((self.cataclysm)(population[0].phenotype(), rng)))
+            .collect();
+        population.truncate(1);
+        population.extend(restarted);
+        self.state.threshold.set(self.state.initial);
+        self.state.restarts.set(self.state.restarts.get() + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::sim::*;
+    use pheno::Distance;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Bits {
+        bits: Vec<bool>,
+    }
+
+    impl Phenotype for Bits {
+        type Fitness = usize;
+
+        // The number of ones.
+        fn fitness(&self) -> usize {
+            self.bits.iter().filter(|&&x| x).count()
+        }
+
+        fn crossover(&self, other: &Bits) -> Bits {
+            Bits { bits: hux(&self.bits, &other.bits, &mut ::rand::thread_rng()) }
+        }
+
+        fn mutate(&self) -> Bits {
+            self.clone()
+        }
+    }
+
+    impl Distance for Bits {
+        fn distance(&self, other: &Bits) -> f64 {
+            hamming(&self.bits, &other.bits) as f64
+        }
+    }
+
+    fn cataclysm(x: &Bits, rng: &mut dyn Rng) -> Bits {
+        Bits { bits: diverge(&x.bits, 0.35, rng) }
+    }
+
+    fn random_bits(length: usize) -> Bits {
+        Bits { bits: (0..length).map(|_| ::rand::random()).collect() }
+    }
+
+    #[test]
+    fn test_hux() {
+        let x = vec![false; 10];
+        let y: Vec<bool> = (0..10).map(|i| i < 6).collect();
+        let child = hux(&x, &y, &mut ::rand::thread_rng());
+        assert_eq!(hamming(&x, &child), 3);
+        assert_eq!(hamming(&y, &child), 3);
+        assert!(child[6..].iter().all(|&b| !b));
+        assert_eq!(hamming(&x, &diverge(&x, 1.0, &mut ::rand::thread_rng())), 10);
+    }
+
+    #[test]
+    fn test_incest_prevention() {
+        let chc = Chc::new(2.0, Bits::distance, cataclysm);
+        let population: Vec<Individual<Bits>> =
+            vec![vec![false; 8], vec![false; 8], vec![true; 8], vec![true; 8]]
+                .into_iter()
+                .map(|bits| Individual::new(Bits { bits }))
+                .collect();
+        let mut rng = ::rand::thread_rng();
+        let parents = chc.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        // Only pairs of opposite bit strings differ enough.
+        assert!(parents.iter().all(|&(x, y)| x.bits[0] != y.bits[0]));
+    }
+
+    #[test]
+    fn test_threshold_and_cataclysm() {
+        let chc = Chc::new(1.0, Bits::distance, cataclysm);
+        let mut rng = ::rand::thread_rng();
+        let mut population: Vec<Individual<Bits>> =
+            (0..10).map(|_| Individual::new(Bits { bits: vec![true; 20] })).collect();
+        let worse = || vec![Individual::new(Bits { bits: vec![false; 20] })];
+        chc.replace(&mut population, worse(), FitnessType::Maximize, &mut rng);
+        assert_eq!(chc.threshold(), 0.0);
+        assert_eq!(chc.restarts(), 0);
+        chc.replace(&mut population, worse(), FitnessType::Maximize, &mut rng);
+        assert_eq!(chc.threshold(), 1.0);
+        assert_eq!(chc.restarts(), 1);
+        assert_eq!(population.len(), 10);
+        assert_eq!(population[0].bits, vec![true; 20]);
+        assert!(population[1..].iter().any(|x| x.bits != vec![true; 20]));
+    }
+
+    #[test]
+    fn test_chc() {
+        let population: Vec<Bits> = (0..20).map(|_| random_bits(32)).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_chc(8.0, cataclysm)
+                        .set_max_iters(200)
+                        .build().unwrap();
+        s.run();
+        assert!(s.get().unwrap().fitness() >= 28);
+    }
+}
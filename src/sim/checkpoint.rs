@@ -0,0 +1,213 @@
+// file: checkpoint.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+use super::{GenerationStats, HistoryEntry, Individual, NanoSecond};
+use super::earlystopper::EarlyStopper;
+
+/// A snapshot of the state of a `Simulator`, from which a run can be resumed.
+///
+/// A `Checkpoint` contains the population, including cached fitness values and ages,
+/// the best individual found so far, the number of iterations, the early-stopping state,
+/// the time spent running, the history, the collected statistics, the hall of fame, the
+/// numbers of restarts and bursts of hypermutation, and the seed set with `set_seed`.
+///
+/// It also contains a seed for the random number generator, which was reseeded with it
+/// when the `Checkpoint` was taken. A run restored into a `SimulatorBuilder` with a
+/// random number generator of the same type therefore continues exactly like the
+/// original one. The thread-local random number generator can not be reseeded, so runs
+/// that use it are not reproducible, with or without a `Checkpoint`.
+///
+/// The selector, replacement policy and other settings are not part of a `Checkpoint`:
+/// they are taken from the `SimulatorBuilder` the `Checkpoint` is restored into. Neither
+/// is any state they keep themselves, such as the generation counter of a
+/// `BoltzmannSelector` or the patience of a `StoppingCondition`, nor a burst of
+/// hypermutation that is in progress.
+///
+/// A `Checkpoint` can be serialized with any `serde` format. `save` and `load`
+/// store it as JSON in a file.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize, T::Fitness: Serialize",
+              deserialize = "T: DeserializeOwned, T::Fitness: DeserializeOwned"))]
+pub struct Checkpoint<T: Phenotype> {
+    pub(super) population: Vec<Individual<T>>,
+    pub(super) best: Option<Individual<T>>,
+    pub(super) iterations: u64,
+    pub(super) earlystopper: Option<EarlyStopper>,
+    pub(super) duration: Option<NanoSecond>,
+    #[serde(default)]
+    pub(super) timed_steps: u64,
+    #[serde(default)]
+    pub(super) rng_seed: Option<u64>,
+    #[serde(default)]
+    pub(super) seed: Option<u64>,
+    #[serde(default)]
+    pub(super) restarts: u64,
+    #[serde(default)]
+    pub(super) hypermutations: u64,
+    #[serde(default)]
+    pub(super) history: Vec<HistoryEntry<T>>,
+    #[serde(default)]
+    pub(super) stats_history: Option<Vec<GenerationStats>>,
+    #[serde(default)]
+    pub(super) hall_of_fame: Vec<Individual<T>>,
+}
+
+impl<T: Phenotype> Checkpoint<T> {
+    /// Get the population at the time of the snapshot.
+    pub fn population(&self) -> &[Individual<T>] {
+        &self.population
+    }
+
+    /// Get the number of iterations at the time of the snapshot.
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+}
+
+impl<T: Phenotype + Serialize> Checkpoint<T>
+    where T::Fitness: Serialize
+{
+    /// Write this `Checkpoint` to the file at `path` as JSON.
+    ///
+    /// The `Checkpoint` is first written to a temporary file next to `path`, which then
+    /// replaces `path`. A crash while saving therefore never destroys an earlier `Checkpoint`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        let path = path.as_ref();
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        let write = || -> Result<(), String> {
+            let file = File::create(&temporary).map_err(|e| e.to_string())?;
+            let mut writer = BufWriter::new(file);
+            serde_json::to_writer(&mut writer, self).map_err(|e| e.to_string())?;
+            writer.flush().map_err(|e| e.to_string())?;
+            writer.get_ref().sync_all().map_err(|e| e.to_string())?;
+            fs::rename(&temporary, path).map_err(|e| e.to_string())
+        };
+        write().map_err(|e| format!("Could not save checkpoint to {}: {}", path.display(), e))
+    }
+}
+
+impl<T: Phenotype + DeserializeOwned> Checkpoint<T>
+    where T::Fitness: DeserializeOwned
+{
+    /// Read a `Checkpoint` written by `save` from the file at `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Checkpoint<T>, String> {
+        let path = path.as_ref();
+        let read = || -> Result<Checkpoint<T>, String> {
+            let file = File::open(path).map_err(|e| e.to_string())?;
+            serde_json::from_reader(BufReader::new(file)).map_err(|e| e.to_string())
+        };
+        read().map_err(|e| format!("Could not load checkpoint from {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    #[derive(Clone, Serialize, Deserialize)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_save_load() {
+        let path = env::temp_dir().join(format!("rsgenetic-checkpoint-{}.json", process::id()));
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(20)))
+                        .set_max_iters(5)
+                        .build().unwrap();
+        s.run();
+        s.save_checkpoint(&path).unwrap();
+
+        let checkpoint = Checkpoint::<Test>::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(checkpoint.iterations(), 5);
+        assert_eq!(checkpoint.population().len(), 100);
+        let mut resumed = seq::Simulator::builder()
+                              .set_selector(Box::new(MaximizeSelector::new(20)))
+                              .set_max_iters(8)
+                              .restore(checkpoint)
+                              .build().unwrap();
+        assert_eq!(resumed.iterations(), 5);
+        assert_eq!(resumed.get().unwrap().f, s.get().unwrap().f);
+        assert_eq!(resumed.run(), RunResult::Done);
+        assert_eq!(resumed.iterations(), 8);
+    }
+
+    #[test]
+    fn test_restore_reproducible() {
+        let builder = || {
+            let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+            seq::Simulator::builder()
+                .set_population(population)
+                .set_selector(Box::new(TournamentSelector::new(20, 5)))
+                .set_max_iters(10)
+                .set_collect_stats()
+                .set_seed(7)
+        };
+        let mut s = builder().build().unwrap();
+        for _ in 0..5 {
+            s.step();
+        }
+        let checkpoint = s.checkpoint();
+        s.run();
+        let mut restored = builder().restore(checkpoint).build().unwrap();
+        restored.run();
+        let fitness = |x: &seq::Simulator<Test, _>| -> Vec<i64> {
+            x.population().iter().map(|x| x.f).collect()
+        };
+        assert_eq!(fitness(&restored), fitness(&s));
+        assert_eq!(restored.history().len(), 10);
+        assert_eq!(restored.stats_history().len(), 10);
+        assert_eq!(restored.report().seed, Some(7));
+    }
+
+    #[test]
+    fn test_load_missing() {
+        let path = env::temp_dir().join("rsgenetic-checkpoint-missing.json");
+        assert!(Checkpoint::<Test>::load(&path).is_err());
+    }
+}
@@ -14,14 +14,43 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use super::FitnessType;
 use super::iterlimit::*;
 
+/// Which fitness value of the population is monitored for early stopping.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum EarlyStopMetric {
+    /// The fitness value of the best performing phenotype.
+    Best,
+    /// The mean fitness value of the population.
+    Mean,
+    /// The median fitness value of the population.
+    Median,
+}
+
+/// The progress of early stopping, which can be carried over to a resumed run with
+/// `set_early_stop_state` on a `SimulatorBuilder`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EarlyStopState {
+    /// The best fitness value recorded so far, or `None` if none has been recorded yet.
+    pub best: Option<f64>,
+    /// The number of iterations since `best` was recorded.
+    pub stale_iterations: u64,
+}
+
 /// Used for early stopping.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EarlyStopper {
     /// Minimum difference required for early stopping.
     delta: f64,
-    /// Previously recorded fitness value.
-    previous: f64,
+    /// Whether `delta` is relative to the best fitness value.
+    #[cfg_attr(feature = "serde", serde(default))]
+    relative: bool,
+    /// Best fitness value recorded so far.
+    best: Option<f64>,
     /// The number of iterations before stopping early.
     iter_limit: IterLimit,
 }
@@ -30,22 +59,88 @@ impl EarlyStopper {
     /// Create a new `EarlyStopper`.
     pub fn new(delta: f64, n_iters: u64) -> EarlyStopper {
         EarlyStopper {
-            delta: delta,
-            previous: 0.0,
+            delta,
+            relative: false,
+            best: None,
             iter_limit: IterLimit::new(n_iters),
         }
     }
 
+    /// Create a new `EarlyStopper`, for which `delta` is a fraction of the best
+    /// fitness value, such as `0.01` for one percent.
+    pub fn new_relative(delta: f64, n_iters: u64) -> EarlyStopper {
+        EarlyStopper { relative: true, ..EarlyStopper::new(delta, n_iters) }
+    }
+
     /// Update the `EarlyStopper` with a new fitness value.
-    pub fn update(&mut self, fitness: f64) {
-        if (fitness - self.previous).abs() < self.delta {
-            self.previous = fitness;
-            self.iter_limit.inc();
-        } else {
+    ///
+    /// The fitness value is compared to the best fitness value recorded so far, rather than
+    /// to the previous one, so that fluctuations of the population do not reset the count.
+    /// The first fitness value is only recorded.
+    pub fn update(&mut self, fitness: f64, fitness_type: FitnessType) {
+        let improved = match self.best {
+            Some(best) => {
+                let improvement = match fitness_type {
+                    FitnessType::Maximize => fitness - best,
+                    FitnessType::Minimize => best - fitness,
+                };
+                let delta = if self.relative {
+                    self.delta * best.abs()
+                } else {
+                    self.delta
+                };
+                improvement >= delta
+            }
+            None => true,
+        };
+        if improved {
+            self.best = Some(fitness);
             self.iter_limit.reset();
+        } else {
+            self.iter_limit.inc();
+        }
+    }
+
+    /// Forget all recorded fitness values.
+    pub fn reset(&mut self) {
+        self.best = None;
+        self.iter_limit.reset();
+    }
+
+    /// Returns whether a fitness value has been recorded yet.
+    pub fn started(&self) -> bool {
+        self.best.is_some()
+    }
+
+    /// Get the progress of early stopping.
+    pub fn state(&self) -> EarlyStopState {
+        EarlyStopState {
+            best: self.best,
+            stale_iterations: self.iter_limit.get(),
         }
     }
 
+    /// Continue from the progress `state`, recorded by `state` on another `EarlyStopper`.
+    pub fn set_state(&mut self, state: EarlyStopState) {
+        self.best = state.best;
+        self.iter_limit.set(state.stale_iterations);
+    }
+
+    /// Get the minimum difference required for early stopping.
+    pub fn delta(&self) -> f64 {
+        self.delta
+    }
+
+    /// Returns whether the minimum difference is relative to the best fitness value.
+    pub fn relative(&self) -> bool {
+        self.relative
+    }
+
+    /// Get the number of iterations before stopping early.
+    pub fn n_iters(&self) -> u64 {
+        self.iter_limit.max()
+    }
+
     /// Returns whether the `Simulator` should stop.
     pub fn reached(&self) -> bool {
         self.iter_limit.reached()
@@ -54,25 +149,75 @@ impl EarlyStopper {
 
 #[cfg(test)]
 mod tests {
-    use super::EarlyStopper;
+    use super::{EarlyStopState, EarlyStopper};
+    use sim::FitnessType;
 
     #[test]
     fn test_early_stopper_reset() {
         let mut stopper = EarlyStopper::new(10.0, 5);
         for _ in 0..4 {
-            stopper.update(1.0);
+            stopper.update(1.0, FitnessType::Maximize);
         }
-        assert_eq!(stopper.reached(), false);
-        stopper.update(20.0);
-        assert_eq!(stopper.reached(), false);
+        assert!(!stopper.reached());
+        stopper.update(20.0, FitnessType::Maximize);
+        assert!(!stopper.reached());
+    }
+
+    #[test]
+    fn test_early_stopper_relative() {
+        let mut stopper = EarlyStopper::new_relative(0.01, 3);
+        for &fitness in &[1000.0, 1002.0, 1004.0, 1006.0] {
+            stopper.update(fitness, FitnessType::Maximize);
+        }
+        assert!(stopper.reached());
+        let mut stopper = EarlyStopper::new_relative(0.01, 3);
+        for &fitness in &[1.0, 1.5, 2.0, 2.5] {
+            stopper.update(fitness, FitnessType::Maximize);
+        }
+        assert!(!stopper.reached());
     }
 
     #[test]
     fn test_early_stopper_reached() {
         let mut stopper = EarlyStopper::new(10.0, 5);
-        for _ in 0..5 {
-            stopper.update(1.0);
+        // The first value is the baseline.
+        for _ in 0..6 {
+            stopper.update(1.0, FitnessType::Maximize);
         }
         assert!(stopper.reached());
     }
+
+    #[test]
+    fn test_early_stopper_best_ever() {
+        // Oscillating around the best value does not reset the count.
+        let mut stopper = EarlyStopper::new(1.0, 4);
+        for &fitness in &[10.0, 5.0, 10.0, 5.0, 10.5] {
+            stopper.update(fitness, FitnessType::Maximize);
+        }
+        assert!(stopper.reached());
+        // Improvements are measured in the direction of the fitness type.
+        let mut stopper = EarlyStopper::new(1.0, 2);
+        for &fitness in &[10.0, 8.0, 6.0] {
+            stopper.update(fitness, FitnessType::Minimize);
+        }
+        assert!(!stopper.reached());
+    }
+
+    #[test]
+    fn test_early_stopper_state() {
+        let mut stopper = EarlyStopper::new(10.0, 5);
+        for _ in 0..4 {
+            stopper.update(1.0, FitnessType::Maximize);
+        }
+        let mut resumed = EarlyStopper::new(10.0, 5);
+        resumed.set_state(stopper.state());
+        assert_eq!(resumed.state(),
+                   EarlyStopState {
+                       best: Some(1.0),
+                       stale_iterations: 3,
+                   });
+        resumed.update(1.0, FitnessType::Maximize);
+        resumed.update(1.0, FitnessType::Maximize);
+        assert!(resumed.reached());
+    }
 }
@@ -0,0 +1,109 @@
+// file: error.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error;
+use std::fmt;
+
+/// An error that occurred while running a `Simulation`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Error {
+    /// A step was made without a population, or the population was empty.
+    EmptyPopulation,
+    /// The parameters of the selector can not be used with the population.
+    /// Contains a message describing the invalid parameter.
+    InvalidSelectorParams(String),
+    /// The selector failed to select parents. Contains the message of the selector.
+    SelectionFailed(String),
+    /// The fitness values of new phenotypes could not be calculated.
+    /// Contains the message of the evaluator.
+    EvaluationFailed(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::EmptyPopulation => {
+                write!(f, "Tried to run a simulator without a population, or the population \
+                           was empty.")
+            }
+            Error::InvalidSelectorParams(ref e) |
+            Error::SelectionFailed(ref e) |
+            Error::EvaluationFailed(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// The reason a `Builder` could not build a `Simulation`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BuildError {
+    /// No population was set, or the population was empty.
+    EmptyPopulation,
+    /// The selector can not select parents from a population of this size.
+    /// Contains a message describing the invalid parameter.
+    InvalidSelector(String),
+    /// At least as many phenotypes are kept by elitism as there are in the population,
+    /// so none could ever be replaced.
+    InvalidElitism {
+        /// The number of phenotypes kept by elitism.
+        elitism: usize,
+        /// The size of the population.
+        population_size: usize,
+    },
+    /// The fitness values of the initial population could not be calculated.
+    /// Contains the message of the evaluator.
+    EvaluationFailed(String),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            BuildError::EmptyPopulation => {
+                write!(f, "Tried to build a simulator without a population, or the population \
+                           was empty.")
+            }
+            BuildError::InvalidSelector(ref e) => write!(f, "{}", e),
+            BuildError::InvalidElitism { elitism, population_size } => {
+                write!(f, "Invalid elitism: {}. Should be less than the population size {}.",
+                       elitism, population_size)
+            }
+            BuildError::EvaluationFailed(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for BuildError {}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Error::EvaluationFailed(String::from("Worker offline.")).to_string(),
+                   "Worker offline.");
+        let error = BuildError::InvalidElitism {
+            elitism: 10,
+            population_size: 10,
+        };
+        assert_eq!(error.to_string(),
+                   "Invalid elitism: 10. Should be less than the population size 10.");
+        let error = BuildError::InvalidSelector(String::from("Invalid parameter `count`: 0."));
+        assert_eq!(error.to_string(), "Invalid parameter `count`: 0.");
+    }
+}
@@ -0,0 +1,251 @@
+// file: asynchronous.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use futures::executor;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use std::future::Future;
+
+/// The number of evaluations an `AsyncEvaluator` has in flight at the same time, unless
+/// `with_max_concurrency` is called.
+const MAX_CONCURRENCY: usize = 16;
+
+/// Runs a future to completion, blocking the current thread.
+///
+/// Implement this for the runtime your futures need, such as a `tokio` runtime, and pass
+/// it to `AsyncEvaluator::with_executor`:
+///
+/// ```ignore
+/// struct Tokio(tokio::runtime::Runtime);
+///
+/// impl Executor for Tokio {
+///     fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+///         self.0.block_on(future)
+///     }
+/// }
+/// ```
+pub trait Executor {
+    /// Run `future` to completion and return its output.
+    fn block_on<F: Future>(&mut self, future: F) -> F::Output;
+}
+
+/// The default `Executor` of an `AsyncEvaluator`, which drives futures on the current
+/// thread with `futures::executor::block_on`. It does not provide the reactor of a runtime
+/// such as `tokio` or `async-std`, so futures that need one do not complete.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LocalExecutor;
+
+impl Executor for LocalExecutor {
+    fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+        executor::block_on(future)
+    }
+}
+
+/// Evaluates phenotypes with an asynchronous fitness function, awaiting the
+/// evaluations of a step concurrently.
+///
+/// This is useful when calculating the fitness value requires waiting on something,
+/// for example a remote service: instead of waiting for every round trip in turn,
+/// up to 16 requests are in flight at the same time. Call `with_max_concurrency` to
+/// change this.
+///
+/// By default, the futures are driven by a `LocalExecutor`, which supports futures that
+/// do not depend on a specific runtime. Most HTTP clients do need one; pass an `Executor`
+/// for that runtime to `with_executor`.
+pub struct AsyncEvaluator<F, E = LocalExecutor> {
+    fitness_function: F,
+    max_concurrency: usize,
+    executor: E,
+}
+
+impl<F> AsyncEvaluator<F> {
+    /// Create and return an asynchronous evaluator.
+    ///
+    /// `fitness_function` is called for every phenotype and returns a future that resolves
+    /// to its fitness value, or to an error message.
+    pub fn new(fitness_function: F) -> AsyncEvaluator<F> {
+        AsyncEvaluator {
+            fitness_function,
+            max_concurrency: MAX_CONCURRENCY,
+            executor: LocalExecutor,
+        }
+    }
+}
+
+impl<F, E> AsyncEvaluator<F, E> {
+    /// Limit the number of evaluations that are in flight at the same time. The default
+    /// is 16.
+    ///
+    /// * `max_concurrency`: must be larger than zero.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> AsyncEvaluator<F, E> {
+        self.max_concurrency = max_concurrency;
+        self
+    }
+
+    /// Drive the futures with `executor` instead of a `LocalExecutor`, for example to
+    /// provide the runtime they need.
+    pub fn with_executor<X: Executor>(self, executor: X) -> AsyncEvaluator<F, X> {
+        AsyncEvaluator {
+            fitness_function: self.fitness_function,
+            max_concurrency: self.max_concurrency,
+            executor,
+        }
+    }
+}
+
+impl<T, F, Fut, E> BatchEvaluator<T> for AsyncEvaluator<F, E>
+    where T: Phenotype,
+          F: Fn(&T) -> Fut,
+          Fut: Future<Output = Result<T::Fitness, String>>,
+          E: Executor
+{
+    fn evaluate(&mut self, phenotypes: &[T]) -> Result<Vec<T::Fitness>, String> {
+        if self.max_concurrency == 0 {
+            return Err(String::from("Invalid parameter `max_concurrency`: 0. Should be larger \
+                                     than zero."));
+        }
+        let fitness_function = &self.fitness_function;
+        let futures = phenotypes.iter().map(fitness_function);
+        let evaluations = stream::iter(futures).buffered(self.max_concurrency).try_collect();
+        self.executor.block_on(evaluations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::eval::*;
+    use futures::future::{ready, Ready};
+    use std::cell::Cell;
+    use std::cmp;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::rc::Rc;
+    use std::task::{Context, Poll};
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn fitness(x: &Test) -> Ready<Result<f64, String>> {
+        ready(Ok(x.f as f64))
+    }
+
+    /// A future that is pending once, and counts how many such futures are in flight.
+    struct Tracked {
+        started: bool,
+        in_flight: Rc<Cell<usize>>,
+        max_in_flight: Rc<Cell<usize>>,
+    }
+
+    impl Future for Tracked {
+        type Output = Result<f64, String>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<f64, String>> {
+            if self.started {
+                self.in_flight.set(self.in_flight.get() - 1);
+                return Poll::Ready(Ok(0.0));
+            }
+            self.started = true;
+            self.in_flight.set(self.in_flight.get() + 1);
+            self.max_in_flight.set(cmp::max(self.max_in_flight.get(), self.in_flight.get()));
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    /// An `Executor` that counts the futures it runs.
+    struct Counting(usize);
+
+    impl Executor for Counting {
+        fn block_on<F: Future>(&mut self, future: F) -> F::Output {
+            self.0 += 1;
+            LocalExecutor.block_on(future)
+        }
+    }
+
+    #[test]
+    fn test_order_preserved() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut evaluator = AsyncEvaluator::new(fitness).with_max_concurrency(8);
+        let fitnesses = evaluator.evaluate(&population).unwrap();
+        assert_eq!(fitnesses, (0..100).map(|i| i as f64).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_error_propagate() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut evaluator = AsyncEvaluator::new(|x: &Test| {
+            ready(if x.f == 50 {
+                Err(String::from("unreachable"))
+            } else {
+                Ok(0.0)
+            })
+        });
+        assert!(evaluator.evaluate(&population).is_err());
+    }
+
+    #[test]
+    fn test_default_max_concurrency() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let in_flight = Rc::new(Cell::new(0));
+        let max_in_flight = Rc::new(Cell::new(0));
+        let seen = max_in_flight.clone();
+        let mut evaluator = AsyncEvaluator::new(move |_: &Test| {
+            Tracked {
+                started: false,
+                in_flight: in_flight.clone(),
+                max_in_flight: max_in_flight.clone(),
+            }
+        });
+        assert_eq!(evaluator.evaluate(&population).unwrap().len(), 100);
+        assert_eq!(seen.get(), 16);
+    }
+
+    #[test]
+    fn test_executor() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut evaluator = AsyncEvaluator::new(fitness).with_executor(Counting(0));
+        evaluator.evaluate(&population).unwrap();
+        evaluator.evaluate(&population).unwrap();
+        assert_eq!(evaluator.executor.0, 2);
+    }
+
+    #[test]
+    fn test_max_concurrency_zero() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut evaluator = AsyncEvaluator::new(fitness).with_max_concurrency(0);
+        assert!(evaluator.evaluate(&population).is_err());
+    }
+}
@@ -0,0 +1,246 @@
+// file: distributed.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::{FitnessFunction, Phenotype};
+use super::*;
+use super::json_lines;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+/// Evaluates phenotypes on worker nodes, communicating over TCP.
+///
+/// Every batch is split into one chunk per worker, and the chunks are evaluated
+/// concurrently. For each chunk, a connection is opened to the worker, and the same
+/// line protocol as `ProcessEvaluator` is spoken: each phenotype is sent as JSON on a line
+/// of its own, and the worker answers with one fitness value per line, in the same order.
+/// Workers written in Rust can use `serve_worker`.
+///
+/// If a worker can not be reached, does not answer in time or answers with garbage,
+/// its chunk is retried on the next worker. The batch fails if a chunk fails on every
+/// attempt.
+pub struct DistributedEvaluator {
+    workers: Vec<SocketAddr>,
+    timeout: Duration,
+    retries: usize,
+}
+
+impl DistributedEvaluator {
+    /// Create a new `DistributedEvaluator` that farms out evaluations to `workers`.
+    ///
+    /// By default, the timeout is one minute and every chunk is retried twice.
+    pub fn new(workers: Vec<SocketAddr>) -> DistributedEvaluator {
+        DistributedEvaluator {
+            workers,
+            timeout: Duration::from_secs(60),
+            retries: 2,
+        }
+    }
+
+    /// Set the maximum time to wait for a worker to accept a connection, or to
+    /// send or receive a single piece of data. Must be larger than zero.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the number of times a chunk is retried on another worker after a failure.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Evaluate `chunk`, starting with the worker at index `first`.
+    fn evaluate_chunk<T, F>(&self, chunk: &[T], first: usize) -> Result<Vec<F>, String>
+        where T: Serialize,
+              F: DeserializeOwned
+    {
+        let mut errors = Vec::new();
+        for attempt in 0..self.retries + 1 {
+            let worker = self.workers[(first + attempt) % self.workers.len()];
+            match self.evaluate_on(worker, chunk) {
+                Ok(fitnesses) => return Ok(fitnesses),
+                Err(e) => errors.push(format!("{}: {}", worker, e)),
+            }
+        }
+        Err(format!("Could not evaluate phenotypes on any worker: {}",
+                    errors.join("; ")))
+    }
+
+    /// Evaluate `chunk` on `worker`.
+    fn evaluate_on<T, F>(&self, worker: SocketAddr, chunk: &[T]) -> Result<Vec<F>, String>
+        where T: Serialize,
+              F: DeserializeOwned
+    {
+        let connect = || -> io::Result<(TcpStream, TcpStream)> {
+            let stream = TcpStream::connect_timeout(&worker, self.timeout)?;
+            stream.set_read_timeout(Some(self.timeout))?;
+            stream.set_write_timeout(Some(self.timeout))?;
+            let writer = stream.try_clone()?;
+            Ok((writer, stream))
+        };
+        let (mut writer, reader) = connect().map_err(|e| format!("Could not connect: {}", e))?;
+        json_lines::exchange(chunk, &mut writer, &mut BufReader::new(reader))
+    }
+}
+
+impl<T> BatchEvaluator<T> for DistributedEvaluator
+    where T: Phenotype + Serialize + Sync,
+          T::Fitness: DeserializeOwned
+{
+    fn evaluate(&mut self, phenotypes: &[T]) -> Result<Vec<T::Fitness>, String> {
+        if phenotypes.is_empty() {
+            return Ok(Vec::new());
+        }
+        if self.workers.is_empty() {
+            return Err(String::from("There are no workers to evaluate phenotypes on."));
+        }
+        let chunk_size = phenotypes.len().div_ceil(self.workers.len());
+        let this = &*self;
+        thread::scope(|scope| {
+            let handles: Vec<_> = phenotypes.chunks(chunk_size)
+                                            .enumerate()
+                                            .map(|(i, chunk)| {
+                                                scope.spawn(move || this.evaluate_chunk(chunk, i))
+                                            })
+                                            .collect();
+            let mut fitnesses = Vec::with_capacity(phenotypes.len());
+            for handle in handles {
+                match handle.join() {
+                    Ok(result) => fitnesses.extend(result?),
+                    Err(_) => return Err(String::from("The thread evaluating phenotypes panicked.")),
+                }
+            }
+            Ok(fitnesses)
+        })
+    }
+}
+
+/// Run a worker for `DistributedEvaluator`s, evaluating phenotypes with `fitness_function`.
+///
+/// Every connection accepted on `listener` is handled on a thread of its own.
+/// This function only returns if `listener` stops accepting connections.
+pub fn serve_worker<T, F>(listener: TcpListener, fitness_function: F)
+    where T: Phenotype + DeserializeOwned,
+          T::Fitness: Serialize,
+          F: FitnessFunction<T>
+{
+    let fitness_function = &fitness_function;
+    thread::scope(|scope| {
+        for stream in listener.incoming().flatten() {
+            scope.spawn(move || handle_connection(stream, fitness_function));
+        }
+    })
+}
+
+/// Answer every phenotype received on `stream` with its fitness value.
+fn handle_connection<T, F>(stream: TcpStream, fitness_function: &F) -> io::Result<()>
+    where T: Phenotype + DeserializeOwned,
+          T::Fitness: Serialize,
+          F: FitnessFunction<T>
+{
+    stream.set_nodelay(true)?;
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let phenotype: T = serde_json::from_str(&line?).map_err(io::Error::other)?;
+        serde_json::to_writer(&mut writer, &fitness_function.fitness(&phenotype))?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::eval::*;
+    use std::net::{SocketAddr, TcpListener};
+    use std::thread;
+    use std::time::Duration;
+
+    #[derive(Clone, Serialize, Deserialize)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn start_worker() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || serve_worker(listener, |t: &Test| (t.f * 2) as f64));
+        address
+    }
+
+    #[test]
+    fn test_workers() {
+        let mut evaluator = DistributedEvaluator::new(vec![start_worker(), start_worker()]);
+        let population: Vec<Test> = (0..101).map(|i| Test { f: i }).collect();
+        let fitnesses = evaluator.evaluate(&population).unwrap();
+        assert_eq!(fitnesses.len(), 101);
+        for (i, fitness) in fitnesses.iter().enumerate() {
+            assert!((fitness - (i * 2) as f64).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_retry() {
+        // Nothing listens on the address of a dropped listener.
+        let unreachable = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let population: Vec<Test> = (0..10).map(|i| Test { f: i }).collect();
+        let mut evaluator = DistributedEvaluator::new(vec![unreachable, start_worker()])
+                                .with_retries(0);
+        assert!(evaluator.evaluate(&population).is_err());
+        let mut evaluator = evaluator.with_retries(1);
+        assert_eq!(evaluator.evaluate(&population).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        // Accept connections, but never answer.
+        thread::spawn(move || {
+            let _streams: Vec<_> = listener.incoming().collect();
+        });
+        let mut evaluator = DistributedEvaluator::new(vec![address])
+                                .with_timeout(Duration::from_millis(100))
+                                .with_retries(1);
+        let population: Vec<Test> = (0..10).map(|i| Test { f: i }).collect();
+        assert!(evaluator.evaluate(&population).is_err());
+    }
+}
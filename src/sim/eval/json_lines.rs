@@ -0,0 +1,80 @@
+// file: json_lines.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The line protocol shared by evaluators that talk to other programs: every phenotype is
+//! sent as JSON on a line of its own, and every fitness value comes back as JSON on a line
+//! of its own, in the same order.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json;
+use std::io::{BufRead, Write};
+use std::thread;
+
+/// Send `phenotypes` to `writer` and read their fitness values from `reader`.
+///
+/// Writing happens on a separate thread, so a peer that answers while it is still
+/// reading can not fill up both directions and deadlock.
+pub fn exchange<T, F, W, R>(phenotypes: &[T], writer: &mut W, reader: &mut R)
+                            -> Result<Vec<F>, String>
+    where T: Serialize,
+          F: DeserializeOwned,
+          W: Write + Send,
+          R: BufRead
+{
+    let mut request = Vec::new();
+    for x in phenotypes {
+        serde_json::to_writer(&mut request, x)
+            .map_err(|e| format!("Could not serialize phenotype: {}", e))?;
+        request.push(b'\n');
+    }
+    thread::scope(|scope| {
+        let sender = scope.spawn(move || {
+            writer.write_all(&request)?;
+            writer.flush()
+        });
+        let fitnesses = read_fitnesses(reader, phenotypes.len());
+        match sender.join() {
+            Ok(Ok(())) => fitnesses,
+            Ok(Err(e)) => Err(format!("Could not send phenotypes: {}", e)),
+            Err(_) => Err(String::from("The thread sending phenotypes panicked.")),
+        }
+    })
+}
+
+/// Read `count` fitness values from `reader`.
+fn read_fitnesses<F, R>(reader: &mut R, count: usize) -> Result<Vec<F>, String>
+    where F: DeserializeOwned,
+          R: BufRead
+{
+    let mut fitnesses = Vec::with_capacity(count);
+    let mut line = String::new();
+    while fitnesses.len() < count {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) => {
+                return Err(String::from("The connection was closed before all fitness values \
+                                         were received."));
+            }
+            Ok(_) => {}
+            Err(e) => return Err(format!("Could not receive fitness values: {}", e)),
+        }
+        let fitness = serde_json::from_str::<F>(line.trim())
+            .map_err(|e| format!("Invalid fitness value `{}`: {}", line.trim(), e))?;
+        fitnesses.push(fitness);
+    }
+    Ok(fitnesses)
+}
@@ -0,0 +1,71 @@
+// file: mod.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The evaluation module provides a trait that can be implemented
+//! to calculate the fitness values of many phenotypes at once.
+//!
+//! A `BatchEvaluator` receives all phenotypes created in a step in a single call.
+//! This allows vectorizing the evaluation, offloading it to a GPU, or issuing a single
+//! query to a database per step instead of one per phenotype.
+//!
+//! When the `async` feature is enabled, this module also provides an `AsyncEvaluator`,
+//! which awaits asynchronous fitness evaluations concurrently.
+//!
+//! When the `serde` feature is enabled, this module also provides a `ProcessEvaluator`,
+//! which evaluates phenotypes in a child process, written in any language.
+//!
+//! When the `distributed` feature is enabled, this module also provides a
+//! `DistributedEvaluator`, which evaluates phenotypes on worker nodes over TCP,
+//! and `serve_worker`, which runs such a worker.
+
+#[cfg(feature = "async")]
+mod asynchronous;
+#[cfg(feature = "distributed")]
+mod distributed;
+#[cfg(feature = "serde")]
+mod json_lines;
+#[cfg(feature = "serde")]
+mod process;
+
+use pheno::Phenotype;
+
+#[cfg(feature = "async")]
+pub use self::asynchronous::{AsyncEvaluator, Executor, LocalExecutor};
+#[cfg(feature = "distributed")]
+pub use self::distributed::{DistributedEvaluator, serve_worker};
+#[cfg(feature = "serde")]
+pub use self::process::ProcessEvaluator;
+
+/// A `BatchEvaluator` calculates the fitness values of a batch of phenotypes.
+pub trait BatchEvaluator<T: Phenotype> {
+    /// Calculate the fitness values of `phenotypes`.
+    ///
+    /// If the evaluation fails, this function returns an `Err(String)`,
+    /// containing a message indicating the error.
+    ///
+    /// Otherwise it contains a vector of fitness values wrapped in `Ok`, with the fitness
+    /// value of `phenotypes[i]` at index `i`.
+    fn evaluate(&mut self, phenotypes: &[T]) -> Result<Vec<T::Fitness>, String>;
+}
+
+impl<T, F> BatchEvaluator<T> for F
+    where T: Phenotype,
+          F: FnMut(&[T]) -> Result<Vec<T::Fitness>, String>
+{
+    fn evaluate(&mut self, phenotypes: &[T]) -> Result<Vec<T::Fitness>, String> {
+        self(phenotypes)
+    }
+}
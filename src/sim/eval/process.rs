@@ -0,0 +1,133 @@
+// file: process.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::json_lines;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::io::{self, BufReader};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+/// Evaluates phenotypes in a child process, communicating over its standard input and output.
+///
+/// For every batch, each phenotype is serialized to JSON and written to the standard input
+/// of the child process on a line of its own. The child process must answer with one line
+/// per phenotype on its standard output, containing the fitness value as JSON, for example
+/// a number, in the same order. The child process is started once and reused for every batch.
+///
+/// This allows evaluating phenotypes with programs written in other languages.
+/// The child process is killed when the evaluator is dropped.
+pub struct ProcessEvaluator {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl ProcessEvaluator {
+    /// Start the child process described by `command` and return an evaluator that uses it.
+    ///
+    /// The standard input and output of `command` are replaced by pipes.
+    pub fn spawn(mut command: Command) -> io::Result<ProcessEvaluator> {
+        let mut child = command.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+        let stdin = child.stdin.take().ok_or_else(|| io::Error::other("No stdin"))?;
+        let stdout = child.stdout.take().ok_or_else(|| io::Error::other("No stdout"))?;
+        Ok(ProcessEvaluator {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+        })
+    }
+}
+
+impl<T> BatchEvaluator<T> for ProcessEvaluator
+    where T: Phenotype + Serialize,
+          T::Fitness: DeserializeOwned
+{
+    fn evaluate(&mut self, phenotypes: &[T]) -> Result<Vec<T::Fitness>, String> {
+        json_lines::exchange(phenotypes, &mut self.stdin, &mut self.stdout)
+    }
+}
+
+impl Drop for ProcessEvaluator {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use ::sim::*;
+    use ::sim::eval::*;
+    use std::process::Command;
+
+    #[derive(Clone, Serialize)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn shell(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        command
+    }
+
+    #[test]
+    fn test_line_protocol() {
+        // Answer with the length of each line, which is 6 + the number of digits in `f`.
+        let mut evaluator = ProcessEvaluator::spawn(shell("while read -r l; do echo ${#l}; done"))
+                                .unwrap();
+        let population: Vec<Test> = (0..1000).map(|i| Test { f: i }).collect();
+        for _ in 0..2 {
+            let fitnesses = evaluator.evaluate(&population).unwrap();
+            assert_eq!(fitnesses.len(), 1000);
+            assert!((fitnesses[5] - 7.0).abs() < 0.001);
+            assert!((fitnesses[500] - 9.0).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn test_process_exits() {
+        let mut evaluator = ProcessEvaluator::spawn(shell("read -r l; echo 1")).unwrap();
+        let population: Vec<Test> = (0..2).map(|i| Test { f: i }).collect();
+        assert!(evaluator.evaluate(&population).is_err());
+    }
+
+    #[test]
+    fn test_invalid_output() {
+        let mut evaluator = ProcessEvaluator::spawn(shell("while read -r l; do echo x; done"))
+                                .unwrap();
+        let population: Vec<Test> = (0..2).map(|i| Test { f: i }).collect();
+        assert!(evaluator.evaluate(&population).is_err());
+    }
+}
@@ -0,0 +1,132 @@
+// file: hall_of_fame.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::{FitnessType, Individual};
+
+/// A bounded archive of the best distinct phenotypes found during a `Simulation`.
+#[derive(Clone)]
+pub struct HallOfFame<T: Phenotype> {
+    /// The maximum number of members.
+    size: usize,
+    /// The members, best performing first.
+    members: Vec<Individual<T>>,
+    /// Returns whether two phenotypes are the same.
+    same: fn(&T, &T) -> bool,
+}
+
+impl<T: Phenotype + PartialEq> HallOfFame<T> {
+    /// Create an empty archive of at most `size` members.
+    pub fn new(size: usize) -> HallOfFame<T> {
+        HallOfFame {
+            size,
+            members: Vec::new(),
+            same: <T as PartialEq>::eq,
+        }
+    }
+}
+
+impl<T: Phenotype> HallOfFame<T> {
+    /// Add the members of `individuals` that perform better than the worst member,
+    /// unless they are already in the archive.
+    pub fn update(&mut self, individuals: &[Individual<T>], fitness_type: FitnessType) {
+        for individual in individuals {
+            if self.members.len() == self.size {
+                match self.members.last() {
+                    Some(worst) if individual.is_better(worst, fitness_type) => {}
+                    _ => continue,
+                }
+            }
+            let same = self.same;
+            if self.members.iter().any(|x| same(x.phenotype(), individual.phenotype())) {
+                continue;
+            }
+            let index = self.members
+                            .iter()
+                            .position(|x| individual.is_better(x, fitness_type))
+                            .unwrap_or(self.members.len());
+            self.members.insert(index, individual.clone());
+            self.members.truncate(self.size);
+        }
+    }
+
+    /// Replace the members by `members`, which must be distinct and sorted best performing
+    /// first, such as the members of an earlier archive.
+    #[cfg(feature = "serde")]
+    pub fn restore(&mut self, mut members: Vec<Individual<T>>) {
+        members.truncate(self.size);
+        self.members = members;
+    }
+
+    /// Get the members, best performing first.
+    pub fn members(&self) -> &[Individual<T>] {
+        &self.members
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use super::HallOfFame;
+
+    #[derive(Clone, PartialEq)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn fitnesses(hall_of_fame: &HallOfFame<Test>) -> Vec<f64> {
+        hall_of_fame.members().iter().map(|x| *x.fitness()).collect()
+    }
+
+    #[test]
+    fn test_update() {
+        let mut hall_of_fame = HallOfFame::new(3);
+        let population: Vec<Individual<Test>> = [5, 1, 5, 3, -5].iter()
+                                                                 .map(|&f| Test { f })
+                                                                 .map(Individual::new)
+                                                                 .collect();
+        hall_of_fame.update(&population, FitnessType::Maximize);
+        // Both phenotypes with a fitness value of 5 are kept, but not their duplicate.
+        assert_eq!(fitnesses(&hall_of_fame), vec![5.0, 5.0, 3.0]);
+        hall_of_fame.update(&[Individual::new(Test { f: 4 })], FitnessType::Maximize);
+        assert_eq!(fitnesses(&hall_of_fame), vec![5.0, 5.0, 4.0]);
+    }
+
+    #[test]
+    fn test_minimize() {
+        let mut hall_of_fame = HallOfFame::new(2);
+        let population: Vec<Individual<Test>> = (0..10).map(|f| Individual::new(Test { f }))
+                                                       .collect();
+        hall_of_fame.update(&population, FitnessType::Minimize);
+        assert_eq!(fitnesses(&hall_of_fame), vec![0.0, 1.0]);
+    }
+}
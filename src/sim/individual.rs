@@ -0,0 +1,225 @@
+// file: individual.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::FitnessType;
+use pheno::{Fitness, Phenotype};
+use std::cmp::Ordering;
+use std::ops::Deref;
+
+/// A member of the population of a `Simulation`.
+///
+/// An `Individual` wraps a `Phenotype` together with the bookkeeping the simulator
+/// keeps about it. It dereferences to the wrapped `Phenotype`.
+///
+/// The fitness value of the `Phenotype` is calculated once, when the `Individual` is
+/// created, and cached from then on. Crossover and mutation always create new
+/// `Individual`s, so the cached value can never go stale.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Individual<T: Phenotype> {
+    phenotype: T,
+    fitness: T::Fitness,
+    age: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    violation: f64,
+}
+
+impl<T: Phenotype> Individual<T> {
+    /// Create a new `Individual` of age zero, calculating its fitness value.
+    pub fn new(phenotype: T) -> Individual<T> {
+        let fitness = phenotype.fitness();
+        Individual::with_fitness(phenotype, fitness)
+    }
+
+    /// Create a new `Individual` of age zero with an already calculated fitness value.
+    pub(super) fn with_fitness(phenotype: T, fitness: T::Fitness) -> Individual<T> {
+        Individual {
+            phenotype,
+            fitness,
+            age: 0,
+            violation: 0.0,
+        }
+    }
+
+    /// Get the wrapped `Phenotype`.
+    pub fn phenotype(&self) -> &T {
+        &self.phenotype
+    }
+
+    /// Get the cached fitness value of the wrapped `Phenotype`.
+    pub fn fitness(&self) -> &T::Fitness {
+        &self.fitness
+    }
+
+    /// Compare the cached fitness value of this `Individual` with that of `other`.
+    ///
+    /// Fitness values that can not be compared, such as `Objectives` of which neither
+    /// dominates the other, are compared by their `Fitness::to_f64` values instead, so
+    /// sorting a population by this ordering is always consistent.
+    pub fn cmp_fitness(&self, other: &Individual<T>) -> Ordering {
+        self.fitness
+            .partial_cmp(&other.fitness)
+            .or_else(|| self.fitness.to_f64().partial_cmp(&other.fitness.to_f64()))
+            .unwrap_or(Ordering::Equal)
+    }
+
+    /// Compare how well this `Individual` performs with `other`, either maximizing or
+    /// minimizing the fitness (`fitness_type`). Returns `Greater` if this one is better.
+    ///
+    /// An `Individual` that violates its constraints less always performs better, so
+    /// feasible ones beat infeasible ones. Otherwise, the fitness values are compared by
+    /// `cmp_fitness`.
+    pub fn cmp_rank(&self, other: &Individual<T>, fitness_type: FitnessType) -> Ordering {
+        match other.violation.partial_cmp(&self.violation) {
+            Some(Ordering::Equal) | None => match fitness_type {
+                FitnessType::Maximize => self.cmp_fitness(other),
+                FitnessType::Minimize => other.cmp_fitness(self),
+            },
+            Some(ordering) => ordering,
+        }
+    }
+
+    /// Check whether this `Individual` performs strictly better than `other`, either
+    /// maximizing or minimizing the fitness (`fitness_type`).
+    ///
+    /// Like `cmp_rank`, but fitness values that can not be compared are never better.
+    pub fn is_better(&self, other: &Individual<T>, fitness_type: FitnessType) -> bool {
+        if self.violation != other.violation {
+            return self.violation < other.violation;
+        }
+        match fitness_type {
+            FitnessType::Maximize => self.fitness > other.fitness,
+            FitnessType::Minimize => self.fitness < other.fitness,
+        }
+    }
+
+    /// Get the total violation of the constraints of the wrapped `Phenotype`, which is
+    /// zero if it is feasible. See `pheno::Constrained`.
+    pub fn violation(&self) -> f64 {
+        self.violation
+    }
+
+    /// Set the total violation of the constraints of the wrapped `Phenotype`.
+    pub(super) fn set_violation(&mut self, violation: f64) {
+        self.violation = violation;
+    }
+
+    /// Get the number of steps this `Individual` has survived.
+    pub fn age(&self) -> u64 {
+        self.age
+    }
+
+    /// Increment the age of this `Individual` by one step.
+    pub(super) fn grow_older(&mut self) {
+        self.age += 1;
+    }
+
+    /// Unwrap the `Phenotype`.
+    pub fn into_phenotype(self) -> T {
+        self.phenotype
+    }
+}
+
+impl<T: Phenotype> From<T> for Individual<T> {
+    fn from(phenotype: T) -> Individual<T> {
+        Individual::new(phenotype)
+    }
+}
+
+impl<T: Phenotype> Deref for Individual<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.phenotype
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use std::cell::Cell;
+
+    thread_local!(static EVALUATIONS: Cell<usize> = const { Cell::new(0) });
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            EVALUATIONS.with(|e| e.set(e.get() + 1));
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_age() {
+        let mut individual = Individual::new(Test { f: -3 });
+        assert_eq!(individual.age(), 0);
+        individual.grow_older();
+        individual.grow_older();
+        assert_eq!(individual.age(), 2);
+    }
+
+    #[test]
+    fn test_deref() {
+        let individual = Individual::from(Test { f: -3 });
+        assert_eq!(individual.phenotype().f, -3);
+        assert!((individual.fitness() - 3.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_fitness_cached() {
+        let individual = Individual::new(Test { f: 7 });
+        let before = EVALUATIONS.with(|e| e.get());
+        for _ in 0..10 {
+            assert!((individual.fitness() - 7.0).abs() < 0.001);
+        }
+        assert!((individual.clone().fitness() - 7.0).abs() < 0.001);
+        assert_eq!(EVALUATIONS.with(|e| e.get()), before);
+    }
+
+    #[test]
+    fn test_cmp_rank() {
+        use std::cmp::Ordering;
+
+        let good = Individual::new(Test { f: 7 });
+        let mut infeasible = Individual::new(Test { f: 9 });
+        infeasible.set_violation(2.0);
+        let mut less_infeasible = Individual::new(Test { f: 3 });
+        less_infeasible.set_violation(1.0);
+        for &fitness_type in &[FitnessType::Maximize, FitnessType::Minimize] {
+            assert_eq!(good.cmp_rank(&infeasible, fitness_type), Ordering::Greater);
+            assert_eq!(infeasible.cmp_rank(&less_infeasible, fitness_type), Ordering::Less);
+            assert!(good.is_better(&infeasible, fitness_type));
+            assert!(!infeasible.is_better(&less_infeasible, fitness_type));
+        }
+        let bad = Individual::new(Test { f: 3 });
+        assert_eq!(good.cmp_rank(&bad, FitnessType::Maximize), Ordering::Greater);
+        assert_eq!(good.cmp_rank(&bad, FitnessType::Minimize), Ordering::Less);
+    }
+}
@@ -15,6 +15,8 @@
 // limitations under the License.
 
 /// An iteration limiter.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IterLimit {
     /// Maximum number of iterations allowed.
     max: u64,
@@ -25,7 +27,7 @@ pub struct IterLimit {
 impl IterLimit {
     /// Create a new iteration limiter.
     pub fn new(max: u64) -> IterLimit {
-        IterLimit { max: max, cur: 0 }
+        IterLimit { max, cur: 0 }
     }
 
     /// Increment the number of iterations.
@@ -43,6 +45,21 @@ impl IterLimit {
         self.cur = 0;
     }
 
+    /// Set the current number of iterations.
+    pub fn set(&mut self, cur: u64) {
+        self.cur = cur;
+    }
+
+    /// Set the maximum number of iterations allowed.
+    pub fn set_max(&mut self, max: u64) {
+        self.max = max;
+    }
+
+    /// Get the maximum number of iterations allowed.
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
     /// Get the current number of iterations.
     pub fn get(&self) -> u64 {
         self.cur
@@ -59,9 +76,9 @@ mod tests {
         for _ in 0..4 {
             limit.inc();
         }
-        assert_eq!(limit.reached(), false);
+        assert!(!limit.reached());
         limit.reset();
-        assert_eq!(limit.reached(), false);
+        assert!(!limit.reached());
     }
 
     #[test]
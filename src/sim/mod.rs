@@ -17,22 +17,59 @@
 use pheno::Phenotype;
 
 pub mod seq;
+pub mod par;
 pub mod select;
+pub mod replace;
+pub mod eval;
+pub mod stop;
+pub mod pareto;
+pub mod species;
+pub mod chc;
+#[cfg(feature = "serde")]
+mod checkpoint;
 mod iterlimit;
 mod earlystopper;
+mod error;
+mod hall_of_fame;
+mod individual;
+mod observer;
+#[cfg(feature = "plotting")]
+mod plot;
+mod progress;
+mod report;
+mod rng;
+mod stats;
+
+#[cfg(feature = "serde")]
+pub use self::checkpoint::Checkpoint;
+pub use self::earlystopper::{EarlyStopMetric, EarlyStopState};
+pub use self::error::{BuildError, Error};
+pub use self::individual::Individual;
+pub use self::observer::{Event, Observer};
+pub use self::progress::print_progress;
+pub use self::report::{Report, StopReason};
+pub use self::rng::{FromEntropy, Reseed};
+pub use self::stats::{GenerationStats, HistoryEntry};
 
 /// A `Builder` can create new instances of an object.
 /// For this library, only `Simulation` objects use this `Builder`.
 pub trait Builder<T> {
-    /// Return the result.
-    fn build(self) -> T;
+    /// Return the result, or a `BuildError` if the settings of the `Builder` are invalid.
+    fn build(self) -> Result<T, BuildError>;
 }
 
+/// Marks a `SimulatorBuilder` that has no population yet, and can not build a
+/// `Simulation`.
+pub enum NoPopulation {}
+
+/// Marks a `SimulatorBuilder` that has a population, and can build a `Simulation`.
+pub enum WithPopulation {}
+
 /// Simulation run time is defined in nanoseconds.
 pub type NanoSecond = i64;
 /// The result of a simulation, containing the best phenotype
-/// or an error message.
-pub type SimResult<T> = Result<Box<T>, String>;
+/// or the error that occurred.
+pub type SimResult<T> = Result<T, Error>;
 
 /// The result of running a single step.
 #[derive(PartialEq,Eq,Debug)]
@@ -55,9 +92,10 @@ pub enum RunResult {
 }
 
 /// A `Simulation` is an execution of a genetic algorithm.
-pub trait Simulation<T: Phenotype> {
-    /// A `Builder` is used to create instances of a `Simulation`.
-    type B: Builder<Box<Self>>;
+pub trait Simulation<T: Phenotype>: Sized {
+    /// A builder is used to create instances of a `Simulation`. It becomes a `Builder`
+    /// once a population has been set.
+    type B;
 
     /// Create a `Builder` to create an instance.
     fn builder() -> Self::B;
@@ -74,25 +112,57 @@ pub trait Simulation<T: Phenotype> {
     /// reaching a maximum number of iterations, consider using `run()` instead:
     /// This function is mostly useful for making illustrations of the evolution.
     fn step(&mut self) -> StepResult;
+    /// Make up to `n` steps in the simulation, stopping as soon as a step does not return
+    /// `StepResult::Success`. Returns the result of the last step, or `StepResult::Success`
+    /// if `n` is zero.
+    ///
+    /// This is useful for running a number of generations between updates of a user interface.
+    fn step_n(&mut self, n: u64) -> StepResult {
+        for _ in 0..n {
+            match self.step() {
+                StepResult::Success => {}
+                result => return result,
+            }
+        }
+        StepResult::Success
+    }
     /// Get the result of the latest step or of a complete run.
     ///
-    /// This function will either return the best performing individual,
-    /// or an error string indicating what went wrong.
+    /// This function will either return the best performing individual found so far,
+    /// even if it is no longer part of the population, or the `Error` that occurred.
     fn get(&self) -> SimResult<T>;
     /// Get the number of nanoseconds spent running, or `None` in case of an overflow.
-    ///
-    /// When `Self` is `par::Simulator`, i.e. a parallel simulator is used,
-    /// the duration is the average duration of all child simulators.
     fn time(&self) -> Option<NanoSecond>;
     /// Get the number of iterations the `Simulator` has executed so far.
-    ///
-    /// When `Self` is `par::Simulator`, i.e. a parallel simulator is used,
-    /// this returns the number of iterations made by the parallel simulator itself.
     fn iterations(&self) -> u64;
 }
 
+/// Information about a completed step of a `Simulation`, passed to the step callback
+/// set with `set_step_callback` on a `SimulatorBuilder`.
+pub struct StepInfo<'a, T: Phenotype + 'a> {
+    /// The number of steps completed so far, including this one.
+    pub generation: u64,
+    /// The maximum number of steps.
+    pub max_generations: u64,
+    /// The number of nanoseconds spent running so far, or `None` in case of an overflow.
+    pub elapsed: Option<NanoSecond>,
+    /// The estimated number of nanoseconds until the maximum number of steps is reached,
+    /// based on the average duration of the steps included in `elapsed`. Iterations skipped
+    /// by `resume` are not included.
+    pub eta: Option<NanoSecond>,
+    /// Whether the fitness values are being maximized or minimized.
+    pub fitness_type: FitnessType,
+    /// The fitness value of the best performing phenotype in the population.
+    pub best_fitness: f64,
+    /// The mean fitness value of the population.
+    pub mean_fitness: f64,
+    /// The population after the step.
+    pub population: &'a [Individual<T>],
+}
+
 /// Whether to maximize or to minimize the fitness value.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum FitnessType {
     /// The `Simulation` will try to increase the fitness value of phenotypes.
     Maximize,
@@ -0,0 +1,157 @@
+// file: observer.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::{Error, Individual};
+use super::select::Parents;
+
+/// Something that happened during a `Simulation`, reported to every `Observer`.
+pub enum Event<'a, T: Phenotype + 'a> {
+    /// A step started.
+    GenerationStarted {
+        /// The number of the step, counting from one.
+        generation: u64,
+    },
+    /// Parents were selected for the step.
+    SelectionCompleted {
+        /// The selected parents.
+        parents: &'a Parents<'a, T>,
+    },
+    /// Children were created and evaluated, but not yet added to the population.
+    OffspringCreated {
+        /// The children.
+        children: &'a [Individual<T>],
+    },
+    /// The `Simulation` finished, because a stopping criterion was met.
+    /// This event is reported once.
+    SimulationFinished {
+        /// The number of steps that were run.
+        generations: u64,
+    },
+    /// The `Simulation` failed.
+    SimulationFailed {
+        /// The error that occurred.
+        error: &'a Error,
+    },
+}
+
+/// An `Observer` is notified of the `Event`s of a `Simulation`.
+///
+/// Observers can be registered by calling `add_observer` on a `SimulatorBuilder`.
+/// This is the integration point for loggers, dashboards and debuggers.
+///
+/// Closures of the form `FnMut(&Event<T>)` are `Observer`s as well.
+pub trait Observer<T: Phenotype> {
+    /// Handle `event`.
+    fn notify(&mut self, event: &Event<T>);
+}
+
+impl<T, F> Observer<T> for F
+    where T: Phenotype,
+          F: FnMut(&Event<T>)
+{
+    fn notify(&mut self, event: &Event<T>) {
+        self(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+    use rand::Rng;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    /// A `Selector` that accepts any population, but always fails to select.
+    #[derive(Clone)]
+    struct FailingSelector;
+
+    impl Selector<Test> for FailingSelector {
+        fn select<'a>(&self,
+                      _: &'a [Individual<Test>],
+                      _: FitnessType,
+                      _: &mut dyn Rng)
+                      -> Result<Parents<'a, Test>, String> {
+            Err(String::from("Selection failed."))
+        }
+    }
+
+    fn record(events: &Rc<RefCell<Vec<String>>>) -> Box<dyn Observer<Test>> {
+        let events = events.clone();
+        Box::new(move |event: &Event<Test>| {
+            let name = match *event {
+                Event::GenerationStarted { generation } => format!("started {}", generation),
+                Event::SelectionCompleted { parents } => format!("selected {}", parents.len()),
+                Event::OffspringCreated { children } => format!("created {}", children.len()),
+                Event::SimulationFinished { generations } => format!("finished {}", generations),
+                Event::SimulationFailed { .. } => String::from("failed"),
+            };
+            events.borrow_mut().push(name);
+        })
+    }
+
+    #[test]
+    fn test_events() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(2)
+                        .add_observer(record(&events))
+                        .build().unwrap();
+        s.run();
+        s.run();
+        assert_eq!(*events.borrow(),
+                   vec!["started 1", "selected 5", "created 5", "started 2", "selected 5",
+                        "created 5", "finished 2"]);
+    }
+
+    #[test]
+    fn test_failure() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(FailingSelector))
+                        .add_observer(record(&events))
+                        .build().unwrap();
+        s.run();
+        assert_eq!(*events.borrow(), vec!["started 1", "failed"]);
+    }
+}
@@ -0,0 +1,828 @@
+// file: par.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains a parallel implementation of `::sim::Simulation`,
+//! called a `Simulator`.
+//!
+//! This `Simulator` behaves exactly like `::sim::seq::Simulator`, but evaluates fitness
+//! values, runs selection tournaments and creates offspring on multiple threads using
+//! `rayon`. This pays off when `Phenotype::fitness()`, `crossover()` or `mutate()` are
+//! expensive, or when selecting thousands of parents.
+//!
+//! Selection runs on multiple threads for the selectors that support it, through
+//! `Selector::set_parallel`: `TournamentSelector`, `TruncationSelector` and
+//! `CrowdedTournamentSelector`, also when wrapped by another selector. Other selectors
+//! select on the calling thread.
+//!
+//! To use a `Simulator`, you need a `SimulatorBuilder`, which you can
+//! obtain by calling `Simulator::builder()`.
+
+use pheno::{Constrained, Distance, FitnessFunction, Phenotype};
+use rand::{Isaac64Rng, Rng, ThreadRng, XorShiftRng};
+use rayon::prelude::*;
+use super::*;
+use super::select::*;
+use super::replace::*;
+use super::eval::*;
+use super::stop::StoppingCondition;
+use super::pareto::Decomposition;
+use super::seq;
+use std::time::Duration;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "csv")]
+use std::io::Write;
+#[cfg(any(feature = "serde", feature = "plotting"))]
+use std::path::Path;
+
+/// A parallel implementation of `::sim::Simulation`.
+/// Fitness evaluation, selection and offspring creation are spread over a thread pool.
+/// See the module documentation.
+///
+/// Like `seq::Simulator`, it is generic over its random number generator `R`.
+pub struct Simulator<T: Phenotype + Send + Sync, R: Rng = ThreadRng> {
+    sim: seq::Simulator<T, R>,
+}
+
+impl<T: Phenotype + Send + Sync> Simulator<T> {
+    /// Create builder, for a `Simulator` that uses the thread-local random number generator.
+    /// See `seq::Simulator::builder`.
+    pub fn builder() -> SimulatorBuilder<T> {
+        Simulator::builder_with_rng(::rand::thread_rng())
+    }
+}
+
+impl<T: Phenotype + Send + Sync, R: Rng> Simulator<T, R> {
+    /// Create builder, for a `Simulator` that uses `rng`.
+    fn builder_with_rng(rng: R) -> SimulatorBuilder<T, R> {
+        let mut builder = seq::Simulator::builder_with_rng(rng);
+        builder.sim.breed = breed;
+        builder.sim.evaluate = evaluate;
+        SimulatorBuilder { builder }
+    }
+}
+
+impl<T: Phenotype + Send + Sync, R: FromEntropy> Simulation<T> for Simulator<T, R> {
+    type B = SimulatorBuilder<T, R>;
+
+    /// Create builder.
+    fn builder() -> SimulatorBuilder<T, R> {
+        Simulator::builder_with_rng(R::from_entropy())
+    }
+
+    fn step(&mut self) -> StepResult {
+        self.sim.step()
+    }
+
+    fn run(&mut self) -> RunResult {
+        self.sim.run()
+    }
+
+    fn get(&self) -> SimResult<T> {
+        self.sim.get()
+    }
+
+    fn iterations(&self) -> u64 {
+        self.sim.iterations()
+    }
+
+    fn time(&self) -> Option<NanoSecond> {
+        self.sim.time()
+    }
+}
+
+impl<T: Phenotype + Send + Sync, R: FromEntropy> Simulator<T, R> {
+    /// Return an iterator that makes one step for every item, yielding the statistics of
+    /// that step. See `seq::Simulator::iter_steps`.
+    pub fn iter_steps(&mut self) -> seq::Steps<'_, T, R> {
+        self.sim.iter_steps()
+    }
+
+    /// Run the simulation until `predicate` returns `true` for the statistics of a step.
+    /// See `seq::Simulator::run_until`.
+    pub fn run_until<F: FnMut(&GenerationStats) -> bool>(&mut self, predicate: F) -> RunResult {
+        self.sim.run_until(predicate)
+    }
+}
+
+impl<T: Phenotype + Send + Sync, R: Rng> Simulator<T, R> {
+    /// Get the statistics of every step so far.
+    /// See `seq::Simulator::stats_history`.
+    pub fn stats_history(&self) -> &[GenerationStats] {
+        self.sim.stats_history()
+    }
+
+    /// Write the statistics of every step so far to `writer` in CSV form.
+    /// See `seq::Simulator::write_stats_csv`.
+    #[cfg(feature = "csv")]
+    pub fn write_stats_csv<W: Write>(&self, writer: W) -> Result<(), String> {
+        self.sim.write_stats_csv(writer)
+    }
+
+    /// Get the best fitness value of every step so far, in order.
+    /// See `seq::Simulator::history`.
+    pub fn history(&self) -> &[HistoryEntry<T>] {
+        self.sim.history()
+    }
+
+    /// Get the current population. See `seq::Simulator::population`.
+    pub fn population(&self) -> &[Individual<T>] {
+        self.sim.population()
+    }
+
+    /// Consume this `Simulator` and move the phenotypes of the current population out.
+    /// See `seq::Simulator::into_population`.
+    pub fn into_population(self) -> Vec<T> {
+        self.sim.into_population()
+    }
+
+    /// Get the phenotypes in the current population that are not dominated by any other
+    /// phenotype in it. See `seq::Simulator::pareto_front`.
+    pub fn pareto_front(&self) -> Vec<T> {
+        self.sim.pareto_front()
+    }
+
+    /// Calculate the hypervolume indicator of the phenotypes returned by `pareto_front`,
+    /// relative to `reference`. See `seq::Simulator::hypervolume`.
+    pub fn hypervolume(&self, reference: &[f64]) -> f64 {
+        self.sim.hypervolume(reference)
+    }
+
+    /// Get the best distinct phenotypes found so far, best performing first.
+    /// See `seq::Simulator::hall_of_fame`.
+    pub fn hall_of_fame(&self) -> &[Individual<T>] {
+        self.sim.hall_of_fame()
+    }
+
+    /// Render the best fitness value of every step so far as a line chart to the file at
+    /// `path`. See `seq::Simulator::plot_history`.
+    #[cfg(feature = "plotting")]
+    pub fn plot_history<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        self.sim.plot_history(path)
+    }
+
+    /// Get the progress of early stopping.
+    /// See `seq::Simulator::early_stop_state`.
+    pub fn early_stop_state(&self) -> Option<EarlyStopState> {
+        self.sim.early_stop_state()
+    }
+
+    /// Summarize the settings of this `Simulator` and the outcome of its run so far.
+    /// See `seq::Simulator::report`.
+    pub fn report(&self) -> Report {
+        self.sim.report()
+    }
+}
+
+impl<T: Phenotype + Send + Sync, R: Rng + Clone> Simulator<T, R> {
+    /// Create a builder for a copy of this `Simulator`, to continue the run in two
+    /// directions from here. See `seq::Simulator::fork`.
+    ///
+    /// Returns a builder that can build a `Simulator`, for chaining purposes.
+    pub fn fork(&self) -> SimulatorBuilder<T, R, WithPopulation> {
+        SimulatorBuilder { builder: self.sim.fork() }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Phenotype + Send + Sync, R: Reseed> Simulator<T, R> {
+    /// Take a snapshot of the state of this `Simulator`.
+    /// See `seq::Simulator::checkpoint`.
+    pub fn checkpoint(&mut self) -> Checkpoint<T> {
+        self.sim.checkpoint()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Phenotype + Send + Sync + Serialize, R: Reseed> Simulator<T, R>
+    where T::Fitness: Serialize
+{
+    /// Save a snapshot of the state of this `Simulator` to the file at `path`.
+    /// See `seq::Simulator::save_checkpoint`.
+    pub fn save_checkpoint<P: AsRef<Path>>(&mut self, path: P) -> Result<(), String> {
+        self.sim.save_checkpoint(path)
+    }
+}
+
+/// Create children from the selected parents and mutate them, in parallel.
+///
+/// Every pair of parents gets its own random number generator, seeded from `rng`,
+/// so the children do not depend on how the work is spread over the threads.
+fn breed<T: Phenotype + Send + Sync>(parents: &Parents<'_, T>, mut rng: &mut dyn Rng) -> Vec<T> {
+    let rngs: Vec<XorShiftRng> = parents.iter().map(|_| Rng::gen(&mut rng)).collect();
+    parents.par_iter()
+           .zip(rngs)
+           .map(|(pair, mut rng): (&(&T, &T), XorShiftRng)| {
+               let child = pair.0.crossover_with_rng(pair.1, &mut rng);
+               child.mutate_with_rng(&mut rng)
+           })
+           .collect()
+}
+
+/// Calculate the fitness values of `phenotypes`, in parallel.
+pub(super) fn evaluate<T: Phenotype + Send + Sync>(phenotypes: Vec<T>,
+                                        fitness_function: &dyn FitnessFunction<T>)
+                                        -> Vec<Individual<T>> {
+    phenotypes.into_par_iter()
+              .map(|x| {
+                  let fitness = fitness_function.fitness(&x);
+                  Individual::with_fitness(x, fitness)
+              })
+              .collect()
+}
+
+/// A `Builder` for the `Simulator` type.
+/// See `seq::SimulatorBuilder`.
+pub struct SimulatorBuilder<T: Phenotype + Send + Sync, R: Rng = ThreadRng, P = NoPopulation> {
+    builder: seq::SimulatorBuilder<T, R, P>,
+}
+
+impl<T: Phenotype + Send + Sync, R: Rng, P> SimulatorBuilder<T, R, P> {
+    /// Set the population of the resulting `Simulator`.
+    ///
+    /// Returns a builder that can build a `Simulator`, for chaining purposes.
+    pub fn set_population(self, pop: Vec<T>) -> SimulatorBuilder<T, R, WithPopulation> {
+        SimulatorBuilder { builder: self.builder.set_population(pop) }
+    }
+
+    /// Set the population of the resulting `Simulator` to the phenotypes yielded by
+    /// `pop`. See `seq::SimulatorBuilder::set_population_iter`.
+    ///
+    /// Returns a builder that can build a `Simulator`, for chaining purposes.
+    pub fn set_population_iter<I>(self, pop: I) -> SimulatorBuilder<T, R, WithPopulation>
+        where I: IntoIterator<Item = T>
+    {
+        SimulatorBuilder { builder: self.builder.set_population_iter(pop) }
+    }
+
+    /// Continue a run that was interrupted after `iterations_done` iterations,
+    /// with `pop` as its population. See `seq::SimulatorBuilder::resume`.
+    ///
+    /// Returns a builder that can build a `Simulator`, for chaining purposes.
+    pub fn resume(self,
+                  pop: Vec<T>,
+                  iterations_done: u64)
+                  -> SimulatorBuilder<T, R, WithPopulation> {
+        SimulatorBuilder { builder: self.builder.resume(pop, iterations_done) }
+    }
+
+    /// Set the selector of the resulting `Simulator`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_selector(mut self, sel: Box<dyn Selector<T>>) -> Self {
+        self.builder = self.builder.set_selector(sel);
+        self
+    }
+
+    /// Set the replacement policy of the resulting `Simulator`, which decides
+    /// which phenotypes are killed off to make room for children.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_replacement(mut self, rep: Box<dyn ReplacementPolicy<T>>) -> Self {
+        self.builder = self.builder.set_replacement(rep);
+        self
+    }
+
+    /// Set the fitness function of the resulting `Simulator`.
+    ///
+    /// The fitness values of all phenotypes will be calculated by `fitness_function`
+    /// instead of by `Phenotype::fitness()`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_fitness_function(mut self, fitness_function: Box<dyn FitnessFunction<T>>) -> Self {
+        self.builder = self.builder.set_fitness_function(fitness_function);
+        self
+    }
+
+    /// Set the batch evaluator of the resulting `Simulator`.
+    ///
+    /// All phenotypes created in a step are passed to `evaluator` in a single call.
+    /// This takes precedence over the fitness function and over parallel evaluation.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_batch_evaluator(mut self, evaluator: Box<dyn BatchEvaluator<T>>) -> Self {
+        self.builder = self.builder.set_batch_evaluator(evaluator);
+        self
+    }
+
+    /// Set the maximum number of iterations of the resulting `Simulator`.
+    ///
+    /// The `Simulator` will stop running after this number of iterations.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_max_iters(mut self, i: u64) -> Self {
+        self.builder = self.builder.set_max_iters(i);
+        self
+    }
+
+    /// Set the maximum running time of the resulting `Simulator`.
+    /// See `seq::SimulatorBuilder::set_max_duration`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_max_duration(mut self, duration: Duration) -> Self {
+        self.builder = self.builder.set_max_duration(duration);
+        self
+    }
+
+    /// Set the target fitness value of the resulting `Simulator`.
+    /// See `seq::SimulatorBuilder::set_target_fitness`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_target_fitness(mut self, target: f64) -> Self {
+        self.builder = self.builder.set_target_fitness(target);
+        self
+    }
+
+    /// Set the fitness type of the resulting `Simulator`,
+    /// determining whether the `Simulator` will try to maximize
+    /// or minimize the fitness values of `Phenotype`s.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_fitness_type(mut self, t: FitnessType) -> Self {
+        self.builder = self.builder.set_fitness_type(t);
+        self
+    }
+
+    /// Set early stopping. If for `n_iters` iterations, the change in the highest fitness
+    /// is smaller than `delta`, the simulator will stop running.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_early_stop(mut self, delta: f64, n_iters: u64) -> Self {
+        self.builder = self.builder.set_early_stop(delta, n_iters);
+        self
+    }
+
+    /// Set early stopping with a relative `delta`.
+    /// See `seq::SimulatorBuilder::set_early_stop_relative`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_early_stop_relative(mut self, delta: f64, n_iters: u64) -> Self {
+        self.builder = self.builder.set_early_stop_relative(delta, n_iters);
+        self
+    }
+
+    /// Restart instead of stopping early.
+    /// See `seq::SimulatorBuilder::set_restart_on_stagnation`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_restart_on_stagnation<F>(mut self, keep: usize, generator: F) -> Self
+        where F: FnMut(&mut dyn Rng) -> T + 'static
+    {
+        self.builder = self.builder.set_restart_on_stagnation(keep, generator);
+        self
+    }
+
+    /// Add new random phenotypes to the population in every step.
+    /// See `seq::SimulatorBuilder::set_immigrants`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_immigrants<F>(mut self, fraction: f64, generator: F) -> Self
+        where F: FnMut(&mut dyn Rng) -> T + 'static
+    {
+        self.builder = self.builder.set_immigrants(fraction, generator);
+        self
+    }
+
+    /// Increase mutation instead of stopping early.
+    /// See `seq::SimulatorBuilder::set_hypermutation`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_hypermutation(mut self, mutations: usize, generations: u64) -> Self {
+        self.builder = self.builder.set_hypermutation(mutations, generations);
+        self
+    }
+
+    /// Continue early stopping from the progress of an earlier run.
+    /// See `seq::SimulatorBuilder::set_early_stop_state`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_early_stop_state(mut self, state: EarlyStopState) -> Self {
+        self.builder = self.builder.set_early_stop_state(state);
+        self
+    }
+
+    /// Set which fitness value of the population is monitored for early stopping.
+    /// See `seq::SimulatorBuilder::set_early_stop_metric`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_early_stop_metric(mut self, metric: EarlyStopMetric) -> Self {
+        self.builder = self.builder.set_early_stop_metric(metric);
+        self
+    }
+
+    /// Set the number of best performing phenotypes that are guaranteed to
+    /// survive each step of the resulting `Simulator`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_elitism(mut self, n: usize) -> Self {
+        self.builder = self.builder.set_elitism(n);
+        self
+    }
+
+    /// Set a callback that the resulting `Simulator` calls after every successful step.
+    /// See `seq::SimulatorBuilder::set_step_callback`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_step_callback<F>(mut self, callback: F) -> Self
+        where F: FnMut(&StepInfo<T>) + 'static
+    {
+        self.builder = self.builder.set_step_callback(callback);
+        self
+    }
+
+    /// Set a repair function, which the resulting `Simulator` calls on every child.
+    /// See `seq::SimulatorBuilder::set_repair`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_repair<F>(mut self, repair: F) -> Self
+        where F: FnMut(&mut T) + 'static
+    {
+        self.builder = self.builder.set_repair(repair);
+        self
+    }
+
+    /// Make the resulting `Simulator` calculate `GenerationStats` after every step.
+    /// See `seq::SimulatorBuilder::set_collect_stats`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_collect_stats(mut self) -> Self {
+        self.builder = self.builder.set_collect_stats();
+        self
+    }
+
+    /// Make the resulting `Simulator` record the best performing phenotype of every step.
+    /// See `seq::SimulatorBuilder::set_history_phenotypes`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_history_phenotypes(mut self) -> Self {
+        self.builder = self.builder.set_history_phenotypes();
+        self
+    }
+
+    /// Add an observer, which the resulting `Simulator` notifies of every `Event`.
+    /// See `seq::SimulatorBuilder::add_observer`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn add_observer(mut self, observer: Box<dyn Observer<T>>) -> Self {
+        self.builder = self.builder.add_observer(observer);
+        self
+    }
+
+    /// Add a stopping condition.
+    /// See `seq::SimulatorBuilder::add_stopping_condition`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn add_stopping_condition(mut self, condition: Box<dyn StoppingCondition<T>>) -> Self {
+        self.builder = self.builder.add_stopping_condition(condition);
+        self
+    }
+
+    /// Seed the random number generator of the resulting `Simulator`.
+    /// See `seq::SimulatorBuilder::set_seed`.
+    ///
+    /// Returns a builder for a `Simulator` that uses an `Isaac64Rng`, for chaining purposes.
+    pub fn set_seed(self, seed: u64) -> SimulatorBuilder<T, Isaac64Rng, P> {
+        SimulatorBuilder { builder: self.builder.set_seed(seed) }
+    }
+
+    /// Set the random number generator of the resulting `Simulator`.
+    /// See `seq::SimulatorBuilder::set_rng`.
+    ///
+    /// Returns a builder for a `Simulator` that uses `rng`, for chaining purposes.
+    pub fn set_rng<S: Rng>(self, rng: S) -> SimulatorBuilder<T, S, P> {
+        SimulatorBuilder { builder: self.builder.set_rng(rng) }
+    }
+
+    /// Use steady-state evolution instead of generational evolution.
+    /// See `seq::SimulatorBuilder::set_steady_state`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_steady_state(mut self) -> Self {
+        self.builder = self.builder.set_steady_state();
+        self
+    }
+}
+
+impl<T: Phenotype + Send + Sync + PartialEq, R: Rng> Simulator<T, R> {
+    /// Get the `k` best performing distinct phenotypes, best performing first.
+    /// See `seq::Simulator::get_n_best`.
+    pub fn get_n_best(&self, k: usize) -> Result<Vec<T>, Error> {
+        self.sim.get_n_best(k)
+    }
+}
+
+impl<T: Phenotype + Send + Sync + PartialEq, R: Rng, P> SimulatorBuilder<T, R, P> {
+    /// Keep an archive of the `size` best distinct phenotypes found during the run.
+    /// See `seq::SimulatorBuilder::set_hall_of_fame`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_hall_of_fame(mut self, size: usize) -> Self {
+        self.builder = self.builder.set_hall_of_fame(size);
+        self
+    }
+
+    /// Replace children that duplicate other phenotypes by mutating them again.
+    /// See `seq::SimulatorBuilder::set_eliminate_duplicates`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_eliminate_duplicates(mut self) -> Self {
+        self.builder = self.builder.set_eliminate_duplicates();
+        self
+    }
+
+    /// Replace children that duplicate other phenotypes by new phenotypes.
+    /// See `seq::SimulatorBuilder::set_eliminate_duplicates_with`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_eliminate_duplicates_with<F>(mut self, generator: F) -> Self
+        where F: FnMut(&mut dyn Rng) -> T + 'static
+    {
+        self.builder = self.builder.set_eliminate_duplicates_with(generator);
+        self
+    }
+}
+
+impl<T: Phenotype + Distance + Send + Sync + 'static, R: Rng, P> SimulatorBuilder<T, R, P> {
+    /// Run the CHC evolutionary algorithm.
+    /// See `seq::SimulatorBuilder::set_chc`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_chc(mut self, threshold: f64, cataclysm: fn(&T, &mut dyn Rng) -> T) -> Self {
+        self.builder = self.builder.set_chc(threshold, cataclysm);
+        self
+    }
+}
+
+impl<T: Phenotype + Distance + Send + Sync, R: Rng, P> SimulatorBuilder<T, R, P> {
+    /// Measure the diversity of the population after every step.
+    /// See `seq::SimulatorBuilder::set_track_diversity`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_track_diversity(mut self, sample: Option<usize>) -> Self {
+        self.builder = self.builder.set_track_diversity(sample);
+        self
+    }
+}
+
+impl<T: Constrained + Send + Sync, R: Rng, P> SimulatorBuilder<T, R, P> {
+    /// Take the constraints of the phenotypes into account.
+    /// See `seq::SimulatorBuilder::set_constrained`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_constrained(mut self) -> Self {
+        self.builder = self.builder.set_constrained();
+        self
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Phenotype + Send + Sync, R: Reseed, P> SimulatorBuilder<T, R, P> {
+    /// Resume a run from `checkpoint`.
+    /// See `seq::SimulatorBuilder::restore`.
+    ///
+    /// Returns a builder that can build a `Simulator`, for chaining purposes.
+    pub fn restore(self, checkpoint: Checkpoint<T>) -> SimulatorBuilder<T, R, WithPopulation> {
+        SimulatorBuilder { builder: self.builder.restore(checkpoint) }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Phenotype + Send + Sync + DeserializeOwned, R: Reseed, P> SimulatorBuilder<T, R, P>
+    where T::Fitness: DeserializeOwned
+{
+    /// Resume a run from the checkpoint in the file at `path`.
+    /// See `seq::SimulatorBuilder::load_checkpoint`.
+    pub fn load_checkpoint<Q>(self,
+                              path: Q)
+                              -> Result<SimulatorBuilder<T, R, WithPopulation>, String>
+        where Q: AsRef<Path>
+    {
+        Ok(SimulatorBuilder { builder: self.builder.load_checkpoint(path)? })
+    }
+}
+
+impl<T: Phenotype + Send + Sync, R: Rng> SimulatorBuilder<T, R, WithPopulation> {
+    /// Run NSGA-II, the multi-objective genetic algorithm.
+    /// See `seq::SimulatorBuilder::set_nsga2`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_nsga2(mut self) -> Self {
+        self.builder = self.builder.set_nsga2();
+        self
+    }
+
+    /// Run SPEA2, the multi-objective genetic algorithm.
+    /// See `seq::SimulatorBuilder::set_spea2`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_spea2(mut self) -> Self {
+        self.builder = self.builder.set_spea2();
+        self
+    }
+
+    /// Run MOEA/D, the multi-objective genetic algorithm, with the subproblems of
+    /// `decomposition`. See `seq::SimulatorBuilder::set_moead`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_moead(mut self, decomposition: Decomposition) -> Self {
+        self.builder = self.builder.set_moead(decomposition);
+        self
+    }
+}
+
+impl<T, R> Builder<Simulator<T, R>> for SimulatorBuilder<T, R, WithPopulation>
+    where T: Phenotype + Send + Sync,
+          R: Rng
+{
+    fn build(mut self) -> Result<Simulator<T, R>, BuildError> {
+        self.builder.sim.selector.set_parallel();
+        Ok(Simulator { sim: self.builder.build()? })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+    use rand::Rng;
+    use std::cmp;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: cmp::min(self.f, t.f) }
+        }
+
+        fn mutate(&self) -> Test {
+            if self.f < 0 {
+                Test { f: self.f + 1 }
+            } else if self.f > 0 {
+                Test { f: self.f - 1 }
+            } else {
+                self.clone()
+            }
+        }
+    }
+
+    /// A `Selector` that accepts any population, but always fails to select.
+    #[derive(Clone)]
+    struct FailingSelector;
+
+    impl Selector<Test> for FailingSelector {
+        fn select<'a>(&self,
+                      _: &'a [Individual<Test>],
+                      _: FitnessType,
+                      _: &mut dyn Rng)
+                      -> Result<Parents<'a, Test>, String> {
+            Err(String::from("Selection failed."))
+        }
+    }
+
+    /// A phenotype whose operators depend on the random number generator.
+    #[derive(Clone)]
+    struct Noisy {
+        f: i64,
+    }
+
+    impl Phenotype for Noisy {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Noisy) -> Noisy {
+            self.crossover_with_rng(t, &mut ::rand::thread_rng())
+        }
+
+        fn mutate(&self) -> Noisy {
+            self.mutate_with_rng(&mut ::rand::thread_rng())
+        }
+
+        fn crossover_with_rng<R: Rng>(&self, t: &Noisy, rng: &mut R) -> Noisy {
+            if rng.gen() { self.clone() } else { t.clone() }
+        }
+
+        fn mutate_with_rng<R: Rng>(&self, rng: &mut R) -> Noisy {
+            Noisy { f: self.f + rng.gen_range(-10, 11) }
+        }
+    }
+
+    #[test]
+    fn test_max_iters() {
+        let selector = MaximizeSelector::new(2);
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = par::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(selector))
+                        .set_max_iters(2)
+                        .build().unwrap();
+        s.run();
+        assert!(s.iterations() <= 2);
+    }
+
+    #[test]
+    fn test_early_stopping() {
+        let selector = MaximizeSelector::new(2);
+        let population: Vec<Test> = (0..100).map(|_| Test { f: 0 }).collect();
+        let mut s = par::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(selector))
+                        .set_early_stop(10.0, 5)
+                        .set_max_iters(10)
+                        .build().unwrap();
+        s.run();
+        assert!(s.iterations() <= 5);
+    }
+
+    #[test]
+    fn test_selector_error_propagate() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = par::Simulator::builder()
+                        .set_population(population.clone())
+                        .set_selector(Box::new(FailingSelector))
+                        .build().unwrap();
+        assert_eq!(s.run(), RunResult::Failure);
+        assert!(s.get().is_err());
+        let result = par::Simulator::builder()
+                         .set_population(population)
+                         .set_selector(Box::new(MaximizeSelector::new(0)))
+                         .build();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_result_ok() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i + 10 }).collect();
+        let mut s = par::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(TournamentSelector::new(4, 4)))
+                        .set_max_iters(1000)
+                        .set_fitness_type(FitnessType::Minimize)
+                        .build().unwrap();
+        s.run();
+        assert_eq!(s.get().unwrap().f, 0);
+    }
+
+    #[test]
+    fn test_seed() {
+        let population: Vec<Noisy> = (0..100).map(|i| Noisy { f: i }).collect();
+        let run = |seed| {
+            let mut s = par::Simulator::builder()
+                            .set_population(population.clone())
+                            .set_selector(Box::new(TournamentSelector::new(20, 4)))
+                            .set_max_iters(10)
+                            .set_seed(seed)
+                            .build().unwrap();
+            s.run();
+            s.get().unwrap().f
+        };
+        let result = run(7);
+        for _ in 0..5 {
+            assert_eq!(run(7), result);
+        }
+    }
+
+    #[test]
+    fn test_parallel_selection() {
+        let population: Vec<Noisy> = (0..100).map(|i| Noisy { f: i }).collect();
+        let run = |selector: TournamentSelector| {
+            let mut s = par::Simulator::builder()
+                            .set_population(population.clone())
+                            .set_selector(Box::new(selector))
+                            .set_max_iters(10)
+                            .set_seed(7)
+                            .build().unwrap();
+            s.run();
+            s.population().iter().map(|x| x.phenotype().f).collect::<Vec<i64>>()
+        };
+        // The `Simulator` runs the tournaments in parallel by itself.
+        assert_eq!(run(TournamentSelector::new(20, 4)),
+                   run(TournamentSelector::new(20, 4).in_parallel()));
+    }
+}
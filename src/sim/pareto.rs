@@ -0,0 +1,485 @@
+// file: pareto.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The pareto module provides the building blocks of multi-objective optimization.
+//!
+//! Fitness values are compared by Pareto dominance through their `PartialOrd`
+//! implementation, which is how `pheno::Objectives` compares its objectives. For fitness
+//! values that are totally ordered, such as `f64`, the Pareto front consists of the best
+//! performing phenotypes.
+
+use pheno::{Fitness, Phenotype};
+use super::{FitnessType, Individual};
+use std::cmp::{self, Ordering};
+use std::f64;
+
+/// Check whether the fitness value `x` dominates `y`: whether it is better, given the
+/// `fitness_type`. Values of which neither dominates the other can not be compared.
+pub fn dominates<F: PartialOrd>(x: &F, y: &F, fitness_type: FitnessType) -> bool {
+    matches!((x.partial_cmp(y), fitness_type),
+             (Some(Ordering::Greater), FitnessType::Maximize) |
+             (Some(Ordering::Less), FitnessType::Minimize))
+}
+
+/// Sort `population` into fronts of non-dominated individuals, and return the indices
+/// of the members of each front.
+///
+/// The first front is the Pareto front: no member of the population dominates its
+/// members. The members of every following front are only dominated by members of the
+/// fronts before it.
+pub fn non_dominated_sort<T: Phenotype>(population: &[Individual<T>],
+                                        fitness_type: FitnessType)
+                                        -> Vec<Vec<usize>> {
+    let n = population.len();
+    // For every individual, the individuals it dominates and the number dominating it.
+    let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut dominators = vec![0; n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let (x, y) = (population[i].fitness(), population[j].fitness());
+            if dominates(x, y, fitness_type) {
+                dominated[i].push(j);
+                dominators[j] += 1;
+            } else if dominates(y, x, fitness_type) {
+                dominated[j].push(i);
+                dominators[i] += 1;
+            }
+        }
+    }
+    let mut fronts = Vec::new();
+    let mut front: Vec<usize> = (0..n).filter(|&i| dominators[i] == 0).collect();
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for &i in &front {
+            for &j in &dominated[i] {
+                dominators[j] -= 1;
+                if dominators[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+        next.sort();
+        fronts.push(front);
+        front = next;
+    }
+    fronts
+}
+
+/// Calculate the crowding distance of every member of `front`, which contains indices
+/// into `population`, and return the distances in the same order.
+///
+/// The crowding distance of an individual measures how far apart its neighbours in the
+/// front are, summed over every objective. Individuals with the lowest or highest value
+/// of an objective have an infinite distance. Preferring individuals with larger
+/// distances keeps the front spread out.
+pub fn crowding_distance<T: Phenotype>(population: &[Individual<T>], front: &[usize]) -> Vec<f64> {
+    let mut distances = vec![0.0; front.len()];
+    if front.is_empty() {
+        return distances;
+    }
+    let objectives: Vec<Vec<f64>> = front.iter()
+                                         .map(|&i| population[i].fitness().objectives())
+                                         .collect();
+    let mut order: Vec<usize> = (0..front.len()).collect();
+    for m in 0..objectives[0].len() {
+        let value = |i: usize| objectives[i].get(m).cloned().unwrap_or(0.0);
+        order.sort_by(|&x, &y| value(x).partial_cmp(&value(y)).unwrap_or(Ordering::Equal));
+        let (first, last) = (order[0], order[order.len() - 1]);
+        distances[first] = f64::INFINITY;
+        distances[last] = f64::INFINITY;
+        let range = value(last) - value(first);
+        if range <= 0.0 {
+            continue;
+        }
+        for k in 1..order.len().saturating_sub(1) {
+            distances[order[k]] += (value(order[k + 1]) - value(order[k - 1])) / range;
+        }
+    }
+    distances
+}
+
+/// Get the rank of the non-dominated front of every member of `population`, counting from
+/// zero, and its crowding distance within that front.
+pub(super) fn rank_and_crowd<T: Phenotype>(population: &[Individual<T>],
+                                           fitness_type: FitnessType)
+                                           -> (Vec<usize>, Vec<f64>) {
+    let mut ranks = vec![0; population.len()];
+    let mut distances = vec![0.0; population.len()];
+    for (rank, front) in non_dominated_sort(population, fitness_type).iter().enumerate() {
+        for (&i, distance) in front.iter().zip(crowding_distance(population, front)) {
+            ranks[i] = rank;
+            distances[i] = distance;
+        }
+    }
+    (ranks, distances)
+}
+
+/// Calculate the Euclidean distance between every pair of members of `population`
+/// in objective space.
+pub(super) fn distances<T: Phenotype>(population: &[Individual<T>]) -> Vec<Vec<f64>> {
+    let objectives: Vec<Vec<f64>> = population.iter().map(|x| x.fitness().objectives()).collect();
+    objectives.iter()
+              .map(|x| {
+                  objectives.iter()
+                            .map(|y| {
+                                x.iter().zip(y).map(|(a, b)| (a - b) * (a - b)).sum::<f64>().sqrt()
+                            })
+                            .collect()
+              })
+              .collect()
+}
+
+/// Calculate the strength fitness of every member of `population`, as in SPEA2.
+/// Unlike fitness values, lower strength fitness values are better.
+///
+/// The strength of an individual is the number of individuals it dominates. Its raw
+/// fitness is the sum of the strengths of the individuals dominating it, which is zero for
+/// non-dominated individuals. To tell apart individuals with the same raw fitness, a
+/// density below one is added, which is larger for individuals with close neighbours in
+/// objective space. Non-dominated individuals therefore have a strength fitness below one.
+pub fn strength_fitness<T: Phenotype>(population: &[Individual<T>],
+                                      fitness_type: FitnessType)
+                                      -> Vec<f64> {
+    let n = population.len();
+    let dominating = |i: usize, j: usize| {
+        dominates(population[i].fitness(), population[j].fitness(), fitness_type)
+    };
+    let strengths: Vec<usize> = (0..n).map(|i| (0..n).filter(|&j| dominating(i, j)).count())
+                                      .collect();
+    let distances = distances(population);
+    // The density is based on the distance to the k-th nearest neighbour.
+    let k = (n as f64).sqrt() as usize;
+    (0..n).map(|i| {
+              let raw: usize = (0..n).filter(|&j| dominating(j, i)).map(|j| strengths[j]).sum();
+              let mut neighbours: Vec<f64> = (0..n).filter(|&j| j != i)
+                                                   .map(|j| distances[i][j])
+                                                   .collect();
+              neighbours.sort_by(|x, y| x.partial_cmp(y).unwrap_or(Ordering::Equal));
+              let sigma = neighbours.get(k.saturating_sub(1)).cloned().unwrap_or(0.0);
+              raw as f64 + 1.0 / (sigma + 2.0)
+          })
+          .collect()
+}
+
+/// Create evenly spread weight vectors for `objectives` objectives, for a `Decomposition`.
+///
+/// The weights of every vector sum to one, and are multiples of `1 / divisions`, which
+/// gives one vector for every way to divide `divisions` parts among the objectives.
+/// For two objectives, that is `divisions + 1` vectors.
+pub fn simplex_weights(objectives: usize, divisions: usize) -> Vec<Vec<f64>> {
+    fn divide(objectives: usize,
+              parts: usize,
+              prefix: &mut Vec<usize>,
+              result: &mut Vec<Vec<usize>>) {
+        if objectives == 1 {
+            prefix.push(parts);
+            result.push(prefix.clone());
+            prefix.pop();
+            return;
+        }
+        for i in 0..(parts + 1) {
+            prefix.push(i);
+            divide(objectives - 1, parts - i, prefix, result);
+            prefix.pop();
+        }
+    }
+
+    let mut parts = Vec::new();
+    if objectives > 0 {
+        divide(objectives, divisions, &mut Vec::new(), &mut parts);
+    }
+    parts.into_iter()
+         .map(|x| x.into_iter().map(|p| p as f64 / cmp::max(divisions, 1) as f64).collect())
+         .collect()
+}
+
+/// Decomposes a multi-objective problem into scalar subproblems, as in MOEA/D.
+///
+/// Every subproblem has a weight vector, and is solved by a member of the population:
+/// the member at the same index. The subproblem is to minimize the weighted Tchebycheff
+/// distance to the ideal point, the best value of every objective found in the
+/// population. Subproblems with close weight vectors are neighbours, and have similar
+/// solutions.
+#[derive(Clone, Debug)]
+pub struct Decomposition {
+    weights: Vec<Vec<f64>>,
+    neighbourhoods: Vec<Vec<usize>>,
+}
+
+impl Decomposition {
+    /// Create a decomposition into one subproblem for every vector of `weights`, each
+    /// of which has one weight for every objective. See `simplex_weights`.
+    ///
+    /// The neighbourhood of every subproblem consists of the `neighbours` subproblems
+    /// with the closest weight vectors, including itself.
+    pub fn new(weights: Vec<Vec<f64>>, neighbours: usize) -> Decomposition {
+        let distance = |x: &[f64], y: &[f64]| -> f64 {
+            x.iter().zip(y).map(|(a, b)| (a - b) * (a - b)).sum()
+        };
+        let neighbourhoods = weights.iter()
+                                    .map(|x| {
+                                        let mut closest: Vec<usize> = (0..weights.len())
+                                                                          .collect();
+                                        closest.sort_by(|&i, &j| {
+                                            distance(x, &weights[i])
+                                                .partial_cmp(&distance(x, &weights[j]))
+                                                .unwrap_or(Ordering::Equal)
+                                        });
+                                        closest.truncate(neighbours);
+                                        closest
+                                    })
+                                    .collect();
+        Decomposition {
+            weights,
+            neighbourhoods,
+        }
+    }
+
+    /// Get the number of subproblems.
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Check whether there are no subproblems.
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    /// Get the subproblems in the neighbourhood of subproblem `i`, closest first.
+    pub fn neighbourhood(&self, i: usize) -> &[usize] {
+        &self.neighbourhoods[i]
+    }
+
+    /// Calculate the weighted Tchebycheff distance of `objectives` to `ideal` for
+    /// subproblem `i`. Lower values are better.
+    pub fn tchebycheff(&self, i: usize, objectives: &[f64], ideal: &[f64]) -> f64 {
+        self.weights[i]
+            .iter()
+            .zip(objectives.iter().zip(ideal))
+            .map(|(w, (f, z))| w * (f - z).abs())
+            .fold(0.0, f64::max)
+    }
+}
+
+/// Get the best value of every objective among `individuals`, given the `fitness_type`.
+pub(super) fn ideal_point<'a, T, I>(individuals: I, fitness_type: FitnessType) -> Vec<f64>
+    where T: Phenotype + 'a,
+          I: IntoIterator<Item = &'a Individual<T>>
+{
+    let mut ideal: Vec<f64> = Vec::new();
+    for individual in individuals {
+        let objectives = individual.fitness().objectives();
+        if ideal.is_empty() {
+            ideal = objectives;
+            continue;
+        }
+        for (z, f) in ideal.iter_mut().zip(objectives) {
+            *z = match fitness_type {
+                FitnessType::Maximize => z.max(f),
+                FitnessType::Minimize => z.min(f),
+            };
+        }
+    }
+    ideal
+}
+
+/// Calculate the hypervolume indicator of `front`: the volume of the part of objective
+/// space between `reference` and the points dominated by a member of `front`.
+///
+/// `reference` should be a point that is worse than every member of `front` in every
+/// objective, given the `fitness_type`. Members that are not better than `reference` in
+/// every objective do not add to the volume. A larger hypervolume means a front that is
+/// closer to the optimum, more spread out, or both, so it can be used to compare runs.
+pub fn hypervolume<T: Phenotype>(front: &[&Individual<T>],
+                                 reference: &[f64],
+                                 fitness_type: FitnessType)
+                                 -> f64 {
+    // Calculate the volume for minimization, mirroring the objectives when maximizing.
+    let sign = match fitness_type {
+        FitnessType::Maximize => -1.0,
+        FitnessType::Minimize => 1.0,
+    };
+    let points = front.iter()
+                      .map(|x| x.fitness().objectives().iter().map(|f| sign * f).collect())
+                      .collect();
+    let reference: Vec<f64> = reference.iter().map(|r| sign * r).collect();
+    slice_volume(points, &reference)
+}
+
+/// Calculate the volume dominated by `points` up to `reference` when minimizing, slicing
+/// the space along the last objective.
+fn slice_volume(points: Vec<Vec<f64>>, reference: &[f64]) -> f64 {
+    let d = reference.len();
+    let mut points: Vec<Vec<f64>> = points.into_iter()
+                                          .filter(|p| {
+                                              p.len() >= d &&
+                                              p.iter().zip(reference).all(|(f, r)| f < r)
+                                          })
+                                          .collect();
+    if d == 0 || points.is_empty() {
+        return 0.0;
+    }
+    if d == 1 {
+        return points.iter().map(|p| reference[0] - p[0]).fold(0.0, f64::max);
+    }
+    points.sort_by(|x, y| x[d - 1].partial_cmp(&y[d - 1]).unwrap_or(Ordering::Equal));
+    let mut volume = 0.0;
+    for k in 0..points.len() {
+        let next = points.get(k + 1).map_or(reference[d - 1], |p| p[d - 1]);
+        let height = next - points[k][d - 1];
+        if height > 0.0 {
+            let slice = points[..k + 1].iter().map(|p| p[..d - 1].to_vec()).collect();
+            volume += height * slice_volume(slice, &reference[..d - 1]);
+        }
+    }
+    volume
+}
+
+/// Get the members of `population` that are not dominated by any other member.
+pub fn pareto_front<T: Phenotype>(population: &[Individual<T>],
+                                  fitness_type: FitnessType)
+                                  -> Vec<&Individual<T>> {
+    population.iter()
+              .filter(|x| {
+                  !population.iter().any(|y| dominates(y.fitness(), x.fitness(), fitness_type))
+              })
+              .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::pareto::*;
+    use pheno::Objectives;
+
+    #[derive(Clone)]
+    struct Test {
+        cost: f64,
+        quality: f64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = Objectives;
+
+        fn fitness(&self) -> Objectives {
+            Objectives(vec![-self.cost, self.quality])
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test {
+                cost: (self.cost + t.cost) / 2.0,
+                quality: (self.quality + t.quality) / 2.0,
+            }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn population() -> Vec<Individual<Test>> {
+        vec![(1.0, 1.0), (2.0, 3.0), (3.0, 2.0), (4.0, 3.0), (4.0, 1.0)]
+            .into_iter()
+            .map(|(cost, quality)| Individual::new(Test { cost, quality }))
+            .collect()
+    }
+
+    #[test]
+    fn test_dominance() {
+        let x = Objectives(vec![1.0, 2.0]);
+        assert!(dominates(&x, &Objectives(vec![1.0, 1.0]), FitnessType::Maximize));
+        assert!(!dominates(&x, &Objectives(vec![1.0, 1.0]), FitnessType::Minimize));
+        assert!(!dominates(&x, &Objectives(vec![2.0, 1.0]), FitnessType::Maximize));
+        assert!(!dominates(&x, &x.clone(), FitnessType::Maximize));
+        assert_eq!(x.partial_cmp(&Objectives(vec![2.0, 1.0])), None);
+    }
+
+    #[test]
+    fn test_non_dominated_sort() {
+        let fronts = non_dominated_sort(&population(), FitnessType::Maximize);
+        assert_eq!(fronts, vec![vec![0, 1], vec![2, 3], vec![4]]);
+    }
+
+    #[test]
+    fn test_crowding_distance() {
+        let population: Vec<Individual<Test>> =
+            vec![(1.0, 1.0), (2.0, 2.0), (4.0, 3.0)]
+                .into_iter()
+                .map(|(cost, quality)| Individual::new(Test { cost, quality }))
+                .collect();
+        let distances = crowding_distance(&population, &[0, 1, 2]);
+        // The second individual is in between the others in both objectives.
+        assert!(distances[0].is_infinite() && distances[2].is_infinite());
+        assert!((distances[1] - 2.0).abs() < 0.001);
+        assert!(crowding_distance(&population, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_strength_fitness() {
+        let fitnesses = strength_fitness(&population(), FitnessType::Maximize);
+        // Individuals 0 and 1 are not dominated. Individuals 2 and 3 are only dominated
+        // by 1, which dominates three individuals. Individual 4 is dominated by all others.
+        assert!(fitnesses[0] < 1.0 && fitnesses[1] < 1.0);
+        assert!(fitnesses[2] > 3.0 && fitnesses[2] < 4.0);
+        assert!(fitnesses[3] > 3.0 && fitnesses[3] < 4.0);
+        assert!(fitnesses[4] > 6.0 && fitnesses[4] < 7.0);
+    }
+
+    #[test]
+    fn test_simplex_weights() {
+        assert_eq!(simplex_weights(2, 2), vec![vec![0.0, 1.0], vec![0.5, 0.5], vec![1.0, 0.0]]);
+        let weights = simplex_weights(3, 4);
+        assert_eq!(weights.len(), 15);
+        assert!(weights.iter().all(|x| (x.iter().sum::<f64>() - 1.0).abs() < 0.001));
+    }
+
+    #[test]
+    fn test_decomposition() {
+        let decomposition = Decomposition::new(simplex_weights(2, 10), 3);
+        assert_eq!(decomposition.len(), 11);
+        assert_eq!(decomposition.neighbourhood(5)[0], 5);
+        let mut neighbourhood = decomposition.neighbourhood(0).to_vec();
+        neighbourhood.sort();
+        assert_eq!(neighbourhood, vec![0, 1, 2]);
+        let distance = decomposition.tchebycheff(5, &[1.0, 3.0], &[2.0, 2.0]);
+        assert!((distance - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_hypervolume() {
+        let population = population();
+        let front = pareto_front(&population, FitnessType::Maximize);
+        // The front is (-1, 1) and (-2, 3): a 4 by 1 and a 3 by 3 rectangle that overlap
+        // in a 3 by 1 rectangle.
+        let volume = hypervolume(&front, &[-5.0, 0.0], FitnessType::Maximize);
+        assert!((volume - 10.0).abs() < 0.001);
+        // Dominated points do not add to the volume.
+        let all: Vec<&Individual<Test>> = population.iter().collect();
+        assert!((hypervolume(&all, &[-5.0, 0.0], FitnessType::Maximize) - 10.0).abs() < 0.001);
+        assert!(hypervolume(&front, &[-1.5, 0.0], FitnessType::Maximize) > 0.0);
+        assert_eq!(hypervolume(&front, &[0.0, 5.0], FitnessType::Maximize), 0.0);
+    }
+
+    #[test]
+    fn test_pareto_front() {
+        let population = population();
+        let front: Vec<f64> = pareto_front(&population, FitnessType::Maximize)
+                                  .iter()
+                                  .map(|x| x.cost)
+                                  .collect();
+        assert_eq!(front, vec![1.0, 2.0]);
+    }
+}
@@ -0,0 +1,111 @@
+// file: plot.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::path::Path;
+use super::HistoryEntry;
+
+/// The size of a plot in pixels.
+const SIZE: (u32, u32) = (800, 600);
+
+/// Render the best fitness value of every entry in `history` as a line chart to the file
+/// at `path`. The file is an SVG image if `path` ends in `.svg`, and a PNG image otherwise.
+pub fn plot_history<T: Phenotype>(history: &[HistoryEntry<T>], path: &Path) -> Result<(), String> {
+    let is_svg = path.extension().is_some_and(|x| x.eq_ignore_ascii_case("svg"));
+    if is_svg {
+        draw(SVGBackend::new(path, SIZE).into_drawing_area(), history)
+    } else {
+        draw(BitMapBackend::new(path, SIZE).into_drawing_area(), history)
+    }
+}
+
+fn draw<DB: DrawingBackend, T: Phenotype>(area: DrawingArea<DB, Shift>,
+                               history: &[HistoryEntry<T>])
+                               -> Result<(), String> {
+    area.fill(&WHITE).map_err(|e| format!("Could not draw plot: {}", e))?;
+    let (mut min, mut max) = history.iter().fold((f64::INFINITY, f64::NEG_INFINITY),
+                                                 |(min, max), x| {
+                                                     (min.min(x.best_fitness),
+                                                      max.max(x.best_fitness))
+                                                 });
+    if history.is_empty() {
+        min = 0.0;
+        max = 1.0;
+    } else if (max - min).abs() < f64::EPSILON {
+        min -= 0.5;
+        max += 0.5;
+    }
+    let last = history.last().map_or(1, |x| x.generation.max(1));
+    let mut chart = ChartBuilder::on(&area).margin(20)
+                                           .build_cartesian_2d(0..last, min..max)
+                                           .map_err(|e| format!("Could not draw plot: {}", e))?;
+    chart.configure_mesh()
+         .draw()
+         .map_err(|e| format!("Could not draw plot: {}", e))?;
+    chart.draw_series(LineSeries::new(history.iter().map(|x| (x.generation, x.best_fitness)),
+                                      &BLUE))
+         .map_err(|e| format!("Could not draw plot: {}", e))?;
+    area.present().map_err(|e| format!("Could not write plot: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+    use std::{env, fs, process};
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_plot_history() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(10)
+                        .build().unwrap();
+        s.run();
+        for extension in &["png", "svg"] {
+            let path = env::temp_dir().join(format!("rsgenetic-plot-{}.{}",
+                                                    process::id(),
+                                                    extension));
+            s.plot_history(&path).unwrap();
+            assert!(fs::metadata(&path).unwrap().len() > 0);
+            fs::remove_file(&path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,104 @@
+// file: progress.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use std::io::{self, Write};
+use super::StepInfo;
+
+/// Show the progress of a `Simulation` on a single line of standard error: the generation,
+/// the best fitness value and the estimated time remaining.
+///
+/// Pass this function to `set_step_callback` on a `SimulatorBuilder`.
+/// The time remaining assumes that the `Simulation` does not stop early.
+pub fn print_progress<T: Phenotype>(info: &StepInfo<T>) {
+    eprint!("\r{}", format_progress(info));
+    if info.generation >= info.max_generations {
+        eprintln!();
+    }
+    let _ = io::stderr().flush();
+}
+
+fn format_progress<T: Phenotype>(info: &StepInfo<T>) -> String {
+    let eta = match info.eta {
+        Some(x) => format!("{:.1}s", x as f64 / 1e9),
+        None => "unknown".to_string(),
+    };
+    format!("Generation {}/{}, best fitness {}, ETA {}",
+            info.generation,
+            info.max_generations,
+            info.best_fitness,
+            eta)
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+    use super::format_progress;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_format_progress() {
+        let population = vec![Individual::new(Test { f: 3 })];
+        let mut info = StepInfo {
+            generation: 10,
+            max_generations: 100,
+            elapsed: Some(1_000_000_000),
+            eta: Some(9_000_000_000),
+            fitness_type: FitnessType::Maximize,
+            best_fitness: 3.0,
+            mean_fitness: 3.0,
+            population: &population,
+        };
+        assert_eq!(format_progress(&info),
+                   "Generation 10/100, best fitness 3, ETA 9.0s");
+        info.eta = None;
+        assert_eq!(format_progress(&info),
+                   "Generation 10/100, best fitness 3, ETA unknown");
+    }
+
+    #[test]
+    fn test_print_progress() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(3)
+                        .set_step_callback(print_progress)
+                        .build().unwrap();
+        assert_eq!(s.run(), RunResult::Done);
+    }
+}
@@ -0,0 +1,118 @@
+// file: age.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use rand::Rng;
+
+/// Kills off the oldest phenotypes.
+#[derive(Default)]
+pub struct AgeReplacement;
+
+impl AgeReplacement {
+    /// Create and return an age replacement policy.
+    ///
+    /// Such a policy removes the phenotypes that have survived the largest number of steps.
+    /// Among phenotypes of the same age, the worst performing ones are removed first.
+    /// This is useful for dynamic problems, where old solutions go stale.
+    pub fn new() -> AgeReplacement {
+        AgeReplacement
+    }
+}
+
+impl<T: Phenotype> ReplacementPolicy<T> for AgeReplacement {
+    fn replace(&self,
+               population: &mut Vec<Individual<T>>,
+               mut children: Vec<Individual<T>>,
+               fitness_type: FitnessType,
+               _: &mut dyn Rng) {
+        let count = children.len();
+        if count >= population.len() {
+            population.clear();
+        } else if count > 0 {
+            let mut indices: Vec<usize> = (0..population.len()).collect();
+            // Only the oldest `count` are needed, in any order.
+            indices.select_nth_unstable_by(count - 1, |&x, &y| {
+                let by_fitness = population[x].cmp_rank(&population[y], fitness_type);
+                population[y]
+                    .age()
+                    .cmp(&population[x].age())
+                    .then(by_fitness)
+            });
+            remove_indices(population, &indices[..count]);
+        }
+        population.append(&mut children);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::replace::*;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_kill_oldest() {
+        let mut population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                             .collect();
+        for individual in population.iter_mut().take(10) {
+            individual.grow_older();
+        }
+        let children: Vec<Individual<Test>> = (0..10).map(|_| Individual::new(Test { f: -1 }))
+                                                    .collect();
+        AgeReplacement::new().replace(&mut population,
+                                      children,
+                                      FitnessType::Maximize,
+                                      &mut ::rand::thread_rng());
+        assert_eq!(population.len(), 100);
+        assert!(population.iter().all(|x| x.f == -1 || x.f >= 10));
+    }
+
+    #[test]
+    fn test_same_age_kills_worst() {
+        let mut population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                             .collect();
+        let children: Vec<Individual<Test>> = (0..10).map(|_| Individual::new(Test { f: -1 }))
+                                                    .collect();
+        AgeReplacement::new().replace(&mut population,
+                                      children,
+                                      FitnessType::Minimize,
+                                      &mut ::rand::thread_rng());
+        assert_eq!(population.len(), 100);
+        assert!(population.iter().all(|x| x.f < 90));
+    }
+}
@@ -0,0 +1,149 @@
+// file: crowding.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use super::super::pareto::rank_and_crowd;
+use rand::Rng;
+use std::cmp::Ordering;
+
+/// Keeps the best non-dominated fronts of the population and the children together,
+/// as in NSGA-II.
+#[derive(Default)]
+pub struct CrowdingReplacement;
+
+impl CrowdingReplacement {
+    /// Create and return a crowding replacement policy.
+    ///
+    /// Such a policy sorts the population and the children together into non-dominated
+    /// fronts, and removes as many phenotypes as there are children, starting from the
+    /// worst front. Within a front, the phenotypes with the smallest crowding distance
+    /// are removed first. Unlike other policies, children can be removed as well.
+    pub fn new() -> CrowdingReplacement {
+        CrowdingReplacement
+    }
+}
+
+impl<T: Phenotype> ReplacementPolicy<T> for CrowdingReplacement {
+    fn replace(&self,
+               population: &mut Vec<Individual<T>>,
+               children: Vec<Individual<T>>,
+               fitness_type: FitnessType,
+               _: &mut dyn Rng) {
+        let size = population.len();
+        population.extend(children);
+        if population.len() <= size {
+            return;
+        }
+        let (ranks, distances) = rank_and_crowd(population, fitness_type);
+        let mut indices: Vec<usize> = (0..population.len()).collect();
+        indices.sort_by(|&x, &y| {
+            ranks[x].cmp(&ranks[y])
+                    .then(distances[y].partial_cmp(&distances[x]).unwrap_or(Ordering::Equal))
+        });
+        indices.truncate(size);
+        // Keep the survivors in their original order.
+        indices.sort();
+        let mut survivors = indices.into_iter().peekable();
+        let mut i = 0;
+        population.retain(|_| {
+            let keep = survivors.peek() == Some(&i);
+            if keep {
+                survivors.next();
+            }
+            i += 1;
+            keep
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::replace::*;
+    use pheno::Objectives;
+
+    #[derive(Clone)]
+    struct Test {
+        cost: i64,
+        quality: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = Objectives;
+
+        fn fitness(&self) -> Objectives {
+            Objectives(vec![-self.cost as f64, self.quality as f64])
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test {
+                cost: (self.cost + t.cost) / 2,
+                quality: (self.quality + t.quality) / 2,
+            }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_keep_best_fronts() {
+        let mut population: Vec<Individual<Test>> =
+            (0..10).map(|i| Individual::new(Test { cost: i, quality: 0 })).collect();
+        let children: Vec<Individual<Test>> =
+            (0..5).map(|i| Individual::new(Test { cost: i, quality: 10 })).collect();
+        CrowdingReplacement::new().replace(&mut population,
+                                           children,
+                                           FitnessType::Maximize,
+                                           &mut ::rand::thread_rng());
+        assert_eq!(population.len(), 10);
+        // The children dominate the five most expensive phenotypes.
+        assert!(population.iter().all(|x| x.quality == 10 || x.cost < 5));
+    }
+
+    #[test]
+    fn test_keep_spread() {
+        let mut population: Vec<Individual<Test>> =
+            (0..3).map(|i| Individual::new(Test { cost: i * 10, quality: i * 10 })).collect();
+        let children = vec![Individual::new(Test { cost: 11, quality: 11 })];
+        CrowdingReplacement::new().replace(&mut population,
+                                           children,
+                                           FitnessType::Maximize,
+                                           &mut ::rand::thread_rng());
+        // All four are in the same front, and the new one is the most crowded.
+        let costs: Vec<i64> = population.iter().map(|x| x.cost).collect();
+        assert_eq!(costs, vec![0, 10, 20]);
+    }
+
+    #[test]
+    fn test_nsga2() {
+        let population: Vec<Test> = (0..50).map(|i| Test { cost: i, quality: i % 10 })
+                                           .collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_nsga2()
+                        .set_max_iters(10)
+                        .build().unwrap();
+        s.run();
+        assert_eq!(s.population().len(), 50);
+        // The front of the initial population can only be improved on by children that
+        // are at least as cheap.
+        assert!(s.pareto_front().iter().all(|x| x.cost <= 9));
+    }
+}
@@ -0,0 +1,130 @@
+// file: decomposition.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::{Fitness, Phenotype};
+use super::*;
+use super::super::{FitnessType, Individual};
+use super::super::pareto::{Decomposition, ideal_point};
+use rand::Rng;
+
+/// Lets the child of every subproblem of a `Decomposition` replace the solutions of the
+/// subproblems in its neighbourhood that it improves on, as in MOEA/D.
+pub struct DecompositionReplacement {
+    decomposition: Decomposition,
+}
+
+impl DecompositionReplacement {
+    /// Create and return a decomposition replacement policy.
+    ///
+    /// Such a policy expects the children in the order of the subproblems of
+    /// `decomposition`, as created from the parents selected by a `DecompositionSelector`.
+    /// The child of subproblem `i` replaces the member of the population solving every
+    /// subproblem in the neighbourhood of `i`, if it has a lower Tchebycheff distance to
+    /// the ideal point of the population and the children for that subproblem.
+    ///
+    /// Members of the population keep their index, so the population size does not
+    /// change, and other children are discarded.
+    pub fn new(decomposition: Decomposition) -> DecompositionReplacement {
+        DecompositionReplacement { decomposition }
+    }
+}
+
+impl<T: Phenotype> ReplacementPolicy<T> for DecompositionReplacement {
+    fn replace(&self,
+               population: &mut Vec<Individual<T>>,
+               children: Vec<Individual<T>>,
+               fitness_type: FitnessType,
+               _: &mut dyn Rng) {
+        let ideal = ideal_point(population.iter().chain(&children), fitness_type);
+        let size = population.len();
+        for (i, child) in children.into_iter().enumerate().take(self.decomposition.len()) {
+            let objectives = child.fitness().objectives();
+            for &j in self.decomposition.neighbourhood(i) {
+                if j >= size {
+                    continue;
+                }
+                let current = population[j].fitness().objectives();
+                if self.decomposition.tchebycheff(j, &objectives, &ideal) <
+                   self.decomposition.tchebycheff(j, &current, &ideal) {
+                    population[j] = child.clone();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::pareto::*;
+    use ::sim::replace::*;
+    use pheno::Objectives;
+
+    #[derive(Clone)]
+    struct Test {
+        x: f64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = Objectives;
+
+        fn fitness(&self) -> Objectives {
+            Objectives(vec![self.x * self.x, (self.x - 2.0) * (self.x - 2.0)])
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { x: (self.x + t.x) / 2.0 }
+        }
+
+        fn mutate(&self) -> Test {
+            Test { x: self.x + ::rand::random::<f64>() - 0.5 }
+        }
+    }
+
+    #[test]
+    fn test_replace_neighbours() {
+        let decomposition = Decomposition::new(simplex_weights(2, 2), 2);
+        let mut population: Vec<Individual<Test>> =
+            vec![-1.0, 5.0, 3.0].into_iter().map(|x| Individual::new(Test { x })).collect();
+        // Balancing both objectives, 1.0 is the best solution of the middle subproblem.
+        let children = vec![Individual::new(Test { x: 9.0 }),
+                            Individual::new(Test { x: 1.0 }),
+                            Individual::new(Test { x: 9.0 })];
+        DecompositionReplacement::new(decomposition).replace(&mut population,
+                                                             children,
+                                                             FitnessType::Minimize,
+                                                             &mut ::rand::thread_rng());
+        let xs: Vec<f64> = population.iter().map(|x| x.x).collect();
+        assert_eq!(xs.len(), 3);
+        assert_eq!(xs[1], 1.0);
+        assert!(!xs.contains(&9.0));
+    }
+
+    #[test]
+    fn test_moead() {
+        let population: Vec<Test> = (0..21).map(|i| Test { x: i as f64 - 10.0 }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_fitness_type(FitnessType::Minimize)
+                        .set_moead(Decomposition::new(simplex_weights(2, 20), 5))
+                        .set_max_iters(50)
+                        .build().unwrap();
+        s.run();
+        assert_eq!(s.population().len(), 21);
+        // The Pareto optimal solutions lie between 0 and 2.
+        assert!(s.pareto_front().iter().all(|t| t.x > -0.5 && t.x < 2.5));
+    }
+}
@@ -0,0 +1,75 @@
+// file: mod.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The replacement module provides a trait that can be implemented
+//! to implement new survivor selection algorithms. This module also provides a couple
+//! of useful replacement policies.
+//!
+//! A replacement policy decides which phenotypes are removed from the population to
+//! make room for the children created in a step.
+
+mod stochastic;
+mod random;
+mod worst;
+mod age;
+mod crowding;
+mod truncation;
+mod decomposition;
+mod restricted;
+
+use pheno::Phenotype;
+use rand::Rng;
+use super::{FitnessType, Individual};
+
+pub use self::stochastic::StochasticReplacement;
+pub use self::random::RandomReplacement;
+pub use self::worst::WorstReplacement;
+pub use self::age::AgeReplacement;
+pub use self::crowding::CrowdingReplacement;
+pub use self::truncation::TruncationReplacement;
+pub use self::decomposition::DecompositionReplacement;
+pub use self::restricted::RestrictedTournamentReplacement;
+
+/// Remove the phenotypes at `indices` from `population` in linear time, keeping the others
+/// in order.
+fn remove_indices<T: Phenotype>(population: &mut Vec<Individual<T>>, indices: &[usize]) {
+    let mut doomed = vec![false; population.len()];
+    for &i in indices {
+        doomed[i] = true;
+    }
+    let mut i = 0;
+    population.retain(|_| {
+        i += 1;
+        !doomed[i - 1]
+    });
+}
+
+/// A `ReplacementPolicy` inserts the children of a step into the population
+/// of a `Simulation`, removing phenotypes to make room for them.
+pub trait ReplacementPolicy<T: Phenotype> {
+    /// Remove as many phenotypes from `population` as there are `children`, then add
+    /// the `children` to it. `fitness_type` indicates whether the fitness values are being
+    /// maximized or minimized. Any randomness must be drawn from `rng`, the random number
+    /// generator of the `Simulation`, so that seeded runs are reproducible.
+    ///
+    /// If there are more children than phenotypes in the population, the entire population
+    /// is replaced.
+    fn replace(&self,
+               population: &mut Vec<Individual<T>>,
+               children: Vec<Individual<T>>,
+               fitness_type: FitnessType,
+               rng: &mut dyn Rng);
+}
@@ -0,0 +1,106 @@
+// file: random.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use rand::Rng;
+
+/// Kills off phenotypes chosen uniformly at random.
+#[derive(Default)]
+pub struct RandomReplacement;
+
+impl RandomReplacement {
+    /// Create and return a random replacement policy.
+    ///
+    /// Every phenotype in the population has the same chance of being killed off,
+    /// regardless of its fitness.
+    pub fn new() -> RandomReplacement {
+        RandomReplacement
+    }
+}
+
+impl<T: Phenotype> ReplacementPolicy<T> for RandomReplacement {
+    fn replace(&self,
+               population: &mut Vec<Individual<T>>,
+               mut children: Vec<Individual<T>>,
+               _: FitnessType,
+               mut rng: &mut dyn Rng) {
+        for _ in 0..children.len() {
+            if population.is_empty() {
+                break;
+            }
+            let i = Rng::gen_range(&mut rng, 0, population.len());
+            population.swap_remove(i);
+        }
+        population.append(&mut children);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::replace::*;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_kill_off_count() {
+        let mut population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                             .collect();
+        let children: Vec<Individual<Test>> = (0..10).map(|_| Individual::new(Test { f: -1 }))
+                                                    .collect();
+        RandomReplacement::new().replace(&mut population,
+                                         children,
+                                         FitnessType::Maximize,
+                                         &mut ::rand::thread_rng());
+        assert_eq!(population.len(), 100);
+        assert_eq!(population.iter().filter(|x| x.f == -1).count(), 10);
+    }
+
+    #[test]
+    fn test_replace_all() {
+        let mut population: Vec<Individual<Test>> = (0..10).map(|i| Individual::new(Test { f: i }))
+                                                             .collect();
+        let children: Vec<Individual<Test>> = (0..20).map(|_| Individual::new(Test { f: -1 }))
+                                                    .collect();
+        RandomReplacement::new().replace(&mut population,
+                                         children,
+                                         FitnessType::Maximize,
+                                         &mut ::rand::thread_rng());
+        assert_eq!(population.len(), 20);
+        assert!(population.iter().all(|x| x.f == -1));
+    }
+}
@@ -0,0 +1,142 @@
+// file: restricted.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use rand::Rng;
+use std::cmp::Ordering;
+
+/// Lets every child compete against the most similar member of a random window of the
+/// population, which it replaces only if it performs better, as in restricted tournament
+/// selection (RTS).
+///
+/// Because children only replace similar phenotypes, the population keeps several niches
+/// instead of converging on a single optimum. The population size does not change, and
+/// children that lose their tournament are discarded.
+pub struct RestrictedTournamentReplacement<T> {
+    window: usize,
+    distance: fn(&T, &T) -> f64,
+}
+
+impl<T> RestrictedTournamentReplacement<T> {
+    /// Create and return a restricted tournament replacement policy.
+    ///
+    /// * `window`: the number of random members of the population every child is compared
+    ///   with. If it is at least the population size, every member is compared with.
+    /// * `distance`: calculates the distance between two phenotypes.
+    pub fn new(window: usize, distance: fn(&T, &T) -> f64) -> RestrictedTournamentReplacement<T> {
+        RestrictedTournamentReplacement { window, distance }
+    }
+}
+
+impl<T: Phenotype> ReplacementPolicy<T> for RestrictedTournamentReplacement<T> {
+    fn replace(&self,
+               population: &mut Vec<Individual<T>>,
+               children: Vec<Individual<T>>,
+               fitness_type: FitnessType,
+               mut rng: &mut dyn Rng) {
+        if population.is_empty() {
+            population.extend(children);
+            return;
+        }
+        for child in children {
+            let window: Vec<usize> = if self.window >= population.len() {
+                (0..population.len()).collect()
+            } else {
+                (0..self.window).map(|_| Rng::gen_range(&mut rng, 0, population.len())).collect()
+            };
+            let distances: Vec<(usize, f64)> =
+                window.into_iter()
+                      .map(|i| (i, (self.distance)(child.phenotype(), population[i].phenotype())))
+                      .collect();
+            let closest = distances.iter()
+                                   .min_by(|x, y| x.1.partial_cmp(&y.1).unwrap_or(Ordering::Equal))
+                                   .map_or(0, |x| x.0);
+            if child.cmp_rank(&population[closest], fitness_type) == Ordering::Greater {
+                population[closest] = child;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+    use ::sim::replace::*;
+
+    #[derive(Clone)]
+    struct Test {
+        x: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        // Two peaks, at 10 and 90, of which the one at 90 is slightly higher.
+        fn fitness(&self) -> f64 {
+            let peak = |center: i64, height: f64| {
+                (height - (self.x - center).abs() as f64).max(0.0)
+            };
+            peak(10, 20.0).max(peak(90, 21.0))
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { x: (self.x + t.x) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            Test { x: self.x + ::rand::random::<i64>() % 3 }
+        }
+    }
+
+    fn distance(x: &Test, y: &Test) -> f64 {
+        (x.x - y.x).abs() as f64
+    }
+
+    #[test]
+    fn test_replace_closest() {
+        let mut population: Vec<Individual<Test>> =
+            vec![5, 85].into_iter().map(|x| Individual::new(Test { x })).collect();
+        // The first child is better than both, but only replaces the closest one.
+        let children = vec![Individual::new(Test { x: 10 }), Individual::new(Test { x: 70 })];
+        RestrictedTournamentReplacement::new(10, distance).replace(&mut population,
+                                                                   children,
+                                                                   FitnessType::Maximize,
+                                                                   &mut ::rand::thread_rng());
+        let xs: Vec<i64> = population.iter().map(|x| x.x).collect();
+        assert_eq!(xs, vec![10, 85]);
+    }
+
+    #[test]
+    fn test_niches() {
+        let population: Vec<Test> = (0..40).map(|i| Test { x: if i < 20 { i } else { 80 + i } })
+                                           .collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(TournamentSelector::new(10, 3)))
+                        .set_replacement(Box::new(RestrictedTournamentReplacement::new(10,
+                                                                                       distance)))
+                        .set_max_iters(50)
+                        .build().unwrap();
+        s.run();
+        assert_eq!(s.population().len(), 40);
+        // Both peaks are kept.
+        assert!(s.population().iter().any(|x| x.x < 50));
+        assert!(s.population().iter().any(|x| x.x > 50));
+    }
+}
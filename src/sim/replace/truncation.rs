@@ -0,0 +1,173 @@
+// file: truncation.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use super::super::pareto::{distances, strength_fitness};
+use rand::Rng;
+use std::cmp::Ordering;
+
+/// Keeps an archive of the best non-dominated phenotypes of the population and the
+/// children together, as in SPEA2.
+#[derive(Default)]
+pub struct TruncationReplacement;
+
+impl TruncationReplacement {
+    /// Create and return a truncation replacement policy.
+    ///
+    /// Such a policy treats the population as the archive of SPEA2, and removes as many
+    /// phenotypes from the population and the children together as there are children.
+    ///
+    /// If there are too few non-dominated phenotypes, the dominated ones with the best
+    /// strength fitness are kept as well. If there are too many, the phenotype closest to
+    /// its nearest neighbour in objective space is removed, one at a time, so the archive
+    /// stays spread out. Unlike other policies, children can be removed as well.
+    pub fn new() -> TruncationReplacement {
+        TruncationReplacement
+    }
+}
+
+impl<T: Phenotype> ReplacementPolicy<T> for TruncationReplacement {
+    fn replace(&self,
+               population: &mut Vec<Individual<T>>,
+               children: Vec<Individual<T>>,
+               fitness_type: FitnessType,
+               _: &mut dyn Rng) {
+        let size = population.len();
+        population.extend(children);
+        if population.len() <= size {
+            return;
+        }
+        let fitnesses = strength_fitness(population, fitness_type);
+        let mut indices: Vec<usize> = (0..population.len()).collect();
+        indices.sort_by(|&x, &y| {
+            fitnesses[x].partial_cmp(&fitnesses[y]).unwrap_or(Ordering::Equal)
+        });
+        let non_dominated = fitnesses.iter().filter(|&&x| x < 1.0).count();
+        if non_dominated <= size {
+            indices.truncate(size);
+        } else {
+            indices.truncate(non_dominated);
+            let distances = distances(population);
+            while indices.len() > size {
+                // For every member, the distances to the others, nearest first.
+                let neighbours: Vec<Vec<f64>> = indices.iter()
+                    .map(|&i| {
+                        let mut row: Vec<f64> = indices.iter()
+                                                       .filter(|&&j| j != i)
+                                                       .map(|&j| distances[i][j])
+                                                       .collect();
+                        row.sort_by(|x, y| x.partial_cmp(y).unwrap_or(Ordering::Equal));
+                        row
+                    })
+                    .collect();
+                let closest = (0..indices.len())
+                    .min_by(|&x, &y| {
+                        neighbours[x].partial_cmp(&neighbours[y]).unwrap_or(Ordering::Equal)
+                    })
+                    .unwrap();
+                indices.remove(closest);
+            }
+        }
+        // Keep the survivors in their original order.
+        indices.sort();
+        let mut survivors = indices.into_iter().peekable();
+        let mut i = 0;
+        population.retain(|_| {
+            let keep = survivors.peek() == Some(&i);
+            if keep {
+                survivors.next();
+            }
+            i += 1;
+            keep
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::replace::*;
+    use pheno::Objectives;
+
+    #[derive(Clone)]
+    struct Test {
+        cost: i64,
+        quality: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = Objectives;
+
+        fn fitness(&self) -> Objectives {
+            Objectives(vec![-self.cost as f64, self.quality as f64])
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test {
+                cost: (self.cost + t.cost) / 2,
+                quality: (self.quality + t.quality) / 2,
+            }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_keep_non_dominated() {
+        let mut population: Vec<Individual<Test>> =
+            (0..10).map(|i| Individual::new(Test { cost: i, quality: 0 })).collect();
+        let children: Vec<Individual<Test>> =
+            (0..5).map(|i| Individual::new(Test { cost: i, quality: 10 })).collect();
+        TruncationReplacement::new().replace(&mut population,
+                                             children,
+                                             FitnessType::Maximize,
+                                             &mut ::rand::thread_rng());
+        assert_eq!(population.len(), 10);
+        assert!(population.iter().all(|x| x.quality == 10 || x.cost < 5));
+    }
+
+    #[test]
+    fn test_truncate_crowded() {
+        let mut population: Vec<Individual<Test>> =
+            (0..3).map(|i| Individual::new(Test { cost: i * 10, quality: i * 10 })).collect();
+        let children = vec![Individual::new(Test { cost: 11, quality: 11 })];
+        TruncationReplacement::new().replace(&mut population,
+                                             children,
+                                             FitnessType::Maximize,
+                                             &mut ::rand::thread_rng());
+        let costs: Vec<i64> = population.iter().map(|x| x.cost).collect();
+        assert_eq!(costs.len(), 3);
+        assert!(costs.contains(&0) && costs.contains(&20));
+    }
+
+    #[test]
+    fn test_spea2() {
+        let population: Vec<Test> = (0..50).map(|i| Test { cost: i, quality: i % 10 })
+                                           .collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_spea2()
+                        .set_max_iters(10)
+                        .build().unwrap();
+        s.run();
+        assert_eq!(s.population().len(), 50);
+        assert!(s.pareto_front().iter().all(|x| x.cost <= 9));
+    }
+}
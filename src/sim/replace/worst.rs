@@ -0,0 +1,126 @@
+// file: worst.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use rand::Rng;
+
+/// Kills off the worst performing phenotypes.
+#[derive(Default)]
+pub struct WorstReplacement;
+
+impl WorstReplacement {
+    /// Create and return a worst replacement policy.
+    ///
+    /// Such a policy removes the phenotypes with the lowest fitness values when maximizing,
+    /// and those with the highest fitness values when minimizing.
+    pub fn new() -> WorstReplacement {
+        WorstReplacement
+    }
+}
+
+impl<T: Phenotype> ReplacementPolicy<T> for WorstReplacement {
+    fn replace(&self,
+               population: &mut Vec<Individual<T>>,
+               mut children: Vec<Individual<T>>,
+               fitness_type: FitnessType,
+               _: &mut dyn Rng) {
+        let count = children.len();
+        if count >= population.len() {
+            population.clear();
+        } else if count > 0 {
+            let mut indices: Vec<usize> = (0..population.len()).collect();
+            // Only the worst `count` are needed, in any order.
+            indices.select_nth_unstable_by(count - 1, |&x, &y| {
+                population[x].cmp_rank(&population[y], fitness_type)
+            });
+            remove_indices(population, &indices[..count]);
+        }
+        population.append(&mut children);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::replace::*;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_kill_worst_minimize() {
+        let mut population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                             .collect();
+        let children: Vec<Individual<Test>> = (0..10).map(|_| Individual::new(Test { f: -1 }))
+                                                    .collect();
+        WorstReplacement::new().replace(&mut population,
+                                        children,
+                                        FitnessType::Minimize,
+                                        &mut ::rand::thread_rng());
+        assert_eq!(population.len(), 100);
+        assert!(population.iter().all(|x| x.f < 90));
+        // The survivors keep their order.
+        assert!(population.iter().take(90).map(|x| x.f).eq(0..90));
+    }
+
+    #[test]
+    fn test_kill_worst_maximize() {
+        let mut population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                             .collect();
+        let children: Vec<Individual<Test>> = (0..10).map(|_| Individual::new(Test { f: -1 }))
+                                                    .collect();
+        WorstReplacement::new().replace(&mut population,
+                                        children,
+                                        FitnessType::Maximize,
+                                        &mut ::rand::thread_rng());
+        assert_eq!(population.len(), 100);
+        assert!(population.iter().all(|x| x.f == -1 || x.f >= 10));
+    }
+
+    #[test]
+    fn test_replace_all() {
+        let mut population: Vec<Individual<Test>> = (0..10).map(|i| Individual::new(Test { f: i }))
+                                                             .collect();
+        let children: Vec<Individual<Test>> = (0..20).map(|_| Individual::new(Test { f: -1 }))
+                                                    .collect();
+        WorstReplacement::new().replace(&mut population,
+                                        children,
+                                        FitnessType::Maximize,
+                                        &mut ::rand::thread_rng());
+        assert_eq!(population.len(), 20);
+        assert!(population.iter().all(|x| x.f == -1));
+    }
+}
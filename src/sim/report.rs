@@ -0,0 +1,178 @@
+// file: report.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{Error, FitnessType, NanoSecond};
+
+/// Why a `Simulation` stopped running.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StopReason {
+    /// The `Simulation` has not stopped yet.
+    Running,
+    /// The maximum number of iterations was reached.
+    MaxIterations,
+    /// The maximum running time was exceeded.
+    TimeLimit,
+    /// The best fitness value reached the target fitness value.
+    TargetReached,
+    /// A `StoppingCondition` was met.
+    Condition,
+    /// The best fitness value did not change enough for a number of iterations.
+    EarlyStop,
+    /// The given error occurred.
+    Failed(Error),
+}
+
+/// A summary of a run of a `Simulation`: its settings and its outcome.
+///
+/// With the `serde` feature enabled, a `Report` can be serialized, for example
+/// to archive every experiment as a single JSON document.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Report {
+    /// The number of phenotypes in the population.
+    pub population_size: usize,
+    /// The maximum number of iterations.
+    pub max_iterations: u64,
+    /// The maximum number of nanoseconds to run, if a limit was set.
+    pub max_duration: Option<NanoSecond>,
+    /// The target fitness value, if one was set.
+    pub target_fitness: Option<f64>,
+    /// Whether fitness values were maximized or minimized.
+    pub fitness_type: FitnessType,
+    /// The number of best performing phenotypes that survive every step.
+    pub elitism: usize,
+    /// Whether steady-state evolution was used.
+    pub steady_state: bool,
+    /// The minimum change in fitness for early stopping, if it was enabled.
+    pub early_stop_delta: Option<f64>,
+    /// The number of iterations for early stopping, if it was enabled.
+    pub early_stop_iterations: Option<u64>,
+    /// Whether the minimum change in fitness for early stopping is relative.
+    pub early_stop_relative: bool,
+    /// The seed of the random number generator, if one was set with `set_seed`.
+    pub seed: Option<u64>,
+    /// The number of iterations that were run.
+    pub iterations: u64,
+    /// The number of nanoseconds spent running, or `None` in case of an overflow.
+    pub time: Option<NanoSecond>,
+    /// Why the `Simulation` stopped.
+    pub stop_reason: StopReason,
+    /// The number of times the population was restarted because it stagnated.
+    pub restarts: u64,
+    /// The number of bursts of increased mutation because the population stagnated.
+    pub hypermutations: u64,
+    /// The fitness value of the best performing phenotype, unless an error occurred.
+    pub best_fitness: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+    use rand::Rng;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    /// A `Selector` that accepts any population, but always fails to select.
+    #[derive(Clone)]
+    struct FailingSelector;
+
+    impl Selector<Test> for FailingSelector {
+        fn select<'a>(&self,
+                      _: &'a [Individual<Test>],
+                      _: FitnessType,
+                      _: &mut dyn Rng)
+                      -> Result<Parents<'a, Test>, String> {
+            Err(String::from("Selection failed."))
+        }
+    }
+
+    #[test]
+    fn test_report() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(5)
+                        .set_seed(3)
+                        .build().unwrap();
+        assert_eq!(s.report().stop_reason, StopReason::Running);
+        s.run();
+        let report = s.report();
+        assert_eq!(report.population_size, 100);
+        assert_eq!(report.max_iterations, 5);
+        assert_eq!(report.fitness_type, FitnessType::Maximize);
+        assert_eq!(report.seed, Some(3));
+        assert_eq!(report.iterations, 5);
+        assert_eq!(report.stop_reason, StopReason::MaxIterations);
+        assert_eq!(report.early_stop_delta, None);
+        assert!((report.best_fitness.unwrap() - s.get().unwrap().fitness()).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_report_failed() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(FailingSelector))
+                        .set_early_stop(1.0, 3)
+                        .build().unwrap();
+        s.run();
+        let report = s.report();
+        assert_eq!(report.early_stop_iterations, Some(3));
+        assert_eq!(report.best_fitness, None);
+        match report.stop_reason {
+            StopReason::Failed(Error::SelectionFailed(_)) => {}
+            other => panic!("Unexpected stop reason {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_report_json() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(5)
+                        .build().unwrap();
+        s.run();
+        let json = ::serde_json::to_string(&s.report()).unwrap();
+        let report: Report = ::serde_json::from_str(&json).unwrap();
+        assert_eq!(report.stop_reason, StopReason::MaxIterations);
+        assert!(json.contains("\"seed\":null"));
+    }
+}
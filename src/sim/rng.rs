@@ -0,0 +1,121 @@
+// file: rng.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use rand::{self, ChaChaRng, Isaac64Rng, IsaacRng, Rng, SeedableRng, StdRng, ThreadRng,
+           XorShiftRng};
+
+/// A random number generator that a `Simulation` can create by itself.
+///
+/// `Simulation::builder()` uses this to create the random number generator of a
+/// `Simulator` that is generic over its random number generator. Implement this trait
+/// to use your own random number generator that way; otherwise, passing it to `set_rng`
+/// on the `SimulatorBuilder` is enough.
+pub trait FromEntropy: Rng {
+    /// Create a new random number generator that is seeded unpredictably.
+    fn from_entropy() -> Self;
+}
+
+impl FromEntropy for ThreadRng {
+    fn from_entropy() -> ThreadRng {
+        rand::thread_rng()
+    }
+}
+
+impl FromEntropy for StdRng {
+    fn from_entropy() -> StdRng {
+        let seed: Vec<usize> = rand::thread_rng().gen_iter().take(32).collect();
+        StdRng::from_seed(&seed[..])
+    }
+}
+
+impl FromEntropy for XorShiftRng {
+    fn from_entropy() -> XorShiftRng {
+        rand::thread_rng().gen()
+    }
+}
+
+impl FromEntropy for IsaacRng {
+    fn from_entropy() -> IsaacRng {
+        rand::thread_rng().gen()
+    }
+}
+
+impl FromEntropy for Isaac64Rng {
+    fn from_entropy() -> Isaac64Rng {
+        rand::thread_rng().gen()
+    }
+}
+
+impl FromEntropy for ChaChaRng {
+    fn from_entropy() -> ChaChaRng {
+        rand::thread_rng().gen()
+    }
+}
+
+/// A random number generator that can be reseeded with a single number.
+///
+/// `Simulator::checkpoint` uses this to carry the state of the random number generator
+/// over into a `Checkpoint`: it draws a seed from the generator and reseeds the generator
+/// with it. `SimulatorBuilder::restore` reseeds the generator of the restored `Simulator`
+/// with the same seed, so both runs draw the same numbers from then on.
+pub trait Reseed: Rng {
+    /// Reseed this random number generator with `seed`. Returns `false` if it can not be
+    /// reseeded, like the thread-local random number generator.
+    fn reseed_with(&mut self, seed: u64) -> bool;
+}
+
+impl Reseed for ThreadRng {
+    fn reseed_with(&mut self, _: u64) -> bool {
+        false
+    }
+}
+
+impl Reseed for StdRng {
+    fn reseed_with(&mut self, seed: u64) -> bool {
+        self.reseed(&[seed as usize, (seed >> 32) as usize][..]);
+        true
+    }
+}
+
+impl Reseed for XorShiftRng {
+    fn reseed_with(&mut self, seed: u64) -> bool {
+        let (low, high) = (seed as u32, (seed >> 32) as u32);
+        // The seed of an `XorShiftRng` must not be all zeros.
+        self.reseed([low, high, !low, !high]);
+        true
+    }
+}
+
+impl Reseed for IsaacRng {
+    fn reseed_with(&mut self, seed: u64) -> bool {
+        self.reseed(&[seed as u32, (seed >> 32) as u32][..]);
+        true
+    }
+}
+
+impl Reseed for Isaac64Rng {
+    fn reseed_with(&mut self, seed: u64) -> bool {
+        self.reseed(&[seed][..]);
+        true
+    }
+}
+
+impl Reseed for ChaChaRng {
+    fn reseed_with(&mut self, seed: u64) -> bool {
+        self.reseed(&[seed as u32, (seed >> 32) as u32][..]);
+        true
+    }
+}
@@ -0,0 +1,165 @@
+// file: age.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use rand::Rng;
+use std::cmp::Ordering;
+
+/// Runs binary tournaments, which are won by the phenotype in the best non-dominated front
+/// of fitness and age, where younger is better.
+///
+/// Commonly known as *Age-Fitness Pareto Selection*. A young phenotype is only dominated
+/// by phenotypes that are at least as young and perform at least as well, so new
+/// phenotypes get a chance to improve before older, better performing ones take over. This
+/// counters stagnation and bloat. Ages are counted in steps, see `Individual::age`.
+#[derive(Clone, Copy, Debug)]
+pub struct AgeFitnessSelector {
+    count: usize,
+}
+
+impl AgeFitnessSelector {
+    /// Create and return an age-fitness selector.
+    ///
+    /// Such a selector sorts the population into non-dominated fronts of fitness and age,
+    /// and runs `count` tournaments between two random phenotypes. The phenotype in the
+    /// better front wins, yielding `count` parents.
+    ///
+    /// * `count`: must be larger than zero, a multiple of two and less than the population size.
+    pub fn new(count: usize) -> AgeFitnessSelector {
+        AgeFitnessSelector { count }
+    }
+}
+
+/// Calculate the index of the non-dominated front of fitness and age of every phenotype in
+/// `population`, starting from 0.
+fn age_fitness_fronts<T: Phenotype>(population: &[Individual<T>],
+                                    fitness_type: FitnessType)
+                                    -> Vec<usize> {
+    let dominates = |x: &Individual<T>, y: &Individual<T>| {
+        let fitness = x.cmp_rank(y, fitness_type);
+        fitness != Ordering::Less && x.age() <= y.age() &&
+        (fitness == Ordering::Greater || x.age() < y.age())
+    };
+    let mut fronts = vec![usize::MAX; population.len()];
+    let mut front = 0;
+    let mut remaining: Vec<usize> = (0..population.len()).collect();
+    while !remaining.is_empty() {
+        let current: Vec<usize> = remaining.iter()
+                                           .cloned()
+                                           .filter(|&i| {
+                                               !remaining.iter().any(|&j| {
+                                                   dominates(&population[j], &population[i])
+                                               })
+                                           })
+                                           .collect();
+        for &i in &current {
+            fronts[i] = front;
+        }
+        remaining.retain(|&i| fronts[i] == usize::MAX);
+        front += 1;
+    }
+    fronts
+}
+
+impl<T: Phenotype> Selector<T> for AgeFitnessSelector {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  mut rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let fronts = age_fitness_fronts(population, fitness_type);
+        let mut tournament = || {
+            let x = Rng::gen_range(&mut rng, 0, population.len());
+            let y = Rng::gen_range(&mut rng, 0, population.len());
+            let winner = if fronts[y] < fronts[x] { y } else { x };
+            population[winner].phenotype()
+        };
+        let mut result: Parents<'a, T> = Vec::with_capacity(self.count / 2);
+        for _ in 0..(self.count / 2) {
+            result.push((tournament(), tournament()));
+        }
+        Ok(result)
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) || self.count >= population_size {
+            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero, a \
+                                multiple of two and less than the population size.",
+                               self.count));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::age_fitness_fronts;
+    use ::sim::*;
+    use ::sim::select::*;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_count_odd() {
+        let selector = AgeFitnessSelector::new(5);
+        let population: Vec<Individual<Test>> = (0..10).map(|i| Individual::new(Test { f: i }))
+                                                       .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_fronts() {
+        // Phenotype i has fitness value i and age 2 * i, so none dominates another, except
+        // that the last one is as young as the first but performs worse.
+        let mut population: Vec<Individual<Test>> =
+            (0..4).map(|i| Individual::new(Test { f: i })).collect();
+        for (i, x) in population.iter_mut().enumerate() {
+            for _ in 0..(2 * i) {
+                x.grow_older();
+            }
+        }
+        population.push(Individual::new(Test { f: -1 }));
+        let fronts = age_fitness_fronts(&population, FitnessType::Maximize);
+        assert_eq!(fronts, vec![0, 0, 0, 0, 1]);
+        // When minimizing, the first phenotype is both the best and the youngest.
+        let fronts = age_fitness_fronts(&population, FitnessType::Minimize);
+        assert_eq!(fronts, vec![1, 2, 3, 4, 0]);
+    }
+}
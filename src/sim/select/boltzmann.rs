@@ -0,0 +1,263 @@
+// file: boltzmann.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::{Fitness, Phenotype};
+use super::*;
+use super::super::{FitnessType, Individual};
+use rand::Rng;
+use rand::distributions::{IndependentSample, Range};
+
+/// A schedule of the temperature of a `BoltzmannSelector` over the generations of a run.
+#[derive(Clone, Copy, Debug)]
+pub enum Temperature {
+    /// The same temperature in every generation.
+    Constant(f64),
+    /// Decrease linearly from `start` to `end` in `generations` generations, and stay at
+    /// `end` afterwards.
+    Linear {
+        /// The temperature in the first generation.
+        start: f64,
+        /// The final temperature.
+        end: f64,
+        /// The number of generations in which to reach `end`.
+        generations: u64,
+    },
+    /// Start at `start`, and multiply by `factor` every generation.
+    Exponential {
+        /// The temperature in the first generation.
+        start: f64,
+        /// The factor by which the temperature changes every generation.
+        factor: f64,
+    },
+    /// Calculate the temperature from the number of the generation, starting from 0.
+    Custom(fn(u64) -> f64),
+}
+
+impl Temperature {
+    /// Get the temperature in `generation`, starting from 0.
+    pub fn at(&self, generation: u64) -> f64 {
+        match *self {
+            Temperature::Constant(t) => t,
+            Temperature::Linear { start, end, generations } => {
+                if generation >= generations {
+                    end
+                } else {
+                    start + (end - start) * generation as f64 / generations as f64
+                }
+            }
+            Temperature::Exponential { start, factor } => {
+                start * factor.powf(generation as f64)
+            }
+            Temperature::Custom(f) => f(generation),
+        }
+    }
+}
+
+/// Check whether a temperature is valid.
+fn validate_temperature(temperature: f64) -> Result<(), String> {
+    if temperature.is_nan() || temperature <= 0.0 || temperature.is_infinite() {
+        return Err(format!("Invalid temperature: {}. Should be larger than zero and finite.",
+                           temperature));
+    }
+    Ok(())
+}
+
+/// Selects phenotypes with a probability proportional to `exp(fitness / temperature)` when
+/// maximizing, and to `exp(-fitness / temperature)` when minimizing.
+///
+/// Commonly known as *Boltzmann Selection*. A high temperature selects almost uniformly,
+/// while a low one nearly always selects the best phenotypes, so lowering the temperature
+/// during a run shifts from exploration to exploitation. The temperature follows a
+/// `Temperature` schedule over the generations of the `Simulation`, which tells the selector
+/// the generation through `Selector::set_generation` before every step. The schedule
+/// therefore carries on when a run is resumed, restored or forked.
+#[derive(Clone, Debug)]
+pub struct BoltzmannSelector {
+    count: usize,
+    temperature: Temperature,
+    generation: u64,
+}
+
+impl BoltzmannSelector {
+    /// Create and return a Boltzmann selector.
+    ///
+    /// * `count`: must be larger than zero, a multiple of two and less than the population size.
+    /// * `temperature`: the temperature schedule. Temperatures must be larger than zero.
+    pub fn new(count: usize, temperature: Temperature) -> BoltzmannSelector {
+        BoltzmannSelector {
+            count,
+            temperature,
+            generation: 0,
+        }
+    }
+
+    /// Get the generation this selector selects parents for next, starting from 0.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+impl<T: Phenotype> Selector<T> for BoltzmannSelector {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  mut rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+        let temperature = self.temperature.at(self.generation);
+        validate_temperature(temperature)?;
+
+        // Relative to the best fitness value, so the exponentials can not overflow.
+        let fitnesses: Vec<f64> = population.iter()
+                                            .map(|x| match fitness_type {
+                                                FitnessType::Maximize => x.fitness().to_f64(),
+                                                FitnessType::Minimize => -x.fitness().to_f64(),
+                                            })
+                                            .collect();
+        let best = fitnesses.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        if best.is_infinite() || fitnesses.iter().any(|f| f.is_nan()) {
+            return Err(String::from("Could not complete Boltzmann Selection: fitness values \
+                                     must be finite."));
+        }
+        let cum_weights: Vec<f64> = fitnesses.iter()
+                                             .scan(0.0, |state, &f| {
+                                                 *state += ((f - best) / temperature).exp();
+                                                 Some(*state)
+                                             })
+                                             .collect();
+        let between = Range::new(0.0, cum_weights[cum_weights.len() - 1]);
+        let mut spin = || {
+            let c = between.ind_sample(&mut rng);
+            let i = cum_weights.iter().position(|&w| w > c).unwrap_or(population.len() - 1);
+            population[i].phenotype()
+        };
+        let mut results: Parents<'a, T> = Vec::with_capacity(self.count / 2);
+        for _ in 0..(self.count / 2) {
+            results.push((spin(), spin()));
+        }
+        Ok(results)
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) || self.count >= population_size {
+            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero, a \
+                                multiple of two and less than the population size.",
+                               self.count));
+        }
+        validate_temperature(self.temperature.at(0))
+    }
+
+    fn set_generation(&mut self, generation: u64) {
+        self.generation = generation;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn population() -> Vec<Individual<Test>> {
+        (0..100).map(|i| Individual::new(Test { f: i })).collect()
+    }
+
+    /// Count how often the best phenotype is selected as one of 98 parents.
+    fn count_best(selector: &BoltzmannSelector, fitness_type: FitnessType) -> usize {
+        let population = population();
+        let best = match fitness_type {
+            FitnessType::Maximize => 99,
+            FitnessType::Minimize => 0,
+        };
+        let mut rng = ::rand::thread_rng();
+        selector.select(&population, fitness_type, &mut rng)
+                .unwrap()
+                .into_iter()
+                .flat_map(|(x, y)| vec![x.f, y.f])
+                .filter(|&f| f == best)
+                .count()
+    }
+
+    #[test]
+    fn test_temperature_invalid() {
+        let selector = BoltzmannSelector::new(20, Temperature::Constant(0.0));
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_nan_fitness() {
+        let mut population = population();
+        population.push(Individual::with_fitness(Test { f: 0 }, f64::NAN));
+        let selector = BoltzmannSelector::new(20, Temperature::Constant(1.0));
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Maximize, &mut rng).is_err());
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_schedules() {
+        let linear = Temperature::Linear {
+            start: 10.0,
+            end: 2.0,
+            generations: 4,
+        };
+        assert_eq!(linear.at(0), 10.0);
+        assert_eq!(linear.at(2), 6.0);
+        assert_eq!(linear.at(10), 2.0);
+        let exponential = Temperature::Exponential {
+            start: 8.0,
+            factor: 0.5,
+        };
+        assert_eq!(exponential.at(3), 1.0);
+        assert_eq!(Temperature::Custom(|g| g as f64 + 1.0).at(4), 5.0);
+    }
+
+    #[test]
+    fn test_cooling() {
+        // Hot enough to select almost uniformly at first, and cold enough to nearly always
+        // select the best phenotype after 10 generations.
+        let mut selector = BoltzmannSelector::new(98, Temperature::Exponential {
+            start: 1000.0,
+            factor: 0.1,
+        });
+        assert!(count_best(&selector, FitnessType::Maximize) < 10);
+        Selector::<Test>::set_generation(&mut selector, 10);
+        assert_eq!(selector.generation(), 10);
+        assert_eq!(count_best(&selector, FitnessType::Maximize), 98);
+        assert_eq!(count_best(&selector, FitnessType::Minimize), 98);
+    }
+}
@@ -0,0 +1,192 @@
+// file: combined.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use rand::Rng;
+
+/// Selects every parent pair with one of several other selectors, chosen at random by
+/// weight.
+///
+/// This combines selection schemes, such as mostly tournament selection with some random
+/// selection, without writing a new selector.
+pub struct CombinedSelector<T: Phenotype> {
+    count: usize,
+    selectors: Vec<(f64, Box<dyn Selector<T>>)>,
+}
+
+impl<T: Phenotype> CombinedSelector<T> {
+    /// Create and return a combined selector, without any selectors to combine yet. Add
+    /// them with `with_selector`.
+    ///
+    /// Such a selector picks a selector by weight for every pair of parents, and takes the
+    /// next pair of that selector, which selects again once its pairs run out. This
+    /// selector yields `count` parents.
+    ///
+    /// * `count`: must be larger than zero and a multiple of two.
+    pub fn new(count: usize) -> CombinedSelector<T> {
+        CombinedSelector {
+            count,
+            selectors: Vec::new(),
+        }
+    }
+
+    /// Add `selector`, which selects parent pairs in proportion to `weight`. The weight
+    /// must be larger than zero.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn with_selector(mut self,
+                         weight: f64,
+                         selector: Box<dyn Selector<T>>)
+                         -> CombinedSelector<T> {
+        self.selectors.push((weight, selector));
+        self
+    }
+}
+
+impl<T: Phenotype> Clone for CombinedSelector<T> {
+    fn clone(&self) -> CombinedSelector<T> {
+        CombinedSelector {
+            count: self.count,
+            selectors: self.selectors.clone(),
+        }
+    }
+}
+
+impl<T: Phenotype + 'static> Selector<T> for CombinedSelector<T> {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  mut rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let total: f64 = self.selectors.iter().map(|&(weight, _)| weight).sum();
+        // The pairs every selector has selected, but that have not been used yet.
+        let mut pending: Vec<Parents<'a, T>> = vec![Vec::new(); self.selectors.len()];
+        let mut result: Parents<'a, T> = Vec::with_capacity(self.count / 2);
+        while result.len() < self.count / 2 {
+            let mut c = Rng::gen::<f64>(&mut rng) * total;
+            let mut i = 0;
+            while i + 1 < self.selectors.len() && c >= self.selectors[i].0 {
+                c -= self.selectors[i].0;
+                i += 1;
+            }
+            if pending[i].is_empty() {
+                pending[i] = self.selectors[i].1.select(population, fitness_type, rng)?;
+                if pending[i].is_empty() {
+                    return Err(String::from("A combined selector did not select any parents."));
+                }
+                pending[i].reverse();
+            }
+            result.push(pending[i].pop().unwrap());
+        }
+        Ok(result)
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) {
+            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero and \
+                                a multiple of two.",
+                               self.count));
+        }
+        if self.selectors.is_empty() {
+            return Err(String::from("A combined selector needs at least one selector."));
+        }
+        for &(weight, ref selector) in &self.selectors {
+            if weight.is_nan() || weight <= 0.0 || weight.is_infinite() {
+                return Err(format!("Invalid weight: {}. Should be larger than zero and \
+                                    finite.",
+                                   weight));
+            }
+            selector.validate(population_size)?;
+        }
+        Ok(())
+    }
+
+    fn set_parallel(&mut self) {
+        for &mut (_, ref mut selector) in &mut self.selectors {
+            selector.set_parallel();
+        }
+    }
+
+    fn set_generation(&mut self, generation: u64) {
+        for &mut (_, ref mut selector) in &mut self.selectors {
+            selector.set_generation(generation);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn population() -> Vec<Individual<Test>> {
+        (0..100).map(|i| Individual::new(Test { f: i })).collect()
+    }
+
+    #[test]
+    fn test_no_selectors() {
+        let selector = CombinedSelector::<Test>::new(20);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_weight_invalid() {
+        let selector = CombinedSelector::new(20).with_selector(0.0,
+                                                               Box::new(RandomSelector::new(2)));
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_weights() {
+        // The maximize selector always selects the pair (99, 98).
+        let selector = CombinedSelector::new(2000)
+                           .with_selector(0.8, Box::new(MaximizeSelector::new(2)))
+                           .with_selector(0.2, Box::new(RandomSelector::new(10)));
+        let population = population();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        assert_eq!(1000, parents.len());
+        let best = parents.iter().filter(|&&(x, y)| x.f == 99 && y.f == 98).count();
+        assert!(best > 750 && best < 850);
+    }
+}
@@ -0,0 +1,286 @@
+// file: crowded.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::{Fitness, Phenotype};
+use super::*;
+use super::super::{FitnessType, Individual};
+use super::super::pareto::{crowding_distance, rank_and_crowd};
+use rand::Rng;
+
+/// Runs binary crowded tournaments, as in NSGA-II.
+///
+/// A tournament is won by the phenotype in the best non-dominated front. If both are in
+/// the same front, the one with the largest crowding distance wins, which favours
+/// phenotypes in sparsely populated parts of the front.
+///
+/// With a tolerance, this selector also preserves diversity for a single objective.
+/// See `with_tolerance`. With `in_parallel`, the tournaments run on multiple threads.
+#[derive(Clone, Copy, Debug)]
+pub struct CrowdedTournamentSelector {
+    count: usize,
+    tolerance: Option<f64>,
+    parallel: bool,
+}
+
+impl CrowdedTournamentSelector {
+    /// Create and return a crowded tournament selector.
+    ///
+    /// Such a selector runs `count` tournaments between two random phenotypes,
+    /// yielding `count` parents. Phenotypes are drawn with replacement, so `count`
+    /// may exceed the population size: NSGA-II creates as many children as there are
+    /// phenotypes, which takes twice as many parents.
+    ///
+    /// * `count`: must be larger than zero and a multiple of two.
+    pub fn new(count: usize) -> CrowdedTournamentSelector {
+        CrowdedTournamentSelector {
+            count,
+            tolerance: None,
+            parallel: false,
+        }
+    }
+
+    /// Run the tournaments on multiple threads, using `rayon`. Every pair of tournaments
+    /// gets its own random number generator, seeded from the one of the `Simulation`, so
+    /// seeded runs stay reproducible, but select different parents than when running
+    /// sequentially.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn in_parallel(mut self) -> CrowdedTournamentSelector {
+        self.parallel = true;
+        self
+    }
+
+    /// Compare fitness values as numbers, through `Fitness::to_f64`, instead of by their
+    /// non-dominated fronts. Fitness values that differ by at most `tolerance` are tied,
+    /// and ties are won by the phenotype with the largest crowding distance in the whole
+    /// population. This keeps a single-objective population from converging on a single
+    /// fitness value.
+    ///
+    /// * `tolerance`: must not be negative.
+    pub fn with_tolerance(mut self, tolerance: f64) -> CrowdedTournamentSelector {
+        self.tolerance = Some(tolerance);
+        self
+    }
+}
+
+impl<T: Phenotype> Selector<T> for CrowdedTournamentSelector {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let size = population.len();
+        let (ranks, distances) = match self.tolerance {
+            Some(_) => {
+                let all: Vec<usize> = (0..population.len()).collect();
+                (Vec::new(), crowding_distance(population, &all))
+            }
+            None => rank_and_crowd(population, fitness_type),
+        };
+        // The fitness values as numbers, so the threads do not need to share the population.
+        let values: Vec<f64> = match self.tolerance {
+            Some(_) => population.iter().map(|x| x.fitness().to_f64()).collect(),
+            None => Vec::new(),
+        };
+        let tournament = |mut rng: &mut dyn Rng| {
+            let x = Rng::gen_range(&mut rng, 0, size);
+            let y = Rng::gen_range(&mut rng, 0, size);
+            let y_wins = match self.tolerance {
+                Some(tolerance) => {
+                    let (fx, fy) = (values[x], values[y]);
+                    if (fx - fy).abs() <= tolerance {
+                        distances[y] > distances[x]
+                    } else {
+                        match fitness_type {
+                            FitnessType::Maximize => fy > fx,
+                            FitnessType::Minimize => fy < fx,
+                        }
+                    }
+                }
+                None => {
+                    ranks[y] < ranks[x] || (ranks[y] == ranks[x] && distances[y] > distances[x])
+                }
+            };
+            if y_wins { y } else { x }
+        };
+        let pair = |rng: &mut dyn Rng| {
+            let x = tournament(rng);
+            (x, tournament(rng))
+        };
+        let pairs: Vec<(usize, usize)> = if self.parallel {
+            par_pairs(self.count / 2, rng, pair)
+        } else {
+            (0..(self.count / 2)).map(|_| pair(rng)).collect()
+        };
+        Ok(pairs.into_iter()
+                .map(|(x, y)| (population[x].phenotype(), population[y].phenotype()))
+                .collect())
+    }
+
+    fn validate(&self, _population_size: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) {
+            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero and \
+                                a multiple of two.",
+                               self.count));
+        }
+        if let Some(tolerance) = self.tolerance {
+            if tolerance.is_nan() || tolerance < 0.0 {
+                return Err(format!("Invalid parameter `tolerance`: {}. Should not be negative.",
+                                   tolerance));
+            }
+        }
+        Ok(())
+    }
+
+    fn set_parallel(&mut self) {
+        self.parallel = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+    use pheno::Objectives;
+    use rand::{SeedableRng, XorShiftRng};
+
+    #[derive(Clone)]
+    struct Test {
+        cost: i64,
+        quality: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = Objectives;
+
+        fn fitness(&self) -> Objectives {
+            Objectives(vec![-self.cost as f64, self.quality as f64])
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test {
+                cost: (self.cost + t.cost) / 2,
+                quality: (self.quality + t.quality) / 2,
+            }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn population() -> Vec<Individual<Test>> {
+        (0..100).map(|i| Individual::new(Test { cost: i, quality: i % 10 })).collect()
+    }
+
+    #[test]
+    fn test_count_zero() {
+        let selector = CrowdedTournamentSelector::new(0);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_count_odd() {
+        let selector = CrowdedTournamentSelector::new(5);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_result_size() {
+        let selector = CrowdedTournamentSelector::new(200);
+        let population = population();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        assert_eq!(200, parents.len() * 2);
+    }
+
+    #[test]
+    fn test_tolerance_negative() {
+        let selector = CrowdedTournamentSelector::new(10).with_tolerance(-1.0);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[derive(Clone)]
+    struct Single {
+        f: i64,
+    }
+
+    impl Phenotype for Single {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f as f64
+        }
+
+        fn crossover(&self, t: &Single) -> Single {
+            Single { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Single {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_single_objective_diversity() {
+        // A third of the population is crowded on the same fitness value.
+        let population: Vec<Individual<Single>> = (0..100).chain((0..50).map(|_| 50))
+                                                          .map(|f| Individual::new(Single { f }))
+                                                          .collect();
+        let selector = CrowdedTournamentSelector::new(1000).with_tolerance(f64::INFINITY);
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        let crowded = parents.iter()
+                             .flat_map(|&(x, y)| vec![x, y])
+                             .filter(|x| x.f == 50)
+                             .count();
+        // A crowded phenotype only wins against another crowded phenotype.
+        assert!(crowded < 250);
+    }
+
+    #[test]
+    fn test_single_objective_fitness() {
+        let population: Vec<Individual<Single>> =
+            (0..100).map(|f| Individual::new(Single { f })).collect();
+        let selector = CrowdedTournamentSelector::new(1000).with_tolerance(0.0);
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        let mean = parents.iter().map(|&(x, y)| x.f + y.f).sum::<i64>() as f64 / 1000.0;
+        // Binary tournaments favour the best performing phenotypes.
+        assert!(mean > 55.0);
+    }
+
+    #[test]
+    fn test_in_parallel() {
+        let population = population();
+        let selector = CrowdedTournamentSelector::new(200).in_parallel();
+        let select = || {
+            let mut rng: XorShiftRng = SeedableRng::from_seed([1, 2, 3, 4]);
+            selector.select(&population, FitnessType::Maximize, &mut rng).unwrap()
+        };
+        let parents = select();
+        assert_eq!(100, parents.len());
+        // Seeded selection does not depend on the threads.
+        let again = select();
+        assert!(parents.iter().zip(&again).all(|(x, y)| ::std::ptr::eq(x.0, y.0) &&
+                                                         ::std::ptr::eq(x.1, y.1)));
+    }
+}
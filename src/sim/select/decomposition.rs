@@ -0,0 +1,136 @@
+// file: decomposition.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use super::super::pareto::Decomposition;
+use rand::Rng;
+
+/// Selects the parents for every subproblem of a `Decomposition` from its neighbourhood,
+/// as in MOEA/D.
+#[derive(Clone, Debug)]
+pub struct DecompositionSelector {
+    decomposition: Decomposition,
+}
+
+impl DecompositionSelector {
+    /// Create and return a decomposition selector.
+    ///
+    /// Such a selector selects two random parents from the neighbourhood of every
+    /// subproblem of `decomposition`, in order, yielding one pair of parents for every
+    /// subproblem. The member of the population at index `i` solves subproblem `i`, so
+    /// the population size must equal the number of subproblems.
+    pub fn new(decomposition: Decomposition) -> DecompositionSelector {
+        DecompositionSelector { decomposition }
+    }
+}
+
+impl<T: Phenotype> Selector<T> for DecompositionSelector {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  _: FitnessType,
+                  mut rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let mut result: Parents<'a, T> = Vec::with_capacity(population.len());
+        for i in 0..self.decomposition.len() {
+            let neighbourhood = self.decomposition.neighbourhood(i);
+            let mut parent = || {
+                let index = Rng::gen_range(&mut rng, 0, neighbourhood.len());
+                population[neighbourhood[index]].phenotype()
+            };
+            result.push((parent(), parent()));
+        }
+        Ok(result)
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if population_size != self.decomposition.len() {
+            return Err(format!("Invalid population size: {}. Should be the number of \
+                                subproblems, {}.",
+                               population_size,
+                               self.decomposition.len()));
+        }
+        if self.decomposition.neighbourhood(0).is_empty() {
+            return Err(String::from("Invalid neighbourhood size: 0. Should be larger than \
+                                     zero."));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::pareto::*;
+    use ::sim::select::*;
+    use pheno::Objectives;
+
+    #[derive(Clone)]
+    struct Test {
+        x: f64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = Objectives;
+
+        fn fitness(&self) -> Objectives {
+            Objectives(vec![self.x * self.x, (self.x - 2.0) * (self.x - 2.0)])
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { x: (self.x + t.x) / 2.0 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn population(size: usize) -> Vec<Individual<Test>> {
+        (0..size).map(|i| Individual::new(Test { x: i as f64 })).collect()
+    }
+
+    #[test]
+    fn test_population_size() {
+        let selector = DecompositionSelector::new(Decomposition::new(simplex_weights(2, 10), 3));
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(10), FitnessType::Minimize, &mut rng).is_err());
+        assert!(selector.select(&population(11), FitnessType::Minimize, &mut rng).is_ok());
+    }
+
+    #[test]
+    fn test_neighbourhood_zero() {
+        let selector = DecompositionSelector::new(Decomposition::new(simplex_weights(2, 10), 0));
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(11), FitnessType::Minimize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_neighbourhood() {
+        let selector = DecompositionSelector::new(Decomposition::new(simplex_weights(2, 10), 1));
+        let population = population(11);
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Minimize, &mut rng).unwrap();
+        // Every subproblem is its own only neighbour.
+        assert_eq!(parents.len(), 11);
+        for (i, &(x, y)) in parents.iter().enumerate() {
+            assert_eq!((x.x, y.x), (i as f64, i as f64));
+        }
+    }
+}
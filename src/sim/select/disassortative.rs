@@ -0,0 +1,166 @@
+// file: disassortative.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use rand::Rng;
+
+/// Selects parents with another selector, and then pairs them up by negative assortative
+/// mating: every first parent is mated with the most distant of a random sample of the
+/// selected parents. Mating dissimilar parents keeps the population diverse.
+pub struct DisassortativeSelector<T: Phenotype> {
+    selector: Box<dyn Selector<T>>,
+    sample: usize,
+    distance: fn(&T, &T) -> f64,
+}
+
+impl<T: Phenotype> DisassortativeSelector<T> {
+    /// Create and return a negative assortative mating selector.
+    ///
+    /// Such a selector lets `selector` select the parents, and keeps the first parent of
+    /// every pair. Its mate is the most distant of `sample` parents, drawn at random from
+    /// all selected parents. The resulting number of parents is that of `selector`.
+    ///
+    /// * `sample`: the number of candidate mates, must be larger than zero.
+    /// * `distance`: calculates the distance between two phenotypes.
+    pub fn new(selector: Box<dyn Selector<T>>,
+               sample: usize,
+               distance: fn(&T, &T) -> f64)
+               -> DisassortativeSelector<T> {
+        DisassortativeSelector {
+            selector,
+            sample,
+            distance,
+        }
+    }
+}
+
+impl<T: Phenotype> Clone for DisassortativeSelector<T> {
+    fn clone(&self) -> DisassortativeSelector<T> {
+        DisassortativeSelector {
+            selector: self.selector.clone(),
+            sample: self.sample,
+            distance: self.distance,
+        }
+    }
+}
+
+impl<T: Phenotype + 'static> Selector<T> for DisassortativeSelector<T> {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  mut rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let parents = self.selector.select(population, fitness_type, rng)?;
+        let candidates: Vec<&'a T> = parents.iter().flat_map(|&(x, y)| vec![x, y]).collect();
+        let mut result: Parents<'a, T> = Vec::with_capacity(parents.len());
+        for (x, _) in parents {
+            let mut mate = candidates[Rng::gen_range(&mut rng, 0, candidates.len())];
+            let mut furthest = (self.distance)(x, mate);
+            for _ in 1..self.sample {
+                let candidate = candidates[Rng::gen_range(&mut rng, 0, candidates.len())];
+                let d = (self.distance)(x, candidate);
+                if d > furthest {
+                    mate = candidate;
+                    furthest = d;
+                }
+            }
+            result.push((x, mate));
+        }
+        Ok(result)
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.sample == 0 {
+            return Err(format!("Invalid parameter `sample`: {}. Should be larger than zero.",
+                               self.sample));
+        }
+        self.selector.validate(population_size)
+    }
+
+    fn set_parallel(&mut self) {
+        self.selector.set_parallel();
+    }
+
+    fn set_generation(&mut self, generation: u64) {
+        self.selector.set_generation(generation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+
+    #[derive(Clone, Debug)]
+    struct Test {
+        f: f64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2.0 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn distance(x: &Test, y: &Test) -> f64 {
+        (x.f - y.f).abs()
+    }
+
+    fn population() -> Vec<Individual<Test>> {
+        (0..100).map(|i| Individual::new(Test { f: i as f64 })).collect()
+    }
+
+    #[test]
+    fn test_sample_zero() {
+        let selector = DisassortativeSelector::new(Box::new(MaximizeSelector::new(20)),
+                                                   0,
+                                                   distance);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_most_distant_mate() {
+        let selector = DisassortativeSelector::new(Box::new(StochasticSelector::new(20)),
+                                                   1000,
+                                                   distance);
+        let population = population();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        assert_eq!(10, parents.len());
+        let selected: Vec<f64> = parents.iter().flat_map(|&(x, y)| vec![x.f, y.f]).collect();
+        let min = selected.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = selected.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        // With a sample this large, the mate is an extreme of the selected parents.
+        for &(x, y) in &parents {
+            assert!(distance(x, y) == (x.f - min).max(max - x.f));
+        }
+    }
+}
@@ -0,0 +1,255 @@
+// file: lexicase.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::{Fitness, Phenotype};
+use super::*;
+use super::super::{FitnessType, Individual};
+use super::super::stats::median;
+use rand::Rng;
+
+/// Selects every parent by filtering the population through the test cases in random
+/// order, keeping only the best performing phenotypes on each case.
+///
+/// Commonly known as *Lexicase Selection*. Every objective of a fitness value, as returned
+/// by `Fitness::objectives`, is a test case, such as the error of a program on one input.
+/// Use `Objectives` for such fitness values, and minimize them if they are errors. As
+/// different cases come first in every selection, phenotypes that solve hard cases are
+/// selected even if they do badly overall.
+///
+/// With continuous errors, hardly any two phenotypes perform exactly the same on a case,
+/// so the first case nearly always decides. `with_epsilon` enables *Epsilon-Lexicase
+/// Selection* for such errors.
+#[derive(Clone, Copy, Debug)]
+pub struct LexicaseSelector {
+    count: usize,
+    epsilon: bool,
+}
+
+impl LexicaseSelector {
+    /// Create and return a lexicase selector.
+    ///
+    /// Such a selector selects every parent by shuffling the cases, and going through
+    /// them until one phenotype is left, or the cases run out and a random one of the
+    /// remaining phenotypes is selected. This selector yields `count` parents.
+    ///
+    /// * `count`: must be larger than zero and a multiple of two.
+    pub fn new(count: usize) -> LexicaseSelector {
+        LexicaseSelector {
+            count,
+            epsilon: false,
+        }
+    }
+
+    /// Keep every phenotype within a tolerance of the best one on each case, instead of
+    /// only the best ones. The tolerance of a case is the median absolute deviation of the
+    /// errors of the population on it.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn with_epsilon(mut self) -> LexicaseSelector {
+        self.epsilon = true;
+        self
+    }
+}
+
+impl<T: Phenotype> Selector<T> for LexicaseSelector {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  mut rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        // Errors on every case, lower is better.
+        let errors: Vec<Vec<f64>> = population.iter()
+                                              .map(|x| {
+                                                  let cases = x.fitness().objectives();
+                                                  match fitness_type {
+                                                      FitnessType::Maximize => {
+                                                          cases.iter().map(|c| -c).collect()
+                                                      }
+                                                      FitnessType::Minimize => cases,
+                                                  }
+                                              })
+                                              .collect();
+        let cases = errors[0].len();
+        if errors.iter().any(|x| x.len() != cases) {
+            return Err(String::from("Lexicase selection requires the same number of cases \
+                                     for every phenotype."));
+        }
+        if errors.iter().any(|x| x.iter().any(|e| e.is_nan())) {
+            return Err(String::from("Could not complete Lexicase Selection: fitness values \
+                                     must not be NaN."));
+        }
+
+        let epsilons: Vec<f64> = (0..cases)
+            .map(|case| {
+                if !self.epsilon {
+                    return 0.0;
+                }
+                let errors: Vec<f64> = errors.iter().map(|x| x[case]).collect();
+                let center = median(errors.clone());
+                median(errors.into_iter().map(|e| (e - center).abs()).collect())
+            })
+            .collect();
+
+        let mut order: Vec<usize> = (0..cases).collect();
+        let mut select_one = || {
+            for i in (1..order.len()).rev() {
+                let j = Rng::gen_range(&mut rng, 0, i + 1);
+                order.swap(i, j);
+            }
+            let mut candidates: Vec<usize> = (0..population.len()).collect();
+            for &case in &order {
+                if candidates.len() == 1 {
+                    break;
+                }
+                let best = candidates.iter()
+                                     .map(|&i| errors[i][case])
+                                     .fold(f64::INFINITY, f64::min);
+                let within = |&i: &usize| errors[i][case] <= best + epsilons[case];
+                let kept: Vec<usize> = candidates.iter().cloned().filter(within).collect();
+                // Infinite errors can make the tolerance NaN, which keeps no one.
+                if !kept.is_empty() {
+                    candidates = kept;
+                }
+            }
+            let winner = candidates[Rng::gen_range(&mut rng, 0, candidates.len())];
+            population[winner].phenotype()
+        };
+        let mut result: Parents<'a, T> = Vec::with_capacity(self.count / 2);
+        for _ in 0..(self.count / 2) {
+            result.push((select_one(), select_one()));
+        }
+        Ok(result)
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) {
+            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero and \
+                                a multiple of two.",
+                               self.count));
+        }
+        if population_size == 0 {
+            return Err(String::from("Lexicase selection requires at least one phenotype."));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+    use pheno::Objectives;
+
+    #[derive(Clone)]
+    struct Test {
+        errors: Vec<f64>,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = Objectives;
+
+        fn fitness(&self) -> Objectives {
+            Objectives(self.errors.clone())
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { errors: t.errors.clone() }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn population(errors: Vec<Vec<f64>>) -> Vec<Individual<Test>> {
+        errors.into_iter().map(|errors| Individual::new(Test { errors })).collect()
+    }
+
+    #[test]
+    fn test_case_count() {
+        let population = population(vec![vec![1.0, 2.0], vec![1.0]]);
+        let mut rng = ::rand::thread_rng();
+        let selector = LexicaseSelector::new(2);
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_specialists() {
+        // Two specialists that each solve one case, a generalist that is mediocre on
+        // both cases, and a phenotype that is dominated by the generalist.
+        let population = population(vec![vec![0.0, 10.0],
+                                         vec![10.0, 0.0],
+                                         vec![4.0, 4.0],
+                                         vec![5.0, 5.0]]);
+        let mut rng = ::rand::thread_rng();
+        let selector = LexicaseSelector::new(1000);
+        let parents = selector.select(&population, FitnessType::Minimize, &mut rng).unwrap();
+        let selected: Vec<&Test> = parents.iter().flat_map(|&(x, y)| vec![x, y]).collect();
+        // Only the specialists are ever selected, about equally often.
+        assert!(selected.iter().all(|x| x.errors[0] == 0.0 || x.errors[1] == 0.0));
+        let first = selected.iter().filter(|x| x.errors[0] == 0.0).count();
+        assert!(first > 400 && first < 600);
+        // When maximizing, the worst specialist on every case wins instead.
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        assert!(parents.iter()
+                       .flat_map(|&(x, y)| vec![x, y])
+                       .all(|x| x.errors[0] == 10.0 || x.errors[1] == 10.0));
+    }
+
+    #[test]
+    fn test_epsilon() {
+        // The median absolute deviation of both cases is 0.1, so the first three phenotypes
+        // are within the tolerance of the best on every case, and the others never are.
+        let population = population(vec![vec![0.0, 0.1],
+                                         vec![0.1, 0.0],
+                                         vec![0.05, 0.05],
+                                         vec![1.0, 1.0],
+                                         vec![1.0, 1.0]]);
+        let mut rng = ::rand::thread_rng();
+        let selector = LexicaseSelector::new(1000).with_epsilon();
+        let parents = selector.select(&population, FitnessType::Minimize, &mut rng).unwrap();
+        let selected: Vec<&Test> = parents.iter().flat_map(|&(x, y)| vec![x, y]).collect();
+        assert!(selected.iter().all(|x| x.errors[0] < 1.0));
+        assert!(selected.iter().any(|x| x.errors[0] == 0.05));
+        // Without a tolerance, the third phenotype is never selected.
+        let parents = LexicaseSelector::new(1000)
+                          .select(&population, FitnessType::Minimize, &mut rng)
+                          .unwrap();
+        assert!(parents.iter().all(|&(x, y)| x.errors[0] != 0.05 && y.errors[0] != 0.05));
+    }
+
+    #[test]
+    fn test_nan_error() {
+        let population = population(vec![vec![0.0, f64::NAN], vec![1.0, 1.0]]);
+        let mut rng = ::rand::thread_rng();
+        let selector = LexicaseSelector::new(2);
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_infinite_error() {
+        // Every error on the first case is infinite, so its tolerance is NaN.
+        let population = population(vec![vec![f64::INFINITY, 0.0],
+                                         vec![f64::INFINITY, 1.0],
+                                         vec![f64::INFINITY, 2.0]]);
+        let mut rng = ::rand::thread_rng();
+        let selector = LexicaseSelector::new(100).with_epsilon();
+        let parents = selector.select(&population, FitnessType::Minimize, &mut rng).unwrap();
+        assert_eq!(parents.len(), 50);
+    }
+}
@@ -16,10 +16,15 @@
 
 use pheno::Phenotype;
 use super::*;
-use super::super::FitnessType;
-use std::cmp::Ordering;
+use super::super::{FitnessType, Individual};
+use rand::Rng;
 
 /// Selects best performing phenotypes from the population.
+///
+/// Despite its name, this selector honors the `FitnessType`: when minimizing, it selects
+/// the phenotypes with the lowest fitness values. `TruncationSelector` selects at random
+/// from a fraction of the best performing phenotypes instead.
+#[derive(Clone, Copy, Debug)]
 pub struct MaximizeSelector {
     count: usize,
 }
@@ -28,49 +33,55 @@ impl MaximizeSelector {
     /// Create and return a maximizing selector.
     ///
     /// Such a selector selects only the `count` best performing phenotypes
-    /// as parents.
+    /// as parents: those with the highest fitness values when maximizing, and with the
+    /// lowest when minimizing.
     ///
     /// * `count`: must be larger than zero, a multiple of two and less than the population size.
     pub fn new(count: usize) -> MaximizeSelector {
-        MaximizeSelector { count: count }
+        MaximizeSelector { count }
     }
 }
 
 impl<T: Phenotype> Selector<T> for MaximizeSelector {
-    fn select(&self,
-              population: &Vec<Box<T>>,
-              fitness_type: FitnessType)
-              -> Result<Parents<T>, String> {
-        if self.count <= 0 || self.count % 2 != 0 || self.count * 2 >= population.len() {
-            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero, a \
-                                multiple of two and less than half the population size.",
-                               self.count));
-        }
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  _: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
 
-        let mut cloned = population.clone();
-        cloned.sort_by(|x, y| {
-            (*x).fitness().partial_cmp(&(*y).fitness()).unwrap_or(Ordering::Equal)
-        });
-        if let FitnessType::Maximize = fitness_type {
-            cloned.reverse();
-        }
-        let sorted: Vec<&Box<T>> = cloned.iter().take(self.count).collect();
+        // Only the best `count` phenotypes need sorting, so partition them off first.
+        let mut sorted: Vec<&Individual<T>> = population.iter().collect();
+        let compare = |x: &&Individual<T>, y: &&Individual<T>| y.cmp_rank(x, fitness_type);
+        sorted.select_nth_unstable_by(self.count - 1, compare);
+        sorted.truncate(self.count);
+        sorted.sort_by(compare);
         let mut index = 0;
-        let mut result: Parents<T> = Vec::new();
+        let mut result: Parents<'a, T> = Vec::new();
         while index < sorted.len() {
-            result.push((sorted[index].clone(), sorted[index + 1].clone()));
+            result.push((sorted[index].phenotype(),
+                         sorted[index + 1].phenotype()));
             index += 2;
         }
         Ok(result)
     }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) || self.count * 2 >= population_size {
+            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero, a \
+                                multiple of two and less than half the population size.",
+                               self.count));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use ::sim::*;
     use ::sim::select::*;
-    use ::pheno::*;
     use std::cmp;
+    use std::ptr;
 
     #[derive(Clone)]
     struct Test {
@@ -78,8 +89,10 @@ mod tests {
     }
 
     impl Phenotype for Test {
+        type Fitness = f64;
+
         fn fitness(&self) -> f64 {
-            (self.f - 0).abs() as f64
+            self.f.abs() as f64
         }
 
         fn crossover(&self, t: &Test) -> Test {
@@ -100,42 +113,87 @@ mod tests {
     #[test]
     fn test_count_zero() {
         let selector = MaximizeSelector::new(0);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert!(selector.select(&population, FitnessType::Minimize).is_err());
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
     }
 
     #[test]
     fn test_count_odd() {
         let selector = MaximizeSelector::new(5);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert!(selector.select(&population, FitnessType::Minimize).is_err());
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
     }
 
     #[test]
     fn test_count_too_large() {
         let selector = MaximizeSelector::new(100);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert!(selector.select(&population, FitnessType::Minimize).is_err());
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
     }
 
     #[test]
     fn test_result_size() {
         let selector = MaximizeSelector::new(20);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert_eq!(20,
-                   selector.select(&population, FitnessType::Minimize).unwrap().len() * 2);
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Minimize, &mut rng).unwrap();
+        assert_eq!(20, parents.len() * 2);
     }
 
     #[test]
     fn test_result_ok() {
         let selector = MaximizeSelector::new(20);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
         // The lowest fitness should be zero.
         assert!((0.0 -
-                 (*selector.select(&population, FitnessType::Minimize)
-                           .unwrap()[0]
-                       .0)
+                 selector.select(&population, FitnessType::Minimize, &mut rng)
+                         .unwrap()[0]
+                     .0
                      .fitness())
                     .abs() < 0.001);
     }
+
+    #[test]
+    fn test_fitness_type() {
+        let selector = MaximizeSelector::new(20);
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Minimize, &mut rng).unwrap();
+        assert!(parents.iter().all(|&(x, y)| x.f < 20 && y.f < 20));
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        assert!(parents.iter().all(|&(x, y)| x.f >= 80 && y.f >= 80));
+    }
+
+    #[test]
+    fn test_result_borrowed() {
+        let selector = MaximizeSelector::new(20);
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        // The parents are the phenotypes in the population, not copies.
+        assert!(ptr::eq(parents[0].0, population[99].phenotype()));
+    }
+
+    #[test]
+    fn test_clone_box() {
+        let selector: Box<dyn Selector<Test>> = Box::new(MaximizeSelector::new(20));
+        let cloned = selector.clone();
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(cloned.validate(population.len()).is_ok());
+        let parents = cloned.select(&population, FitnessType::Minimize, &mut rng).unwrap();
+        assert_eq!(20, parents.len() * 2);
+    }
 }
@@ -25,29 +25,125 @@ mod max;
 mod tournament;
 mod stochastic;
 mod roulette;
+mod rank;
+mod boltzmann;
+mod truncation;
+mod random;
+mod lexicase;
+mod scaling;
+mod combined;
+mod remainder;
+mod age;
+mod pareto;
+mod crowded;
+mod strength;
+mod decomposition;
+mod niching;
+mod species;
+mod disassortative;
 
 use pheno::Phenotype;
-use super::FitnessType;
+use rand::{Rng, XorShiftRng};
+use rayon::prelude::*;
+use super::{FitnessType, Individual};
 
 pub use self::max::MaximizeSelector;
 pub use self::tournament::TournamentSelector;
 pub use self::stochastic::StochasticSelector;
 pub use self::roulette::RouletteSelector;
+pub use self::rank::{ExponentialRankSelector, LinearRankSelector};
+pub use self::boltzmann::{BoltzmannSelector, Temperature};
+pub use self::truncation::TruncationSelector;
+pub use self::random::RandomSelector;
+pub use self::lexicase::LexicaseSelector;
+pub use self::scaling::{LinearScalingSelector, SigmaScalingSelector};
+pub use self::combined::CombinedSelector;
+pub use self::remainder::RemainderSelector;
+pub use self::age::AgeFitnessSelector;
+pub use self::pareto::ParetoSelector;
+pub use self::crowded::CrowdedTournamentSelector;
+pub use self::strength::StrengthSelector;
+pub use self::decomposition::DecompositionSelector;
+pub use self::niching::{ClearingSelector, SharingSelector};
+pub use self::species::SpeciesSelector;
+pub use self::disassortative::DisassortativeSelector;
 
-/// `Parents` come in a `Vec` of two `Box<T>`'s.
-pub type Parents<T> = Vec<(Box<T>, Box<T>)>;
+/// Draw `pairs` pairs of indices of parents with `draw`, on multiple threads.
+///
+/// Every pair gets its own random number generator, seeded from `rng`, so the pairs do not
+/// depend on how the work is spread over the threads.
+fn par_pairs<F>(pairs: usize, mut rng: &mut dyn Rng, draw: F) -> Vec<(usize, usize)>
+    where F: Fn(&mut dyn Rng) -> (usize, usize) + Sync
+{
+    let rngs: Vec<XorShiftRng> = (0..pairs).map(|_| Rng::gen(&mut rng)).collect();
+    rngs.into_par_iter().map(|mut rng| draw(&mut rng)).collect()
+}
+
+/// `Parents` come in a `Vec` of pairs of references to `T`'s in the population, so
+/// selecting does not clone any phenotypes.
+pub type Parents<'a, T> = Vec<(&'a T, &'a T)>;
 
 /// A `Selector` can select `Parents` for a new iteration of a `Simulation`.
-pub trait Selector<T: Phenotype> {
+///
+/// A `Selector` must implement `Clone` and must not borrow anything, so that it can be
+/// cloned behind a `Box` through `SelectorClone`, for example by `Simulator::fork`.
+pub trait Selector<T: Phenotype>: SelectorClone<T> {
     /// Select elements from a `population`, either maximizing or minimizing the fitness
-    /// (`fitness_type`).
+    /// (`fitness_type`). Any randomness must be drawn from `rng`, the random number
+    /// generator of the `Simulation`, so that seeded runs are reproducible.
     ///
     /// If invalid parameters are supplied or the algorithm fails, this function returns an
     /// `Err(String)`, containing a message indicating the error.
     ///
-    /// Otherwise it contains a vector of parent pairs wrapped in `Ok`.
-    fn select(&self,
-              population: &Vec<Box<T>>,
-              fitness_type: FitnessType)
-              -> Result<Parents<T>, String>;
+    /// Otherwise it contains a vector of parent pairs wrapped in `Ok`, which refer to
+    /// members of `population`.
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String>;
+
+    /// Check whether the parameters of this `Selector` allow selecting from a population
+    /// of `population_size` phenotypes. This is called when a `Simulation` is built, so
+    /// invalid parameters are reported before running.
+    ///
+    /// If they do not, this function returns an `Err(String)`, containing a message
+    /// indicating the error. The default implementation accepts any population size.
+    fn validate(&self, _population_size: usize) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Select on multiple threads from now on, if this `Selector` supports it.
+    /// `par::Simulator` calls this when it is built, so its selection runs on the `rayon`
+    /// thread pool as well.
+    ///
+    /// The default implementation does nothing, so the `Selector` keeps selecting on the
+    /// calling thread.
+    fn set_parallel(&mut self) {}
+
+    /// Set the generation this `Selector` selects parents for next, starting from 0.
+    /// A `Simulation` calls this before every selection with the number of iterations it
+    /// has run, so selectors that change over a run, such as `BoltzmannSelector`, follow it.
+    ///
+    /// The default implementation ignores the generation.
+    fn set_generation(&mut self, _generation: u64) {}
+}
+
+/// Clones a `Selector` behind a `Box`. This is implemented for every `Selector` that
+/// implements `Clone`, so it never needs to be implemented by hand.
+pub trait SelectorClone<T: Phenotype> {
+    /// Clone this `Selector` into a new `Box`.
+    fn clone_box(&self) -> Box<dyn Selector<T>>;
+}
+
+impl<T: Phenotype, S: Selector<T> + Clone + 'static> SelectorClone<T> for S {
+    fn clone_box(&self) -> Box<dyn Selector<T>> {
+        Box::new(self.clone())
+    }
+}
+
+impl<T: Phenotype> Clone for Box<dyn Selector<T>> {
+    fn clone(&self) -> Box<dyn Selector<T>> {
+        (**self).clone_box()
+    }
 }
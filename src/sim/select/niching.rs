@@ -0,0 +1,376 @@
+// file: niching.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use rand::Rng;
+use std::collections::HashMap;
+
+/// Let `selector` select from `population`, with its fitness values replaced by
+/// `fitnesses`. The parents refer to `population`.
+pub(super) fn select_derated<'a, T>(selector: &dyn Selector<T>,
+                                    population: &'a [Individual<T>],
+                                    fitnesses: Vec<f64>,
+                                    fitness_type: FitnessType,
+                                    rng: &mut dyn Rng)
+                                    -> Result<Parents<'a, T>, String>
+    where T: Phenotype<Fitness = f64>
+{
+    let derate = |(x, fitness): (&Individual<T>, f64)| {
+        let mut derated = Individual::with_fitness(x.phenotype().clone(), fitness);
+        derated.set_violation(x.violation());
+        derated
+    };
+    let derated: Vec<Individual<T>> = population.iter().zip(fitnesses).map(derate).collect();
+    let parents = selector.select(&derated, fitness_type, rng)?;
+    // Map the parents back to the population.
+    let indices: HashMap<*const T, usize> = derated.iter()
+                                                   .enumerate()
+                                                   .map(|(i, x)| (x.phenotype() as *const T, i))
+                                                   .collect();
+    let original = |x: &T| population[indices[&(x as *const T)]].phenotype();
+    Ok(parents.into_iter().map(|(x, y)| (original(x), original(y))).collect())
+}
+
+/// Check whether a niche radius is valid.
+fn validate_radius(radius: f64) -> Result<(), String> {
+    if radius.is_nan() || radius <= 0.0 {
+        return Err(format!("Invalid parameter `radius`: {}. Should be larger than zero.",
+                           radius));
+    }
+    Ok(())
+}
+
+/// Derates the fitness values of phenotypes in crowded niches, and then selects with
+/// another selector. This spreads the population over several optima of multimodal
+/// fitness functions, instead of letting it converge on a single one.
+///
+/// The sharing function of two phenotypes at distance `d` is `1 - (d / radius)^alpha` if
+/// `d` is less than `radius`, and zero otherwise. The niche count of a phenotype is the sum
+/// of its sharing functions with the whole population, including itself. Its fitness value
+/// is divided by its niche count when maximizing, and multiplied by it when minimizing.
+///
+/// Fitness values must not be negative.
+pub struct SharingSelector<T: Phenotype<Fitness = f64>> {
+    selector: Box<dyn Selector<T>>,
+    radius: f64,
+    alpha: f64,
+    distance: fn(&T, &T) -> f64,
+}
+
+impl<T: Phenotype<Fitness = f64>> SharingSelector<T> {
+    /// Create and return a fitness sharing selector.
+    ///
+    /// Such a selector derates the fitness values of the population, and lets `selector`
+    /// select from the derated population. The resulting number of parents is that of
+    /// `selector`.
+    ///
+    /// * `radius`: the sharing radius, must be larger than zero.
+    /// * `distance`: calculates the distance between two phenotypes.
+    pub fn new(selector: Box<dyn Selector<T>>,
+               radius: f64,
+               distance: fn(&T, &T) -> f64)
+               -> SharingSelector<T> {
+        SharingSelector {
+            selector,
+            radius,
+            alpha: 1.0,
+            distance,
+        }
+    }
+
+    /// Set the exponent of the sharing function. The default is 1.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn with_alpha(mut self, alpha: f64) -> SharingSelector<T> {
+        self.alpha = alpha;
+        self
+    }
+
+    /// Calculate the niche count of every phenotype in `population`.
+    fn niche_counts(&self, population: &[Individual<T>]) -> Vec<f64> {
+        let mut counts = vec![0.0; population.len()];
+        for i in 0..population.len() {
+            for j in i..population.len() {
+                let d = (self.distance)(population[i].phenotype(), population[j].phenotype());
+                if d < self.radius {
+                    let share = 1.0 - (d / self.radius).powf(self.alpha);
+                    counts[i] += share;
+                    if i != j {
+                        counts[j] += share;
+                    }
+                }
+            }
+        }
+        counts
+    }
+}
+
+impl<T: Phenotype<Fitness = f64>> Clone for SharingSelector<T> {
+    fn clone(&self) -> SharingSelector<T> {
+        SharingSelector {
+            selector: self.selector.clone(),
+            radius: self.radius,
+            alpha: self.alpha,
+            distance: self.distance,
+        }
+    }
+}
+
+impl<T: Phenotype<Fitness = f64> + 'static> Selector<T> for SharingSelector<T> {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let fitnesses = population.iter()
+                                  .zip(self.niche_counts(population))
+                                  .map(|(x, count)| match fitness_type {
+                                      FitnessType::Maximize => x.fitness() / count,
+                                      FitnessType::Minimize => x.fitness() * count,
+                                  })
+                                  .collect();
+        select_derated(&*self.selector, population, fitnesses, fitness_type, rng)
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        validate_radius(self.radius)?;
+        self.selector.validate(population_size)
+    }
+
+    fn set_parallel(&mut self) {
+        self.selector.set_parallel();
+    }
+
+    fn set_generation(&mut self, generation: u64) {
+        self.selector.set_generation(generation);
+    }
+}
+
+/// Clears the fitness values of all but the best phenotypes in every niche, and then
+/// selects with another selector. Like `SharingSelector`, this spreads the population over
+/// several optima, but it is simpler and often stronger.
+///
+/// Going from the best performing phenotype to the worst, every phenotype that has not
+/// been cleared yet starts a niche, containing all phenotypes within `radius` of it. Only
+/// the best `capacity` phenotypes of a niche keep their fitness values. The fitness values
+/// of the others are cleared: set to zero when maximizing, and to infinity when minimizing.
+///
+/// Fitness values must not be negative when maximizing.
+pub struct ClearingSelector<T: Phenotype<Fitness = f64>> {
+    selector: Box<dyn Selector<T>>,
+    radius: f64,
+    capacity: usize,
+    distance: fn(&T, &T) -> f64,
+}
+
+impl<T: Phenotype<Fitness = f64>> ClearingSelector<T> {
+    /// Create and return a clearing selector.
+    ///
+    /// Such a selector clears the fitness values of the population, and lets `selector`
+    /// select from the cleared population. The resulting number of parents is that of
+    /// `selector`.
+    ///
+    /// * `radius`: the niche radius, must be larger than zero.
+    /// * `capacity`: the number of phenotypes that keep their fitness value in every niche,
+    ///   must be larger than zero.
+    /// * `distance`: calculates the distance between two phenotypes.
+    pub fn new(selector: Box<dyn Selector<T>>,
+               radius: f64,
+               capacity: usize,
+               distance: fn(&T, &T) -> f64)
+               -> ClearingSelector<T> {
+        ClearingSelector {
+            selector,
+            radius,
+            capacity,
+            distance,
+        }
+    }
+
+    /// Find which phenotypes of `population` are cleared.
+    fn cleared(&self, population: &[Individual<T>], fitness_type: FitnessType) -> Vec<bool> {
+        let mut order: Vec<usize> = (0..population.len()).collect();
+        order.sort_by(|&x, &y| population[y].cmp_rank(&population[x], fitness_type));
+        let mut cleared = vec![false; population.len()];
+        for (n, &i) in order.iter().enumerate() {
+            if cleared[i] {
+                continue;
+            }
+            let mut winners = 1;
+            for &j in &order[n + 1..] {
+                if cleared[j] ||
+                   (self.distance)(population[i].phenotype(), population[j].phenotype()) >=
+                   self.radius {
+                    continue;
+                }
+                if winners < self.capacity {
+                    winners += 1;
+                } else {
+                    cleared[j] = true;
+                }
+            }
+        }
+        cleared
+    }
+}
+
+impl<T: Phenotype<Fitness = f64>> Clone for ClearingSelector<T> {
+    fn clone(&self) -> ClearingSelector<T> {
+        ClearingSelector {
+            selector: self.selector.clone(),
+            radius: self.radius,
+            capacity: self.capacity,
+            distance: self.distance,
+        }
+    }
+}
+
+impl<T: Phenotype<Fitness = f64> + 'static> Selector<T> for ClearingSelector<T> {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let cleared_fitness = match fitness_type {
+            FitnessType::Maximize => 0.0,
+            FitnessType::Minimize => f64::INFINITY,
+        };
+        let fitnesses = population.iter()
+                                  .zip(self.cleared(population, fitness_type))
+                                  .map(|(x, cleared)| {
+                                      if cleared { cleared_fitness } else { *x.fitness() }
+                                  })
+                                  .collect();
+        select_derated(&*self.selector, population, fitnesses, fitness_type, rng)
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        validate_radius(self.radius)?;
+        if self.capacity == 0 {
+            return Err(format!("Invalid parameter `capacity`: {}. Should be larger than zero.",
+                               self.capacity));
+        }
+        self.selector.validate(population_size)
+    }
+
+    fn set_parallel(&mut self) {
+        self.selector.set_parallel();
+    }
+
+    fn set_generation(&mut self, generation: u64) {
+        self.selector.set_generation(generation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+
+    #[derive(Clone)]
+    struct Test {
+        x: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        // Two peaks, at 10 and 90, of which the one at 90 is slightly higher.
+        fn fitness(&self) -> f64 {
+            let peak = |center: i64, height: f64| {
+                (height - (self.x - center).abs() as f64).max(0.0)
+            };
+            peak(10, 20.0).max(peak(90, 21.0))
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { x: (self.x + t.x) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn distance(x: &Test, y: &Test) -> f64 {
+        (x.x - y.x).abs() as f64
+    }
+
+    #[test]
+    fn test_radius_invalid() {
+        let selector = SharingSelector::new(Box::new(MaximizeSelector::new(10)), 0.0, distance);
+        let population: Vec<Individual<Test>> =
+            (0..100).map(|x| Individual::new(Test { x })).collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_crowded_niche() {
+        // Most of the population crowds the highest peak.
+        let population: Vec<Individual<Test>> = (0..20).map(|x| Test { x: 90 + x % 3 })
+                                                       .chain((0..2).map(|_| Test { x: 10 }))
+                                                       .map(Individual::new)
+                                                       .collect();
+        let mut rng = ::rand::thread_rng();
+        let parents = MaximizeSelector::new(2)
+                          .select(&population, FitnessType::Maximize, &mut rng)
+                          .unwrap();
+        assert!(parents.iter().all(|&(x, y)| x.x >= 90 && y.x >= 90));
+        let selector = SharingSelector::new(Box::new(MaximizeSelector::new(2)), 10.0, distance);
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        assert!(parents.iter().all(|&(x, y)| x.x == 10 && y.x == 10));
+        // The parents refer to the population itself.
+        assert!(::std::ptr::eq(parents[0].0, population[20].phenotype()) ||
+                ::std::ptr::eq(parents[0].0, population[21].phenotype()));
+    }
+
+    #[test]
+    fn test_capacity_invalid() {
+        let selector =
+            ClearingSelector::new(Box::new(MaximizeSelector::new(10)), 10.0, 0, distance);
+        let population: Vec<Individual<Test>> =
+            (0..100).map(|x| Individual::new(Test { x })).collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_clearing() {
+        let population: Vec<Individual<Test>> = (0..20).map(|x| Test { x: 90 + x % 3 })
+                                                       .chain((0..2).map(|_| Test { x: 11 }))
+                                                       .map(Individual::new)
+                                                       .collect();
+        let selector =
+            ClearingSelector::new(Box::new(MaximizeSelector::new(2)), 10.0, 1, distance);
+        let cleared = selector.cleared(&population, FitnessType::Maximize);
+        // Only the best phenotype of each peak keeps its fitness value.
+        assert_eq!(cleared.iter().filter(|&&x| !x).count(), 2);
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        assert_eq!(parents.len(), 1);
+        let (x, y) = parents[0];
+        let mut found = vec![x.x, y.x];
+        found.sort();
+        assert_eq!(found, vec![11, 90]);
+    }
+}
@@ -0,0 +1,155 @@
+// file: pareto.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use super::super::pareto::non_dominated_sort;
+use rand::Rng;
+
+/// Runs binary tournaments, which are won by the phenotype in the best non-dominated front.
+///
+/// This selector compares fitness values by Pareto dominance, so it is suited for
+/// multi-objective fitness values such as `Objectives`.
+#[derive(Clone, Copy, Debug)]
+pub struct ParetoSelector {
+    count: usize,
+}
+
+impl ParetoSelector {
+    /// Create and return a Pareto selector.
+    ///
+    /// Such a selector sorts the population into non-dominated fronts, and runs
+    /// `count` tournaments between two random phenotypes. The phenotype in the better
+    /// front wins, yielding `count` parents.
+    ///
+    /// * `count`: must be larger than zero, a multiple of two and less than the population size.
+    pub fn new(count: usize) -> ParetoSelector {
+        ParetoSelector { count }
+    }
+}
+
+impl<T: Phenotype> Selector<T> for ParetoSelector {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  mut rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let mut ranks = vec![0; population.len()];
+        for (rank, front) in non_dominated_sort(population, fitness_type).iter().enumerate() {
+            for &i in front {
+                ranks[i] = rank;
+            }
+        }
+        let mut tournament = || {
+            let x = Rng::gen_range(&mut rng, 0, population.len());
+            let y = Rng::gen_range(&mut rng, 0, population.len());
+            let winner = if ranks[y] < ranks[x] { y } else { x };
+            population[winner].phenotype()
+        };
+        let mut result: Parents<'a, T> = Vec::with_capacity(self.count / 2);
+        for _ in 0..(self.count / 2) {
+            result.push((tournament(), tournament()));
+        }
+        Ok(result)
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) || self.count >= population_size {
+            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero, a \
+                                multiple of two and less than the population size.",
+                               self.count));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+    use pheno::Objectives;
+
+    #[derive(Clone)]
+    struct Test {
+        cost: i64,
+        quality: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = Objectives;
+
+        fn fitness(&self) -> Objectives {
+            Objectives(vec![-self.cost as f64, self.quality as f64])
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test {
+                cost: (self.cost + t.cost) / 2,
+                quality: (self.quality + t.quality) / 2,
+            }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn population() -> Vec<Individual<Test>> {
+        (0..100).map(|i| Individual::new(Test { cost: i, quality: i % 10 })).collect()
+    }
+
+    #[test]
+    fn test_count_odd() {
+        let selector = ParetoSelector::new(5);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_count_too_large() {
+        let selector = ParetoSelector::new(100);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_result_size() {
+        let selector = ParetoSelector::new(20);
+        let population = population();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        assert_eq!(20, parents.len() * 2);
+    }
+
+    #[test]
+    fn test_simulation() {
+        let population: Vec<Test> = (0..100).map(|i| Test { cost: i, quality: i % 10 })
+                                            .collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(ParetoSelector::new(20)))
+                        .set_replacement(Box::new(replace::WorstReplacement::new()))
+                        .set_max_iters(20)
+                        .build().unwrap();
+        s.run();
+        // The front of the initial population, from cost 0 to 9, can only be improved on
+        // by children that are at least as cheap.
+        assert!(s.pareto_front().iter().all(|x| x.cost <= 9));
+    }
+}
@@ -0,0 +1,127 @@
+// file: random.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use rand::Rng;
+
+/// Selects pairs of different phenotypes uniformly at random, ignoring their fitness values.
+///
+/// As it applies no selection pressure at all, this selector is a baseline to check
+/// whether another selector helps on a problem.
+#[derive(Clone, Copy, Debug)]
+pub struct RandomSelector {
+    count: usize,
+}
+
+impl RandomSelector {
+    /// Create and return a random selector.
+    ///
+    /// This selector yields `count` parents.
+    ///
+    /// * `count`: must be larger than zero and a multiple of two.
+    pub fn new(count: usize) -> RandomSelector {
+        RandomSelector { count }
+    }
+}
+
+impl<T: Phenotype> Selector<T> for RandomSelector {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  _: FitnessType,
+                  mut rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let mut result: Parents<'a, T> = Vec::with_capacity(self.count / 2);
+        for _ in 0..(self.count / 2) {
+            let x = Rng::gen_range(&mut rng, 0, population.len());
+            // Any other phenotype.
+            let mut y = Rng::gen_range(&mut rng, 0, population.len() - 1);
+            if y >= x {
+                y += 1;
+            }
+            result.push((population[x].phenotype(), population[y].phenotype()));
+        }
+        Ok(result)
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) {
+            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero and \
+                                a multiple of two.",
+                               self.count));
+        }
+        if population_size < 2 {
+            return Err(String::from("Random selection requires at least two phenotypes."));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_count_odd() {
+        let selector = RandomSelector::new(5);
+        let population: Vec<Individual<Test>> = (0..10).map(|i| Individual::new(Test { f: i }))
+                                                       .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_uniform() {
+        let selector = RandomSelector::new(2000);
+        let population: Vec<Individual<Test>> = (0..4).map(|i| Individual::new(Test { f: i }))
+                                                      .collect();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        assert_eq!(1000, parents.len());
+        assert!(parents.iter().all(|&(x, y)| x.f != y.f));
+        // Every phenotype is selected about 500 times.
+        for f in 0..4 {
+            let count = parents.iter().filter(|&&(x, _)| x.f == f).count() +
+                        parents.iter().filter(|&&(_, y)| y.f == f).count();
+            assert!(count > 400 && count < 600);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+// file: rank.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use rand::Rng;
+use rand::distributions::{IndependentSample, Range};
+
+/// Select `count` parents from `population`, with a probability proportional to
+/// `weights[rank]`, where rank 0 is the worst performing phenotype.
+fn select_ranked<'a, T: Phenotype>(population: &'a [Individual<T>],
+                                   weights: &[f64],
+                                   count: usize,
+                                   fitness_type: FitnessType,
+                                   mut rng: &mut dyn Rng)
+                                   -> Parents<'a, T> {
+    let mut ranked: Vec<&Individual<T>> = population.iter().collect();
+    ranked.sort_by(|x, y| x.cmp_rank(y, fitness_type));
+    let cum_weights: Vec<f64> = weights.iter()
+                                       .scan(0.0, |state, &w| {
+                                           *state += w;
+                                           Some(*state)
+                                       })
+                                       .collect();
+    let between = Range::new(0.0, cum_weights[cum_weights.len() - 1]);
+    let mut spin = || {
+        let c = between.ind_sample(&mut rng);
+        let i = cum_weights.iter().position(|&w| w > c).unwrap_or(ranked.len() - 1);
+        ranked[i].phenotype()
+    };
+    let mut results: Parents<'a, T> = Vec::with_capacity(count / 2);
+    for _ in 0..(count / 2) {
+        results.push((spin(), spin()));
+    }
+    results
+}
+
+/// Check whether `count` parents can be selected from `population_size` phenotypes.
+fn validate_count(count: usize, population_size: usize) -> Result<(), String> {
+    if count == 0 || !count.is_multiple_of(2) || count >= population_size {
+        return Err(format!("Invalid parameter `count`: {}. Should be larger than zero, a \
+                            multiple of two and less than the population size.",
+                           count));
+    }
+    Ok(())
+}
+
+/// Selects phenotypes with a probability that increases linearly with their rank in the
+/// population.
+///
+/// Unlike `RouletteSelector`, only the order of the fitness values matters, so selection
+/// does not depend on how fitness values are scaled.
+#[derive(Clone, Copy, Debug)]
+pub struct LinearRankSelector {
+    count: usize,
+    pressure: f64,
+}
+
+impl LinearRankSelector {
+    /// Create and return a linear rank selector.
+    ///
+    /// Such a selector sorts the population, and selects the best performing phenotype
+    /// with `pressure` times the average probability, and the worst with `2 - pressure`
+    /// times the average probability. This selector yields `count` parents.
+    ///
+    /// * `count`: must be larger than zero, a multiple of two and less than the population size.
+    /// * `pressure`: the selection pressure, must be between 1 (uniform selection) and 2.
+    pub fn new(count: usize, pressure: f64) -> LinearRankSelector {
+        LinearRankSelector { count, pressure }
+    }
+}
+
+impl<T: Phenotype> Selector<T> for LinearRankSelector {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let n = population.len() as f64;
+        let weights: Vec<f64> =
+            (0..population.len())
+                .map(|i| 2.0 - self.pressure + 2.0 * (self.pressure - 1.0) * i as f64 / (n - 1.0))
+                .collect();
+        Ok(select_ranked(population, &weights, self.count, fitness_type, rng))
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        validate_count(self.count, population_size)?;
+        if self.pressure.is_nan() || self.pressure < 1.0 || self.pressure > 2.0 {
+            return Err(format!("Invalid parameter `pressure`: {}. Should be between 1 and 2.",
+                               self.pressure));
+        }
+        Ok(())
+    }
+}
+
+/// Selects phenotypes with a probability that decays geometrically with their rank in the
+/// population, starting from the best performing phenotype.
+///
+/// This puts more pressure towards the best phenotypes than `LinearRankSelector`.
+#[derive(Clone, Copy, Debug)]
+pub struct ExponentialRankSelector {
+    count: usize,
+    base: f64,
+}
+
+impl ExponentialRankSelector {
+    /// Create and return an exponential rank selector.
+    ///
+    /// Such a selector sorts the population, and selects every phenotype with `base`
+    /// times the probability of the next better one. This selector yields `count` parents.
+    ///
+    /// * `count`: must be larger than zero, a multiple of two and less than the population size.
+    /// * `base`: must be larger than zero and at most 1. Lower values put more pressure
+    ///   towards the best phenotypes.
+    pub fn new(count: usize, base: f64) -> ExponentialRankSelector {
+        ExponentialRankSelector { count, base }
+    }
+}
+
+impl<T: Phenotype> Selector<T> for ExponentialRankSelector {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let n = population.len();
+        let weights: Vec<f64> = (0..n).map(|i| self.base.powi((n - 1 - i) as i32)).collect();
+        Ok(select_ranked(population, &weights, self.count, fitness_type, rng))
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        validate_count(self.count, population_size)?;
+        if self.base.is_nan() || self.base <= 0.0 || self.base > 1.0 {
+            return Err(format!("Invalid parameter `base`: {}. Should be larger than zero and \
+                                at most 1.",
+                               self.base));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn population() -> Vec<Individual<Test>> {
+        (0..100).map(|i| Individual::new(Test { f: i * i * i })).collect()
+    }
+
+    /// Count how often each of 4 phenotypes is selected.
+    fn histogram(selector: &dyn Selector<Test>, fitness_type: FitnessType) -> Vec<usize> {
+        let population: Vec<Individual<Test>> =
+            (0..4).map(|i| Individual::new(Test { f: i * 1000 })).collect();
+        let mut rng = ::rand::thread_rng();
+        let mut counts = vec![0; 4];
+        for _ in 0..1000 {
+            for (x, y) in selector.select(&population, fitness_type, &mut rng).unwrap() {
+                counts[(x.f / 1000) as usize] += 1;
+                counts[(y.f / 1000) as usize] += 1;
+            }
+        }
+        counts
+    }
+
+    #[test]
+    fn test_linear_pressure_invalid() {
+        let selector = LinearRankSelector::new(20, 2.5);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_linear_count_too_large() {
+        let selector = LinearRankSelector::new(100, 1.5);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_linear_result_size() {
+        let selector = LinearRankSelector::new(20, 1.5);
+        let population = population();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        assert_eq!(20, parents.len() * 2);
+    }
+
+    #[test]
+    fn test_linear_probabilities() {
+        // With the maximum pressure, the weights of the ranks are 0, 2/3, 4/3 and 2.
+        let counts = histogram(&LinearRankSelector::new(2, 2.0), FitnessType::Maximize);
+        assert_eq!(counts[0], 0);
+        assert!(counts[3] > 900 && counts[3] < 1100);
+        assert!(counts[1] > 250 && counts[1] < 420);
+        let counts = histogram(&LinearRankSelector::new(2, 2.0), FitnessType::Minimize);
+        assert_eq!(counts[3], 0);
+        assert!(counts[0] > 900 && counts[0] < 1100);
+    }
+
+    #[test]
+    fn test_exponential_base_invalid() {
+        let selector = ExponentialRankSelector::new(20, 0.0);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_exponential_probabilities() {
+        // The weights of the ranks are 1/8, 1/4, 1/2 and 1, so the best phenotype is
+        // selected with probability 8/15.
+        let counts = histogram(&ExponentialRankSelector::new(2, 0.5), FitnessType::Maximize);
+        assert!(counts[3] > 960 && counts[3] < 1180);
+        assert!(counts[0] > 80 && counts[0] < 190);
+        let counts = histogram(&ExponentialRankSelector::new(2, 0.5), FitnessType::Minimize);
+        assert!(counts[0] > 960 && counts[0] < 1180);
+    }
+}
@@ -0,0 +1,159 @@
+// file: remainder.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use super::roulette::weights;
+use rand::Rng;
+use rand::distributions::{IndependentSample, Range};
+
+/// Selects every phenotype as often as its expected number of copies in fitness
+/// proportionate selection, rounded down, and fills the remaining places by roulette over
+/// the fractional parts.
+///
+/// Commonly known as *Stochastic Remainder Selection*. Fitness values are weighted like in
+/// `RouletteSelector`, but the number of times a phenotype is selected varies much less.
+/// The selected parents are paired up at random.
+#[derive(Clone, Copy, Debug)]
+pub struct RemainderSelector {
+    count: usize,
+}
+
+impl RemainderSelector {
+    /// Create and return a stochastic remainder selector.
+    ///
+    /// This selector yields `count` parents.
+    ///
+    /// * `count`: must be larger than zero, a multiple of two and less than the population size.
+    pub fn new(count: usize) -> RemainderSelector {
+        RemainderSelector { count }
+    }
+}
+
+impl<T: Phenotype> Selector<T> for RemainderSelector {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  mut rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let mut weights = weights(population, fitness_type);
+        let mut total: f64 = weights.iter().sum();
+        if total.is_nan() || total.is_infinite() {
+            return Err(String::from("Could not complete Stochastic Remainder Selection: \
+                                     fitness values must be finite."));
+        }
+        if total <= 0.0 {
+            weights = vec![1.0; population.len()];
+            total = population.len() as f64;
+        }
+
+        // The whole expected copies first, keeping the fractional parts.
+        let mut selected: Vec<usize> = Vec::with_capacity(self.count);
+        let mut fractions: Vec<f64> = Vec::with_capacity(population.len());
+        for (i, w) in weights.into_iter().enumerate() {
+            let expected = w * self.count as f64 / total;
+            let copies = (expected.floor() as usize).min(self.count - selected.len());
+            selected.extend((0..copies).map(|_| i));
+            fractions.push(expected - expected.floor());
+        }
+        let remainder: f64 = fractions.iter().sum();
+        while selected.len() < self.count {
+            let c = Range::new(0.0, remainder.max(f64::MIN_POSITIVE))
+                        .ind_sample(&mut rng);
+            let mut cumulative = 0.0;
+            let i = fractions.iter()
+                             .position(|&f| {
+                                 cumulative += f;
+                                 cumulative > c
+                             })
+                             .unwrap_or(population.len() - 1);
+            selected.push(i);
+        }
+
+        for i in (1..selected.len()).rev() {
+            let j = Rng::gen_range(&mut rng, 0, i + 1);
+            selected.swap(i, j);
+        }
+        Ok(selected.chunks(2)
+                   .map(|pair| (population[pair[0]].phenotype(), population[pair[1]].phenotype()))
+                   .collect())
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) || self.count >= population_size {
+            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero, a \
+                                multiple of two and less than the population size.",
+                               self.count));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_count_too_large() {
+        let selector = RemainderSelector::new(10);
+        let population: Vec<Individual<Test>> = (0..10).map(|i| Individual::new(Test { f: i }))
+                                                       .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_expected_copies() {
+        // The fitness values 0 to 99 sum to 4950, so the expected copies of every phenotype
+        // are its fitness value times 98 / 4950: at least one from 51 on.
+        let selector = RemainderSelector::new(98);
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                        .collect();
+        let mut rng = ::rand::thread_rng();
+        for _ in 0..100 {
+            let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+            assert_eq!(49, parents.len());
+            let selected: Vec<i64> = parents.iter().flat_map(|&(x, y)| vec![x.f, y.f]).collect();
+            assert!((51..100).all(|f| selected.contains(&f)));
+            assert!(!selected.contains(&0));
+        }
+    }
+}
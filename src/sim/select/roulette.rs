@@ -14,15 +14,23 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use pheno::Phenotype;
+use pheno::{Fitness, Phenotype};
 use super::*;
-use super::super::FitnessType;
+use super::super::{FitnessType, Individual};
+use rand::Rng;
 use rand::distributions::{IndependentSample, Range};
-use std::cmp::Ordering;
 
-/// Selects phenotypes with a probability based on their fitness value.
+/// Selects phenotypes with a probability proportional to their fitness value.
 ///
 /// Commonly known as *Roulette Wheel Selection*.
+///
+/// When maximizing, negative fitness values are shifted, so that the worst phenotype has
+/// weight zero. When minimizing, fitness values are inverted: the weight of a phenotype is
+/// the difference between the worst fitness value and its own. Infinitely bad fitness
+/// values, such as penalties for infeasible phenotypes, get weight zero and are left out
+/// when shifting or inverting. If all weights are zero, every phenotype is equally likely
+/// to be selected.
+#[derive(Clone, Copy, Debug)]
 pub struct RouletteSelector {
     count: usize,
 }
@@ -31,59 +39,79 @@ impl RouletteSelector {
     /// Create and return a roulette selector.
     ///
     /// Such a selector selects parents with a higher chance if those
-    /// phenotypes have high fitness values. This selector yields `count` parents.
+    /// phenotypes have better fitness values. This selector yields `count` parents.
     ///
     /// * `count`: must be larger than zero, a multiple of two and less than the population size.
     pub fn new(count: usize) -> RouletteSelector {
-        RouletteSelector { count: count }
+        RouletteSelector { count }
     }
 }
 
-impl<T: Phenotype> Selector<T> for RouletteSelector {
-    fn select(&self, population: &Vec<Box<T>>, _: FitnessType) -> Result<Parents<T>, String> {
-        if self.count <= 0 || self.count % 2 != 0 || self.count >= population.len() {
-            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero, a \
-                                multiple of two and less than the population size.",
-                               self.count));
-        }
-
-        let mut results: Parents<T> = Vec::new();
+/// Calculate the selection weight of every phenotype in `population`.
+pub(super) fn weights<T: Phenotype>(population: &[Individual<T>],
+                                    fitness_type: FitnessType)
+                                    -> Vec<f64> {
+    let fitnesses: Vec<f64> = population.iter().map(|x| x.fitness().to_f64()).collect();
+    let finite = || fitnesses.iter().cloned().filter(|f| f.is_finite());
+    let min = finite().fold(f64::INFINITY, f64::min);
+    let max = finite().fold(f64::NEG_INFINITY, f64::max);
+    fitnesses.iter()
+             .map(|&f| match fitness_type {
+                 FitnessType::Maximize if f == f64::NEG_INFINITY => 0.0,
+                 FitnessType::Minimize if f == f64::INFINITY => 0.0,
+                 FitnessType::Maximize if min < 0.0 => f - min,
+                 FitnessType::Maximize => f,
+                 FitnessType::Minimize => max - f,
+             })
+             .collect()
+}
 
-        let mut cloned = population.clone();
-        cloned.sort_by(|x, y| {
-            (*x).fitness().partial_cmp(&(*y).fitness()).unwrap_or(Ordering::Equal)
-        });
-        // Calculate cumulative fitness
-        let cum_fitness: Vec<_> = cloned.iter()
-                                        .scan(0.0, |state, ref x| {
-                                            *state = *state + x.fitness();
+impl<T: Phenotype> Selector<T> for RouletteSelector {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  mut rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        // Calculate cumulative weights
+        let cum_weights: Vec<f64> = weights(population, fitness_type)
+                                        .into_iter()
+                                        .scan(0.0, |state, w| {
+                                            *state += w;
                                             Some(*state)
                                         })
                                         .collect();
+        let total = cum_weights[cum_weights.len() - 1];
+        if total.is_nan() || total.is_infinite() {
+            return Err(String::from("Could not complete Roulette Selection: fitness values \
+                                     must be finite."));
+        }
 
-        let between = Range::new(cum_fitness[0], cum_fitness[cum_fitness.len() - 1]);
-        let mut rng = ::rand::thread_rng();
-
-        let mut selected = 0;
-        while selected < self.count {
-            let mut inner_selected: Vec<Box<T>> = Vec::with_capacity(2);
-            while inner_selected.len() < 2 {
-                let c = between.ind_sample(&mut rng);
-
-                let result = cloned.iter().find(|p| c >= p.fitness());
-                if result.is_none() {
-                    // This should never be true, but we wish to avoid panicking.
-                    return Err(format!("Could not complete Roulette Selection. This most likely \
-                                        indicates a bug in rsgenetic."));
-                }
-                inner_selected.push(result.unwrap().clone());
+        let mut spin = || {
+            if total <= 0.0 {
+                return population[Rng::gen_range(&mut rng, 0, population.len())].phenotype();
             }
-            results.push((inner_selected[0].clone(), inner_selected[1].clone()));
-
-            selected += 2;
+            let c = Range::new(0.0, total).ind_sample(&mut rng);
+            // The first phenotype of which the cumulative weight exceeds `c`.
+            let i = cum_weights.iter().position(|&w| w > c).unwrap_or(population.len() - 1);
+            population[i].phenotype()
+        };
+        let mut results: Parents<'a, T> = Vec::with_capacity(self.count / 2);
+        for _ in 0..(self.count / 2) {
+            results.push((spin(), spin()));
         }
         Ok(results)
     }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) || self.count >= population_size {
+            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero, a \
+                                multiple of two and less than the population size.",
+                               self.count));
+        }
+        Ok(())
+    }
 }
 
 
@@ -91,7 +119,6 @@ impl<T: Phenotype> Selector<T> for RouletteSelector {
 mod tests {
     use ::sim::*;
     use ::sim::select::*;
-    use ::pheno::*;
     use std::cmp;
 
     #[derive(Clone)]
@@ -100,8 +127,10 @@ mod tests {
     }
 
     impl Phenotype for Test {
+        type Fitness = f64;
+
         fn fitness(&self) -> f64 {
-            (self.f - 0).abs() as f64
+            self.f.abs() as f64
         }
 
         fn crossover(&self, t: &Test) -> Test {
@@ -122,29 +151,98 @@ mod tests {
     #[test]
     fn test_count_zero() {
         let selector = RouletteSelector::new(0);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert!(selector.select(&population, FitnessType::Minimize).is_err());
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
     }
 
     #[test]
     fn test_count_odd() {
         let selector = RouletteSelector::new(5);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert!(selector.select(&population, FitnessType::Minimize).is_err());
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
     }
 
     #[test]
     fn test_count_too_large() {
         let selector = RouletteSelector::new(100);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert!(selector.select(&population, FitnessType::Minimize).is_err());
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
     }
 
     #[test]
     fn test_result_size() {
         let selector = RouletteSelector::new(20);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert_eq!(20,
-                   selector.select(&population, FitnessType::Minimize).unwrap().len() * 2);
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Minimize, &mut rng).unwrap();
+        assert_eq!(20, parents.len() * 2);
+    }
+
+    /// Count how often the phenotype with `f` is selected from `fitnesses`.
+    fn count_selected(fitnesses: &[f64], fitness_type: FitnessType, f: i64) -> usize {
+        let population: Vec<Individual<Test>> =
+            fitnesses.iter()
+                     .enumerate()
+                     .map(|(i, &fitness)| Individual::with_fitness(Test { f: i as i64 }, fitness))
+                     .collect();
+        let selector = RouletteSelector::new(2);
+        let mut rng = ::rand::thread_rng();
+        (0..1000).map(|_| selector.select(&population, fitness_type, &mut rng).unwrap())
+                 .flat_map(|parents| vec![parents[0].0.f, parents[0].1.f])
+                 .filter(|&x| x == f)
+                 .count()
+    }
+
+    #[test]
+    fn test_maximize() {
+        // Weights 0, 1 and 9: the last phenotype should be selected 90% of the time.
+        let count = count_selected(&[0.0, 1.0, 9.0], FitnessType::Maximize, 2);
+        assert!(count > 1650 && count < 1950);
+        assert_eq!(count_selected(&[0.0, 1.0, 9.0], FitnessType::Maximize, 0), 0);
+    }
+
+    #[test]
+    fn test_negative_fitness() {
+        // Shifted to weights 0, 1 and 9.
+        let count = count_selected(&[-5.0, -4.0, 4.0], FitnessType::Maximize, 2);
+        assert!(count > 1650 && count < 1950);
+        assert_eq!(count_selected(&[-5.0, -4.0, 4.0], FitnessType::Maximize, 0), 0);
+    }
+
+    #[test]
+    fn test_minimize() {
+        // Inverted to weights 9, 8 and 0.
+        assert_eq!(count_selected(&[1.0, 2.0, 10.0], FitnessType::Minimize, 2), 0);
+        let count = count_selected(&[1.0, 2.0, 10.0], FitnessType::Minimize, 0);
+        assert!(count > 900 && count < 1200);
+    }
+
+    #[test]
+    fn test_infinite_fitness() {
+        // Infinitely bad phenotypes are never selected, and do not affect the others.
+        let count = count_selected(&[f64::NEG_INFINITY, 0.0, 1.0, 9.0], FitnessType::Maximize, 3);
+        assert!(count > 1650 && count < 1950);
+        assert_eq!(count_selected(&[f64::NEG_INFINITY, 0.0, 1.0, 9.0], FitnessType::Maximize, 0),
+                   0);
+        assert_eq!(count_selected(&[1.0, 2.0, 10.0, f64::INFINITY], FitnessType::Minimize, 3),
+                   0);
+        let count = count_selected(&[1.0, 2.0, 10.0, f64::INFINITY], FitnessType::Minimize, 0);
+        assert!(count > 900 && count < 1200);
+        // When every phenotype is infinitely bad, they are equally likely to be selected.
+        let count = count_selected(&[f64::INFINITY; 3], FitnessType::Minimize, 0);
+        assert!(count > 500 && count < 800);
+    }
+
+    #[test]
+    fn test_equal_fitness() {
+        let count = count_selected(&[3.0, 3.0, 3.0], FitnessType::Minimize, 0);
+        assert!(count > 500 && count < 800);
     }
 }
@@ -0,0 +1,321 @@
+// file: scaling.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use super::niching::select_derated;
+use rand::Rng;
+
+/// Calculate the mean and the standard deviation of `values`.
+fn mean_deviation(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n;
+    (mean, variance.sqrt())
+}
+
+/// Rescales fitness values by their standard deviation, and then selects with a fitness
+/// proportionate selector, such as `RouletteSelector`.
+///
+/// Commonly known as *Sigma Scaling*. The scaled fitness value is
+/// `1 + (fitness - mean) / (factor * deviation)`, or zero if that is negative, so the
+/// selection pressure stays about the same during a run: raw fitness values let a few
+/// outliers take over early on, and barely differ once the population has converged. If
+/// all fitness values are equal, every scaled fitness value is 1.
+///
+/// Scaled fitness values are always maximized, also when minimizing the raw fitness values.
+pub struct SigmaScalingSelector<T: Phenotype<Fitness = f64>> {
+    selector: Box<dyn Selector<T>>,
+    factor: f64,
+}
+
+impl<T: Phenotype<Fitness = f64>> SigmaScalingSelector<T> {
+    /// Create and return a sigma scaling selector.
+    ///
+    /// Such a selector scales the fitness values of the population, and lets `selector`
+    /// select from the scaled population. The resulting number of parents is that of
+    /// `selector`.
+    pub fn new(selector: Box<dyn Selector<T>>) -> SigmaScalingSelector<T> {
+        SigmaScalingSelector {
+            selector,
+            factor: 2.0,
+        }
+    }
+
+    /// Set the number of standard deviations below the mean at which the scaled fitness
+    /// value becomes zero. Must be larger than zero. The default is 2.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn with_factor(mut self, factor: f64) -> SigmaScalingSelector<T> {
+        self.factor = factor;
+        self
+    }
+}
+
+impl<T: Phenotype<Fitness = f64>> Clone for SigmaScalingSelector<T> {
+    fn clone(&self) -> SigmaScalingSelector<T> {
+        SigmaScalingSelector {
+            selector: self.selector.clone(),
+            factor: self.factor,
+        }
+    }
+}
+
+impl<T: Phenotype<Fitness = f64> + 'static> Selector<T> for SigmaScalingSelector<T> {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let fitnesses: Vec<f64> = population.iter()
+                                            .map(|x| match fitness_type {
+                                                FitnessType::Maximize => *x.fitness(),
+                                                FitnessType::Minimize => -*x.fitness(),
+                                            })
+                                            .collect();
+        let (mean, deviation) = mean_deviation(&fitnesses);
+        let scaled = fitnesses.into_iter()
+                              .map(|f| if deviation > 0.0 {
+                                  (1.0 + (f - mean) / (self.factor * deviation)).max(0.0)
+                              } else {
+                                  1.0
+                              })
+                              .collect();
+        select_derated(&*self.selector, population, scaled, FitnessType::Maximize, rng)
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.factor.is_nan() || self.factor <= 0.0 {
+            return Err(format!("Invalid parameter `factor`: {}. Should be larger than zero.",
+                               self.factor));
+        }
+        self.selector.validate(population_size)
+    }
+
+    fn set_parallel(&mut self) {
+        self.selector.set_parallel();
+    }
+
+    fn set_generation(&mut self, generation: u64) {
+        self.selector.set_generation(generation);
+    }
+}
+
+/// Rescales fitness values linearly, as `a * fitness + b`, and then selects with a fitness
+/// proportionate selector, such as `RouletteSelector`.
+///
+/// Commonly known as *Linear Scaling*. The scaled fitness values have the same mean as the
+/// raw ones, and the best one is `multiple` times the mean, so the best phenotype is
+/// expected to be selected `multiple` times as often as an average one. If that would make
+/// the worst scaled fitness value negative, it is zero instead, and the best one is as
+/// large as the mean allows.
+///
+/// Fitness values are shifted to start at zero if any of them is negative, and negated
+/// before that when minimizing. Scaled fitness values are always maximized.
+pub struct LinearScalingSelector<T: Phenotype<Fitness = f64>> {
+    selector: Box<dyn Selector<T>>,
+    multiple: f64,
+}
+
+impl<T: Phenotype<Fitness = f64>> LinearScalingSelector<T> {
+    /// Create and return a linear scaling selector.
+    ///
+    /// Such a selector scales the fitness values of the population, and lets `selector`
+    /// select from the scaled population. The resulting number of parents is that of
+    /// `selector`.
+    ///
+    /// * `multiple`: the expected number of times the best phenotype is selected, relative
+    ///   to an average one. Must be larger than 1, and is usually between 1.2 and 2.
+    pub fn new(selector: Box<dyn Selector<T>>, multiple: f64) -> LinearScalingSelector<T> {
+        LinearScalingSelector { selector, multiple }
+    }
+
+    /// Scale `fitnesses`, which are maximized and not negative.
+    fn scale(&self, fitnesses: Vec<f64>) -> Vec<f64> {
+        let n = fitnesses.len() as f64;
+        let mean = fitnesses.iter().sum::<f64>() / n;
+        let max = fitnesses.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let min = fitnesses.iter().cloned().fold(f64::INFINITY, f64::min);
+        if max <= mean {
+            // All fitness values are equal.
+            return vec![1.0; fitnesses.len()];
+        }
+        let (a, b) = if min > (self.multiple * mean - max) / (self.multiple - 1.0) {
+            ((self.multiple - 1.0) * mean / (max - mean),
+             mean * (max - self.multiple * mean) / (max - mean))
+        } else {
+            (mean / (mean - min), -min * mean / (mean - min))
+        };
+        fitnesses.into_iter().map(|f| (a * f + b).max(0.0)).collect()
+    }
+}
+
+impl<T: Phenotype<Fitness = f64>> Clone for LinearScalingSelector<T> {
+    fn clone(&self) -> LinearScalingSelector<T> {
+        LinearScalingSelector {
+            selector: self.selector.clone(),
+            multiple: self.multiple,
+        }
+    }
+}
+
+impl<T: Phenotype<Fitness = f64> + 'static> Selector<T> for LinearScalingSelector<T> {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let fitnesses: Vec<f64> = population.iter()
+                                            .map(|x| match fitness_type {
+                                                FitnessType::Maximize => *x.fitness(),
+                                                FitnessType::Minimize => -*x.fitness(),
+                                            })
+                                            .collect();
+        let min = fitnesses.iter().cloned().fold(f64::INFINITY, f64::min);
+        let shifted = if min < 0.0 {
+            fitnesses.into_iter().map(|f| f - min).collect()
+        } else {
+            fitnesses
+        };
+        let scaled = self.scale(shifted);
+        select_derated(&*self.selector, population, scaled, FitnessType::Maximize, rng)
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.multiple.is_nan() || self.multiple <= 1.0 {
+            return Err(format!("Invalid parameter `multiple`: {}. Should be larger than 1.",
+                               self.multiple));
+        }
+        self.selector.validate(population_size)
+    }
+
+    fn set_parallel(&mut self) {
+        self.selector.set_parallel();
+    }
+
+    fn set_generation(&mut self, generation: u64) {
+        self.selector.set_generation(generation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+
+    #[derive(Clone)]
+    struct Test {
+        f: f64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2.0 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    /// Count how often a phenotype with fitness value `f` is selected from `fitnesses`.
+    fn count_selected(selector: &dyn Selector<Test>,
+                      fitnesses: &[f64],
+                      fitness_type: FitnessType,
+                      f: f64)
+                      -> usize {
+        let population: Vec<Individual<Test>> =
+            fitnesses.iter().map(|&f| Individual::new(Test { f })).collect();
+        let mut rng = ::rand::thread_rng();
+        selector.select(&population, fitness_type, &mut rng)
+                .unwrap()
+                .into_iter()
+                .flat_map(|(x, y)| vec![x.f, y.f])
+                .filter(|&x| x == f)
+                .count()
+    }
+
+    #[test]
+    fn test_sigma_factor_invalid() {
+        let selector = SigmaScalingSelector::new(Box::new(RouletteSelector::new(2)))
+                           .with_factor(0.0);
+        let population: Vec<Individual<Test>> =
+            (0..10).map(|i| Individual::new(Test { f: i as f64 })).collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_sigma_scaling() {
+        // A converged population, in which raw fitness values barely differ. The mean is
+        // 1000.5 and the deviation 0.5, so the scaled fitness values are 0.5 and 1.5.
+        let fitnesses: Vec<f64> = (0..1000).map(|i| 1000.0 + (i % 2) as f64).collect();
+        let selector = SigmaScalingSelector::new(Box::new(RouletteSelector::new(998)));
+        let best = count_selected(&selector, &fitnesses, FitnessType::Maximize, 1001.0);
+        assert!(best > 680 && best < 800);
+        let best = count_selected(&selector, &fitnesses, FitnessType::Minimize, 1000.0);
+        assert!(best > 680 && best < 800);
+    }
+
+    #[test]
+    fn test_linear_multiple_invalid() {
+        let selector = LinearScalingSelector::new(Box::new(RouletteSelector::new(2)), 1.0);
+        let population: Vec<Individual<Test>> =
+            (0..10).map(|i| Individual::new(Test { f: i as f64 })).collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_linear_scale() {
+        let selector = LinearScalingSelector::<Test>::new(Box::new(RouletteSelector::new(2)),
+                                                          2.0);
+        // The mean stays 3, and the best becomes 6.
+        let scaled = selector.scale(vec![2.5, 2.5, 4.0]);
+        assert!((scaled.iter().sum::<f64>() / 3.0 - 3.0).abs() < 1e-9);
+        assert!((scaled[2] - 6.0).abs() < 1e-9);
+        // The mean is 4. Doubling it for the best would make the worst negative, so the worst
+        // becomes zero instead, and the best is less than double the mean.
+        let scaled = selector.scale(vec![1.0, 5.0, 5.0, 5.0]);
+        assert!((scaled[0] - 0.0).abs() < 1e-9);
+        assert!((scaled.iter().sum::<f64>() / 4.0 - 4.0).abs() < 1e-9);
+        assert!(scaled[1] < 8.0);
+        assert_eq!(selector.scale(vec![5.0, 5.0]), vec![1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_linear_scaling() {
+        // Raw fitness values that barely differ. Scaled, the best is selected twice as
+        // often as the average.
+        let fitnesses: Vec<f64> = (0..1000).map(|i| 1000.0 + (i % 2) as f64).collect();
+        let selector = LinearScalingSelector::new(Box::new(RouletteSelector::new(998)), 2.0);
+        let best = count_selected(&selector, &fitnesses, FitnessType::Maximize, 1001.0);
+        assert!(best > 940);
+        let best = count_selected(&selector, &fitnesses, FitnessType::Minimize, 1000.0);
+        assert!(best > 940);
+    }
+}
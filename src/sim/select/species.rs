@@ -0,0 +1,223 @@
+// file: species.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use super::super::species::{allocate_offspring, shared_fitness, speciate};
+use rand::Rng;
+use std::cmp::Ordering;
+
+/// Clusters the population into species, and selects parents within every species, as in
+/// NEAT. See the `sim::species` module.
+///
+/// Every species gets a number of parent pairs in proportion to the shared fitness values
+/// of its members, so a large species can not take over the population. Within a species,
+/// parents are selected by binary tournaments. Mating restricted to a species avoids
+/// crossing radically different phenotypes, which mostly yields poor children, but
+/// `with_interspecies_rate` allows some mating between species.
+///
+/// Fitness values must not be negative, and must be maximized.
+pub struct SpeciesSelector<T> {
+    count: usize,
+    threshold: f64,
+    interspecies: f64,
+    distance: fn(&T, &T) -> f64,
+}
+
+/// Run a binary tournament between random `members` of `population`.
+fn tournament<'a, T: Phenotype>(population: &'a [Individual<T>],
+                                members: &[usize],
+                                fitness_type: FitnessType,
+                                mut rng: &mut dyn Rng)
+                                -> &'a T {
+    let x = members[Rng::gen_range(&mut rng, 0, members.len())];
+    let y = members[Rng::gen_range(&mut rng, 0, members.len())];
+    let y_wins = population[y].cmp_rank(&population[x], fitness_type) == Ordering::Greater;
+    population[if y_wins { y } else { x }].phenotype()
+}
+
+impl<T> SpeciesSelector<T> {
+    /// Create and return a species selector.
+    ///
+    /// * `count`: must be larger than zero and a multiple of two.
+    /// * `threshold`: the compatibility threshold, must be larger than zero. Phenotypes that
+    ///   are closer than this belong to the same species.
+    /// * `distance`: calculates the compatibility distance between two phenotypes.
+    pub fn new(count: usize, threshold: f64, distance: fn(&T, &T) -> f64) -> SpeciesSelector<T> {
+        SpeciesSelector {
+            count,
+            threshold,
+            interspecies: 0.0,
+            distance,
+        }
+    }
+
+    /// Set the probability that the second parent of a pair is selected from another
+    /// species, if there is one. The default is 0, so parents are always selected from the
+    /// same species.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn with_interspecies_rate(mut self, rate: f64) -> SpeciesSelector<T> {
+        self.interspecies = rate;
+        self
+    }
+}
+
+impl<T> Clone for SpeciesSelector<T> {
+    fn clone(&self) -> SpeciesSelector<T> {
+        SpeciesSelector {
+            count: self.count,
+            threshold: self.threshold,
+            interspecies: self.interspecies,
+            distance: self.distance,
+        }
+    }
+}
+
+impl<T: Phenotype<Fitness = f64> + 'static> Selector<T> for SpeciesSelector<T> {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  mut rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+        if fitness_type == FitnessType::Minimize {
+            return Err(String::from("Species selection requires maximizing fitness values."));
+        }
+
+        let species = speciate(population, self.threshold, self.distance);
+        let shared = shared_fitness(population, &species);
+        let offspring = allocate_offspring(&species, &shared, self.count / 2);
+        let mut result: Parents<'a, T> = Vec::with_capacity(self.count / 2);
+        for (i, &pairs) in offspring.iter().enumerate() {
+            for _ in 0..pairs {
+                let x = tournament(population, &species[i].members, fitness_type, &mut *rng);
+                let mut other = i;
+                if species.len() > 1 && Rng::gen::<f64>(&mut rng) < self.interspecies {
+                    // Pick any species but this one.
+                    other = Rng::gen_range(&mut rng, 0, species.len() - 1);
+                    if other >= i {
+                        other += 1;
+                    }
+                }
+                let y = tournament(population, &species[other].members, fitness_type, &mut *rng);
+                result.push((x, y));
+            }
+        }
+        Ok(result)
+    }
+
+    fn validate(&self, _: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) {
+            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero and \
+                                a multiple of two.",
+                               self.count));
+        }
+        if self.threshold.is_nan() || self.threshold <= 0.0 {
+            return Err(format!("Invalid parameter `threshold`: {}. Should be larger than zero.",
+                               self.threshold));
+        }
+        if self.interspecies.is_nan() || self.interspecies < 0.0 || self.interspecies > 1.0 {
+            return Err(format!("Invalid parameter `interspecies`: {}. Should be between 0 and \
+                                1.",
+                               self.interspecies));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+
+    #[derive(Clone)]
+    struct Test {
+        x: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.x as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { x: (self.x + t.x) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn distance(x: &Test, y: &Test) -> f64 {
+        (x.x - y.x).abs() as f64
+    }
+
+    #[test]
+    fn test_minimize() {
+        let population: Vec<Individual<Test>> =
+            (0..10).map(|x| Individual::new(Test { x })).collect();
+        let selector = SpeciesSelector::new(10, 5.0, distance);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_count_odd() {
+        let population: Vec<Individual<Test>> =
+            (0..10).map(|x| Individual::new(Test { x })).collect();
+        let selector = SpeciesSelector::new(5, 5.0, distance);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_within_species() {
+        // A large species of weak phenotypes, and a small species of strong ones.
+        let population: Vec<Individual<Test>> = (0..18).map(|_| Test { x: 10 })
+                                                       .chain(vec![Test { x: 100 },
+                                                                   Test { x: 102 }])
+                                                       .map(Individual::new)
+                                                       .collect();
+        let selector = SpeciesSelector::new(40, 5.0, distance);
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        assert_eq!(parents.len(), 20);
+        // Parents are never taken from different species.
+        assert!(parents.iter().all(|&(x, y)| (x.x == 10) == (y.x == 10)));
+        // The shared fitness totals are 10 and 101.
+        assert_eq!(parents.iter().filter(|&&(x, _)| x.x == 10).count(), 2);
+    }
+
+    #[test]
+    fn test_interspecies_rate() {
+        let population: Vec<Individual<Test>> = (0..10).map(|_| Test { x: 10 })
+                                                       .chain((0..10).map(|_| Test { x: 100 }))
+                                                       .map(Individual::new)
+                                                       .collect();
+        let mut rng = ::rand::thread_rng();
+        let invalid = SpeciesSelector::new(40, 5.0, distance).with_interspecies_rate(1.5);
+        assert!(invalid.select(&population, FitnessType::Maximize, &mut rng).is_err());
+        let selector = SpeciesSelector::new(40, 5.0, distance).with_interspecies_rate(1.0);
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        // Every pair is taken from different species.
+        assert!(parents.iter().all(|&(x, y)| x.x != y.x));
+    }
+}
@@ -16,12 +16,13 @@
 
 use pheno::Phenotype;
 use super::*;
-use super::super::FitnessType;
+use super::super::{FitnessType, Individual};
 use rand::Rng;
 
 /// Selects phenotypes at random, starting from a random index and taking equidistant jumps.
 ///
 /// Commonly known as *Stochastic Universal Sampling*.
+#[derive(Clone, Copy, Debug)]
 pub struct StochasticSelector {
     count: usize,
 }
@@ -35,38 +36,46 @@ impl StochasticSelector {
     ///
     /// * `count`: must be larger than zero, a multiple of 2 and less than the population size.
     pub fn new(count: usize) -> StochasticSelector {
-        StochasticSelector { count: count }
+        StochasticSelector { count }
     }
 }
 
 impl<T: Phenotype> Selector<T> for StochasticSelector {
-    fn select(&self, population: &Vec<Box<T>>, _: FitnessType) -> Result<Parents<T>, String> {
-        if self.count <= 0 || self.count % 2 != 0 || self.count >= population.len() {
-            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero, a \
-                                multiple of two and less than the population size.",
-                               self.count));
-        }
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  _: FitnessType,
+                  mut rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
 
         let ratio = population.len() / self.count;
-        let mut result: Parents<T> = Vec::new();
-        let mut i = ::rand::thread_rng().gen_range::<usize>(0, population.len());
+        let mut result: Parents<'a, T> = Vec::new();
+        let mut i = Rng::gen_range(&mut rng, 0, population.len());
         let mut selected = 0;
         while selected < self.count {
-            result.push((population[i].clone(),
-                         population[(i + ratio - 1) % population.len()].clone()));
+            result.push((population[i].phenotype(),
+                         population[(i + ratio - 1) % population.len()].phenotype()));
             i += ratio - 1;
-            i = i % population.len();
+            i %= population.len();
             selected += 2;
         }
         Ok(result)
     }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) || self.count >= population_size {
+            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero, a \
+                                multiple of two and less than the population size.",
+                               self.count));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use ::sim::*;
     use ::sim::select::*;
-    use ::pheno::*;
     use std::cmp;
 
     #[derive(Clone)]
@@ -75,8 +84,10 @@ mod tests {
     }
 
     impl Phenotype for Test {
+        type Fitness = f64;
+
         fn fitness(&self) -> f64 {
-            (self.f - 0).abs() as f64
+            self.f.abs() as f64
         }
 
         fn crossover(&self, t: &Test) -> Test {
@@ -97,29 +108,37 @@ mod tests {
     #[test]
     fn test_count_zero() {
         let selector = StochasticSelector::new(0);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert!(selector.select(&population, FitnessType::Minimize).is_err());
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
     }
 
     #[test]
     fn test_count_odd() {
         let selector = StochasticSelector::new(5);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert!(selector.select(&population, FitnessType::Minimize).is_err());
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
     }
 
     #[test]
     fn test_count_too_large() {
         let selector = StochasticSelector::new(100);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert!(selector.select(&population, FitnessType::Minimize).is_err());
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
     }
 
     #[test]
     fn test_result_size() {
         let selector = StochasticSelector::new(20);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert_eq!(20,
-                   selector.select(&population, FitnessType::Minimize).unwrap().len() * 2);
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Minimize, &mut rng).unwrap();
+        assert_eq!(20, parents.len() * 2);
     }
 }
@@ -0,0 +1,127 @@
+// file: strength.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use super::super::pareto::strength_fitness;
+use rand::Rng;
+
+/// Runs binary tournaments on the strength fitness of SPEA2.
+///
+/// A tournament is won by the phenotype that is dominated by the fewest and weakest
+/// phenotypes, and then by the one with the fewest close neighbours in objective space.
+/// See `pareto::strength_fitness`.
+#[derive(Clone, Copy, Debug)]
+pub struct StrengthSelector {
+    count: usize,
+}
+
+impl StrengthSelector {
+    /// Create and return a strength selector.
+    ///
+    /// Such a selector runs `count` tournaments between two random phenotypes,
+    /// yielding `count` parents. Phenotypes are drawn with replacement, so `count`
+    /// may exceed the population size.
+    ///
+    /// * `count`: must be larger than zero and a multiple of two.
+    pub fn new(count: usize) -> StrengthSelector {
+        StrengthSelector { count }
+    }
+}
+
+impl<T: Phenotype> Selector<T> for StrengthSelector {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  mut rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let fitnesses = strength_fitness(population, fitness_type);
+        let mut tournament = || {
+            let x = Rng::gen_range(&mut rng, 0, population.len());
+            let y = Rng::gen_range(&mut rng, 0, population.len());
+            population[if fitnesses[y] < fitnesses[x] { y } else { x }].phenotype()
+        };
+        let mut result: Parents<'a, T> = Vec::with_capacity(self.count / 2);
+        for _ in 0..(self.count / 2) {
+            result.push((tournament(), tournament()));
+        }
+        Ok(result)
+    }
+
+    fn validate(&self, _population_size: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) {
+            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero and \
+                                a multiple of two.",
+                               self.count));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+    use pheno::Objectives;
+
+    #[derive(Clone)]
+    struct Test {
+        cost: i64,
+        quality: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = Objectives;
+
+        fn fitness(&self) -> Objectives {
+            Objectives(vec![-self.cost as f64, self.quality as f64])
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test {
+                cost: (self.cost + t.cost) / 2,
+                quality: (self.quality + t.quality) / 2,
+            }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn population() -> Vec<Individual<Test>> {
+        (0..100).map(|i| Individual::new(Test { cost: i, quality: i % 10 })).collect()
+    }
+
+    #[test]
+    fn test_count_odd() {
+        let selector = StrengthSelector::new(5);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_result_size() {
+        let selector = StrengthSelector::new(200);
+        let population = population();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        assert_eq!(200, parents.len() * 2);
+    }
+}
@@ -16,14 +16,35 @@
 
 use pheno::Phenotype;
 use super::*;
-use super::super::FitnessType;
-use std::cmp::Ordering;
+use super::super::{FitnessType, Individual};
 use rand::Rng;
+use std::cmp::Ordering;
+use std::collections::HashSet;
 
 /// Runs several tournaments, and selects best performing phenotypes from each tournament.
+///
+/// With `with_probability`, tournaments are won by weaker participants as well, which
+/// lowers the selection pressure. With `with_separate_tournaments`, every parent wins a
+/// tournament of its own. With `in_parallel`, the tournaments run on multiple threads.
+#[derive(Clone, Copy, Debug)]
 pub struct TournamentSelector {
     count: usize,
     participants: usize,
+    probability: f64,
+    replacement: bool,
+    separate: bool,
+    parallel: bool,
+}
+
+/// Pick a participant from `tournament`, indices in the population sorted best first, and
+/// remove it. The best one is picked with probability `p`, the second best with
+/// `p * (1 - p)`, and so on; the last one takes the remaining probability.
+fn pick(tournament: &mut Vec<usize>, p: f64, mut rng: &mut dyn Rng) -> usize {
+    let mut i = 0;
+    while i + 1 < tournament.len() && Rng::gen::<f64>(&mut rng) >= p {
+        i += 1;
+    }
+    tournament.remove(i)
 }
 
 impl TournamentSelector {
@@ -34,53 +55,170 @@ impl TournamentSelector {
     /// `count` parents.
     ///
     /// * `count`: must be larger than zero, a multiple of two and less than the population size.
-    /// * `participants`: must be larger than zero and less than the population size.
+    /// * `participants`: must be at least two and less than the population size.
     pub fn new(count: usize, participants: usize) -> TournamentSelector {
         TournamentSelector {
-            count: count,
-            participants: participants,
+            count,
+            participants,
+            probability: 1.0,
+            replacement: true,
+            separate: false,
+            parallel: false,
+        }
+    }
+
+    /// Set the probability that the best participant of a tournament wins. Otherwise, the
+    /// second best wins with the same probability, and so on. This applies to both parents
+    /// of a tournament. The default is 1, so the best two participants are always selected.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn with_probability(mut self, probability: f64) -> TournamentSelector {
+        self.probability = probability;
+        self
+    }
+
+    /// Draw the participants of every tournament without replacement, so no phenotype
+    /// participates twice in the same tournament, and the two parents of a pair always
+    /// differ. By default participants are drawn with replacement.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn without_replacement(mut self) -> TournamentSelector {
+        self.replacement = false;
+        self
+    }
+
+    /// Run a separate tournament for every parent, instead of mating the best two
+    /// participants of a single tournament, so the parents of a pair come from different
+    /// samples of the population. This runs `count` tournaments. Without replacement, the
+    /// first parent does not participate in the tournament of the second one.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn with_separate_tournaments(mut self) -> TournamentSelector {
+        self.separate = true;
+        self
+    }
+
+    /// Run the tournaments on multiple threads, using `rayon`. This pays off when
+    /// selecting thousands of parents. Every tournament gets its own random number
+    /// generator, seeded from the one of the `Simulation`, so seeded runs stay
+    /// reproducible, but select different parents than when running sequentially.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn in_parallel(mut self) -> TournamentSelector {
+        self.parallel = true;
+        self
+    }
+
+    /// Draw the participants of a tournament from a population of `size`, except
+    /// `excluded` when drawing without replacement, and sort them best first by `compare`.
+    fn tournament<F>(&self,
+                     size: usize,
+                     excluded: Option<usize>,
+                     compare: &F,
+                     mut rng: &mut dyn Rng)
+                     -> Vec<usize>
+        where F: Fn(usize, usize) -> Ordering
+    {
+        let mut tournament: Vec<usize> = Vec::with_capacity(self.participants);
+        if self.replacement {
+            for _ in 0..self.participants {
+                tournament.push(Rng::gen_range(&mut rng, 0, size));
+            }
+        } else if 2 * self.participants <= size {
+            // Redraw participants that were drawn before, which takes time in the order of
+            // the tournament size, instead of the population size.
+            let mut drawn: HashSet<usize> = HashSet::with_capacity(self.participants + 1);
+            drawn.extend(excluded);
+            while tournament.len() < self.participants {
+                let i = Rng::gen_range(&mut rng, 0, size);
+                if drawn.insert(i) {
+                    tournament.push(i);
+                }
+            }
+        } else {
+            // The tournament takes up most of the population, so shuffle all of it.
+            let mut indices: Vec<usize> = (0..size).collect();
+            if let Some(excluded) = excluded {
+                indices.swap_remove(excluded);
+            }
+            // Partial Fisher-Yates shuffle of the indices.
+            for i in 0..self.participants {
+                let j = Rng::gen_range(&mut rng, i, indices.len());
+                indices.swap(i, j);
+                tournament.push(indices[i]);
+            }
+        }
+        tournament.sort_by(|&x, &y| compare(x, y));
+        tournament
+    }
+
+    /// Select the indices of a pair of parents from a population of `size`, ordered by
+    /// `compare`.
+    fn pair<F>(&self, size: usize, compare: &F, rng: &mut dyn Rng) -> (usize, usize)
+        where F: Fn(usize, usize) -> Ordering
+    {
+        let mut tournament = self.tournament(size, None, compare, rng);
+        let x = pick(&mut tournament, self.probability, rng);
+        if self.separate {
+            tournament = self.tournament(size, Some(x), compare, rng);
         }
+        let y = pick(&mut tournament, self.probability, rng);
+        (x, y)
     }
 }
 
 impl<T: Phenotype> Selector<T> for TournamentSelector {
-    fn select(&self,
-              population: &Vec<Box<T>>,
-              fitness_type: FitnessType)
-              -> Result<Parents<T>, String> {
-        if self.count <= 0 || self.count % 2 != 0 || self.count * 2 >= population.len() {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let size = population.len();
+        let pairs: Vec<(usize, usize)> = if self.parallel {
+            // Rank the population first, so the threads do not need to share it.
+            let mut order: Vec<usize> = (0..size).collect();
+            order.sort_by(|&x, &y| population[y].cmp_rank(&population[x], fitness_type));
+            let mut ranks = vec![0; size];
+            for i in 1..size {
+                let tie = population[order[i]].cmp_rank(&population[order[i - 1]],
+                                                         fitness_type) ==
+                          Ordering::Equal;
+                ranks[order[i]] = if tie { ranks[order[i - 1]] } else { i };
+            }
+            let compare = |x: usize, y: usize| ranks[x].cmp(&ranks[y]);
+            par_pairs(self.count / 2, rng, |rng| self.pair(size, &compare, rng))
+        } else {
+            let compare = |x: usize, y: usize| population[y].cmp_rank(&population[x], fitness_type);
+            (0..(self.count / 2)).map(|_| self.pair(size, &compare, rng)).collect()
+        };
+        Ok(pairs.into_iter()
+                .map(|(x, y)| (population[x].phenotype(), population[y].phenotype()))
+                .collect())
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) || self.count * 2 >= population_size {
             return Err(format!("Invalid parameter `count`: {}. Should be larger than zero, a \
                                 multiple of two and less than half the population size.",
                                self.count));
         }
-        if self.participants <= 0 || self.participants >= population.len() {
-            return Err(format!("Invalid parameter `participants`: {}. Should be larger than \
-                                zero and less than the population size.",
+        if self.participants < 2 || self.participants >= population_size {
+            return Err(format!("Invalid parameter `participants`: {}. Should be at least two \
+                                and less than the population size.",
                                self.participants));
         }
-
-        let mut result: Parents<T> = Vec::new();
-        let mut rng = ::rand::thread_rng();
-        for _ in 0..(self.count / 2) {
-            let mut tournament: Vec<Box<T>> = Vec::with_capacity(self.participants);
-            for _ in 0..self.participants {
-                let index = rng.gen_range::<usize>(0, population.len());
-                tournament.push(population[index].clone());
-            }
-            tournament.sort_by(|x, y| {
-                (*x).fitness().partial_cmp(&(*y).fitness()).unwrap_or(Ordering::Equal)
-            });
-            match fitness_type {
-                FitnessType::Maximize => {
-                    result.push((tournament[tournament.len() - 1].clone(),
-                                 tournament[tournament.len() - 2].clone()));
-                }
-                FitnessType::Minimize => {
-                    result.push((tournament[0].clone(), tournament[1].clone()));
-                }
-            }
+        if self.probability.is_nan() || self.probability <= 0.0 || self.probability > 1.0 {
+            return Err(format!("Invalid parameter `probability`: {}. Should be larger than \
+                                zero and at most 1.",
+                               self.probability));
         }
-        Ok(result)
+        Ok(())
+    }
+
+    fn set_parallel(&mut self) {
+        self.parallel = true;
     }
 }
 
@@ -88,7 +226,7 @@ impl<T: Phenotype> Selector<T> for TournamentSelector {
 mod tests {
     use ::sim::*;
     use ::sim::select::*;
-    use ::pheno::*;
+    use rand::{SeedableRng, XorShiftRng};
     use std::cmp;
 
     #[derive(Clone)]
@@ -97,8 +235,10 @@ mod tests {
     }
 
     impl Phenotype for Test {
+        type Fitness = f64;
+
         fn fitness(&self) -> f64 {
-            (self.f - 0).abs() as f64
+            self.f.abs() as f64
         }
 
         fn crossover(&self, t: &Test) -> Test {
@@ -119,43 +259,146 @@ mod tests {
     #[test]
     fn test_count_zero() {
         let selector = TournamentSelector::new(0, 1);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert!(selector.select(&population, FitnessType::Minimize).is_err());
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
     }
 
     #[test]
     fn test_participants_zero() {
         let selector = TournamentSelector::new(2, 0);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert!(selector.select(&population, FitnessType::Minimize).is_err());
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
     }
 
     #[test]
     fn test_count_odd() {
         let selector = TournamentSelector::new(5, 1);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert!(selector.select(&population, FitnessType::Minimize).is_err());
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
     }
 
     #[test]
     fn test_count_too_large() {
         let selector = TournamentSelector::new(100, 1);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert!(selector.select(&population, FitnessType::Minimize).is_err());
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
     }
 
     #[test]
     fn test_participants_too_large() {
         let selector = TournamentSelector::new(2, 100);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert!(selector.select(&population, FitnessType::Minimize).is_err());
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_tournament_without_replacement() {
+        let compare = |x: usize, y: usize| x.cmp(&y);
+        let mut rng = ::rand::thread_rng();
+        // Both small and large tournaments, relative to the population.
+        for &participants in &[20, 900] {
+            let selector = TournamentSelector::new(2, participants).without_replacement();
+            let tournament = selector.tournament(1000, Some(3), &compare, &mut rng);
+            assert_eq!(tournament.len(), participants);
+            assert!(tournament.windows(2).all(|x| x[0] < x[1]));
+            assert!(tournament.iter().all(|&x| x != 3 && x < 1000));
+        }
     }
 
     #[test]
     fn test_result_size() {
         let selector = TournamentSelector::new(20, 5);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        assert_eq!(20,
-                   selector.select(&population, FitnessType::Minimize).unwrap().len() * 2);
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Minimize, &mut rng).unwrap();
+        assert_eq!(20, parents.len() * 2);
+    }
+
+    #[test]
+    fn test_probability_invalid() {
+        let selector = TournamentSelector::new(2, 5).with_probability(0.0);
+        let population: Vec<Individual<Test>> = (0..100).map(|i| Individual::new(Test { f: i }))
+                                                     .collect();
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population, FitnessType::Minimize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_probability() {
+        let population: Vec<Individual<Test>> = (0..10).map(|i| Individual::new(Test { f: i }))
+                                                      .collect();
+        let mut rng = ::rand::thread_rng();
+        // Two participants, so the best one is the first parent in about half of the
+        // tournaments, unless both participants are the same phenotype.
+        let selector = TournamentSelector::new(2, 2).with_probability(0.5);
+        let wins = (0..1000).map(|_| selector.select(&population, FitnessType::Maximize, &mut rng)
+                                             .unwrap()[0])
+                            .filter(|&(x, y)| x.f > y.f)
+                            .count();
+        assert!(wins > 350 && wins < 650);
+        let selector = TournamentSelector::new(2, 2);
+        let parents = (0..100).map(|_| selector.select(&population, FitnessType::Maximize, &mut rng)
+                                               .unwrap()[0]);
+        assert!(parents.into_iter().all(|(x, y)| x.f >= y.f));
+    }
+
+    #[test]
+    fn test_without_replacement() {
+        let population: Vec<Individual<Test>> = (0..10).map(|i| Individual::new(Test { f: i }))
+                                                      .collect();
+        let mut rng = ::rand::thread_rng();
+        let selector = TournamentSelector::new(4, 9).without_replacement();
+        for _ in 0..100 {
+            let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+            // Nine of ten phenotypes participate, so only one of the best two can be missing.
+            assert!(parents.iter().all(|&(x, y)| x.f >= 8 && y.f >= 7 && x.f != y.f));
+        }
+    }
+
+    #[test]
+    fn test_separate_tournaments() {
+        let population: Vec<Individual<Test>> = (0..10).map(|i| Individual::new(Test { f: i }))
+                                                      .collect();
+        let mut rng = ::rand::thread_rng();
+        // Both parents win a tournament of nine, so both are 9 unless they must differ.
+        let selector = TournamentSelector::new(4, 9).with_separate_tournaments();
+        let parents = (0..100).map(|_| selector.select(&population, FitnessType::Maximize, &mut rng)
+                                               .unwrap()[0]);
+        assert!(parents.into_iter().any(|(x, y)| x.f == 9 && y.f == 9));
+        let selector = selector.without_replacement();
+        for _ in 0..100 {
+            let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+            assert!(parents.iter().all(|&(x, y)| x.f >= 8 && y.f >= 8 && x.f != y.f));
+        }
+    }
+
+    #[test]
+    fn test_in_parallel() {
+        // Ties between phenotypes with the same fitness value.
+        let population: Vec<Individual<Test>> =
+            (0..1000).map(|i| Individual::new(Test { f: i / 2 })).collect();
+        let selector = TournamentSelector::new(400, 20).without_replacement().in_parallel();
+        let select = |seed: u32| {
+            let mut rng: XorShiftRng = SeedableRng::from_seed([seed, 2, 3, 4]);
+            selector.select(&population, FitnessType::Maximize, &mut rng).unwrap()
+        };
+        let parents = select(1);
+        assert_eq!(200, parents.len());
+        assert!(parents.iter().all(|&(x, y)| x.f >= y.f));
+        // Seeded selection does not depend on the threads.
+        let again = select(1);
+        assert!(parents.iter().zip(&again).all(|(x, y)| ::std::ptr::eq(x.0, y.0) &&
+                                                         ::std::ptr::eq(x.1, y.1)));
     }
 }
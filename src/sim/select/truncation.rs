@@ -0,0 +1,187 @@
+// file: truncation.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::*;
+use super::super::{FitnessType, Individual};
+use rand::Rng;
+
+/// Selects parents at random from the best performing fraction of the population.
+///
+/// Commonly known as *Truncation Selection*. The best performing phenotypes are those with
+/// the highest fitness values when maximizing, and the lowest when minimizing. With
+/// `in_parallel`, the pairs are drawn on multiple threads.
+#[derive(Clone, Copy, Debug)]
+pub struct TruncationSelector {
+    count: usize,
+    fraction: f64,
+    parallel: bool,
+}
+
+/// Draw a pair of different indices below `kept`.
+fn draw(kept: usize, mut rng: &mut dyn Rng) -> (usize, usize) {
+    let x = Rng::gen_range(&mut rng, 0, kept);
+    // Any other phenotype that was kept.
+    let mut y = Rng::gen_range(&mut rng, 0, kept - 1);
+    if y >= x {
+        y += 1;
+    }
+    (x, y)
+}
+
+impl TruncationSelector {
+    /// Create and return a truncation selector.
+    ///
+    /// Such a selector keeps the best `fraction` of the population, and mates random pairs
+    /// of different phenotypes from it. This selector yields `count` parents.
+    ///
+    /// * `count`: must be larger than zero and a multiple of two.
+    /// * `fraction`: must be larger than zero and at most 1, and keep at least two
+    ///   phenotypes.
+    pub fn new(count: usize, fraction: f64) -> TruncationSelector {
+        TruncationSelector {
+            count,
+            fraction,
+            parallel: false,
+        }
+    }
+
+    /// Draw the pairs on multiple threads, using `rayon`. This pays off when selecting
+    /// thousands of parents. Every pair gets its own random number generator, seeded from
+    /// the one of the `Simulation`, so seeded runs stay reproducible, but select different
+    /// parents than when running sequentially.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn in_parallel(mut self) -> TruncationSelector {
+        self.parallel = true;
+        self
+    }
+
+    /// The number of phenotypes that are kept from a population of `population_size`.
+    fn kept(&self, population_size: usize) -> usize {
+        (self.fraction * population_size as f64).ceil() as usize
+    }
+}
+
+impl<T: Phenotype> Selector<T> for TruncationSelector {
+    fn select<'a>(&self,
+                  population: &'a [Individual<T>],
+                  fitness_type: FitnessType,
+                  rng: &mut dyn Rng)
+                  -> Result<Parents<'a, T>, String> {
+        Selector::<T>::validate(self, population.len())?;
+
+        let kept = self.kept(population.len()).min(population.len());
+        let mut sorted: Vec<&Individual<T>> = population.iter().collect();
+        let compare = |x: &&Individual<T>, y: &&Individual<T>| y.cmp_rank(x, fitness_type);
+        sorted.select_nth_unstable_by(kept - 1, compare);
+        sorted.truncate(kept);
+        let pairs: Vec<(usize, usize)> = if self.parallel {
+            par_pairs(self.count / 2, rng, |rng| draw(kept, rng))
+        } else {
+            (0..(self.count / 2)).map(|_| draw(kept, rng)).collect()
+        };
+        Ok(pairs.into_iter()
+                .map(|(x, y)| (sorted[x].phenotype(), sorted[y].phenotype()))
+                .collect())
+    }
+
+    fn validate(&self, population_size: usize) -> Result<(), String> {
+        if self.count == 0 || !self.count.is_multiple_of(2) {
+            return Err(format!("Invalid parameter `count`: {}. Should be larger than zero and \
+                                a multiple of two.",
+                               self.count));
+        }
+        if self.fraction.is_nan() || self.fraction <= 0.0 || self.fraction > 1.0 ||
+           self.kept(population_size) < 2 {
+            return Err(format!("Invalid parameter `fraction`: {}. Should be larger than zero, \
+                                at most 1 and keep at least two phenotypes.",
+                               self.fraction));
+        }
+        Ok(())
+    }
+
+    fn set_parallel(&mut self) {
+        self.parallel = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::select::*;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn population() -> Vec<Individual<Test>> {
+        (0..100).map(|i| Individual::new(Test { f: i })).collect()
+    }
+
+    #[test]
+    fn test_fraction_too_small() {
+        let selector = TruncationSelector::new(20, 0.01);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_count_odd() {
+        let selector = TruncationSelector::new(5, 0.5);
+        let mut rng = ::rand::thread_rng();
+        assert!(selector.select(&population(), FitnessType::Maximize, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_fitness_type() {
+        let selector = TruncationSelector::new(200, 0.1);
+        let population = population();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        assert_eq!(100, parents.len());
+        assert!(parents.iter().all(|&(x, y)| x.f >= 90 && y.f >= 90 && x.f != y.f));
+        let parents = selector.select(&population, FitnessType::Minimize, &mut rng).unwrap();
+        assert!(parents.iter().all(|&(x, y)| x.f < 10 && y.f < 10 && x.f != y.f));
+    }
+
+    #[test]
+    fn test_in_parallel() {
+        let selector = TruncationSelector::new(2000, 0.1).in_parallel();
+        let population = population();
+        let mut rng = ::rand::thread_rng();
+        let parents = selector.select(&population, FitnessType::Maximize, &mut rng).unwrap();
+        assert_eq!(1000, parents.len());
+        assert!(parents.iter().all(|&(x, y)| x.f >= 90 && y.f >= 90 && x.f != y.f));
+    }
+}
@@ -20,102 +20,482 @@
 //! To use a `Simulator`, you need a `SimulatorBuilder`, which you can
 //! obtain by calling `Simulator::builder()`.
 
-use pheno::Phenotype;
-use std::cmp::Ordering;
-use rand::Rng;
+use pheno::{Constrained, Distance, Fitness, FitnessFunction, Phenotype};
+use rand::{Isaac64Rng, Rng, SeedableRng, ThreadRng};
+use std::cell::RefCell;
+use std::cmp;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::time::Duration;
 use super::*;
 use super::select::*;
+use super::replace::*;
+use super::eval::*;
+use super::stop::StoppingCondition;
 use super::iterlimit::*;
 use super::earlystopper::*;
+use super::hall_of_fame::HallOfFame;
+use super::pareto::Decomposition;
+use super::chc::Chc;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "csv")]
+use std::io::Write;
+#[cfg(any(feature = "serde", feature = "plotting"))]
+use std::path::Path;
 use time::SteadyTime;
 
+/// Evaluate `$body` inside a `tracing` span called `$name`, if the `tracing` feature is enabled.
+macro_rules! phase {
+    ($name:expr, $body:expr) => {{
+        #[cfg(feature = "tracing")]
+        let _span = ::tracing::debug_span!($name).entered();
+        $body
+    }};
+}
+
+/// Calculates the fitness values of new phenotypes using a `FitnessFunction`.
+type Evaluate<T> = fn(Vec<T>, &dyn FitnessFunction<T>) -> Vec<Individual<T>>;
+/// Called after every step.
+type StepCallback<T> = Box<dyn FnMut(&StepInfo<T>)>;
+/// Fixes infeasible children before they are evaluated. Shared with forks.
+type Repair<T> = Rc<RefCell<dyn FnMut(&mut T)>>;
+
+/// Creates a new random phenotype. Shared with forks.
+type Generator<T> = Rc<RefCell<dyn FnMut(&mut dyn Rng) -> T>>;
+
+/// Settings for restarting when the population stagnates.
+#[derive(Clone)]
+struct Restart<T> {
+    /// The number of best performing phenotypes that are kept.
+    keep: usize,
+    /// Creates the phenotypes that replace the rest of the population.
+    generator: Generator<T>,
+}
+
+/// Settings for adding new random phenotypes to the population in every step.
+#[derive(Clone)]
+struct Immigrants<T> {
+    /// The fraction of the population that is replaced.
+    fraction: f64,
+    /// Creates the new phenotypes.
+    generator: Generator<T>,
+}
+
+/// Settings for replacing children that duplicate other phenotypes.
+#[derive(Clone)]
+struct Duplicates<T> {
+    /// Returns whether two phenotypes are the same.
+    same: fn(&T, &T) -> bool,
+    /// Creates the replacements, or `None` to mutate the duplicates instead.
+    generator: Option<Generator<T>>,
+}
+
+/// Settings for measuring the diversity of the population.
+#[derive(Clone)]
+struct Diversity<T> {
+    /// Calculates the distance between two phenotypes.
+    distance: fn(&T, &T) -> f64,
+    /// The number of random pairs to measure, or `None` to measure every pair.
+    sample: Option<usize>,
+}
+
+/// Settings and state for bursts of increased mutation when the population stagnates.
+#[derive(Clone)]
+struct Hypermutation {
+    /// The number of times each child is mutated during a burst.
+    mutations: usize,
+    /// The number of steps a burst lasts.
+    generations: u64,
+    /// The number of steps left in the current burst.
+    remaining: u64,
+    /// Whether the population stagnated again after a burst, without restarting in between.
+    tried: bool,
+}
+
 /// A sequential implementation of `::sim::Simulation`.
 /// The genetic algorithm is run in a single thread.
-pub struct Simulator<T: Phenotype> {
-    population: Vec<Box<T>>,
+///
+/// All random decisions of the `Simulator` are made by a random number generator of
+/// type `R`, which is the thread-local random number generator by default.
+pub struct Simulator<T: Phenotype, R: Rng = ThreadRng> {
+    population: Vec<Individual<T>>,
     iter_limit: IterLimit,
-    selector: Box<Selector<T>>,
+    pub(super) selector: Box<dyn Selector<T>>,
+    replacement: Rc<dyn ReplacementPolicy<T>>,
+    fitness_function: Rc<dyn FitnessFunction<T>>,
+    batch_evaluator: Option<Rc<RefCell<Box<dyn BatchEvaluator<T>>>>>,
+    violations: Option<fn(&T) -> f64>,
     fitness_type: FitnessType,
     earlystopper: Option<EarlyStopper>,
+    early_stop_metric: EarlyStopMetric,
+    steady_state: bool,
+    elitism: usize,
     duration: Option<NanoSecond>,
-    error: Option<String>,
+    /// The number of steps whose running time is included in `duration`.
+    timed_steps: u64,
+    max_duration: Option<Duration>,
+    target_fitness: Option<f64>,
+    error: Option<Error>,
+    rng: R,
+    seed: Option<u64>,
+    step_callback: Option<StepCallback<T>>,
+    repair: Option<Repair<T>>,
+    duplicates: Option<Duplicates<T>>,
+    observers: Vec<Box<dyn Observer<T>>>,
+    stopping_conditions: Vec<Box<dyn StoppingCondition<T>>>,
+    condition_met: bool,
+    restart: Option<Restart<T>>,
+    restarts: u64,
+    hypermutation: Option<Hypermutation>,
+    hypermutations: u64,
+    immigrants: Option<Immigrants<T>>,
+    hall_of_fame: Option<HallOfFame<T>>,
+    best_ever: Option<Individual<T>>,
+    finished: bool,
+    stats_history: Option<Vec<GenerationStats>>,
+    diversity: Option<Diversity<T>>,
+    history: Vec<HistoryEntry<T>>,
+    history_phenotypes: bool,
+    /// Creates children from selected parents.
+    pub(super) breed: fn(&Parents<'_, T>, &mut dyn Rng) -> Vec<T>,
+    /// Calculates the fitness values of new phenotypes.
+    pub(super) evaluate: Evaluate<T>,
 }
 
-impl<T: Phenotype> Simulation<T> for Simulator<T> {
-    type B = SimulatorBuilder<T>;
+impl<T: Phenotype> Simulator<T> {
+    /// Create builder, for a `Simulator` that uses the thread-local random number generator.
+    ///
+    /// Call `set_rng` or `set_seed` on the builder to use another random number generator.
+    pub fn builder() -> SimulatorBuilder<T> {
+        Simulator::builder_with_rng(::rand::thread_rng())
+    }
+}
 
-    /// Create builder.
-    fn builder() -> SimulatorBuilder<T> {
+impl<T: Phenotype, R: Rng> Simulator<T, R> {
+    /// Create builder, for a `Simulator` that uses `rng`.
+    pub(super) fn builder_with_rng(rng: R) -> SimulatorBuilder<T, R> {
         SimulatorBuilder {
             sim: Simulator {
                 population: Vec::new(),
                 iter_limit: IterLimit::new(100),
                 selector: Box::new(MaximizeSelector::new(3)),
+                replacement: Rc::new(StochasticReplacement::new()),
+                fitness_function: Rc::new(PhenotypeFitness),
+                batch_evaluator: None,
+                violations: None,
                 fitness_type: FitnessType::Maximize,
                 earlystopper: None,
+                early_stop_metric: EarlyStopMetric::Best,
+                steady_state: false,
+                elitism: 0,
                 duration: Some(0),
+                timed_steps: 0,
+                max_duration: None,
+                target_fitness: None,
                 error: None,
+                rng,
+                seed: None,
+                step_callback: None,
+                repair: None,
+                duplicates: None,
+                observers: Vec::new(),
+                stopping_conditions: Vec::new(),
+                condition_met: false,
+                restart: None,
+                restarts: 0,
+                hypermutation: None,
+                hypermutations: 0,
+                immigrants: None,
+                hall_of_fame: None,
+                best_ever: None,
+                finished: false,
+                stats_history: None,
+                diversity: None,
+                history: Vec::new(),
+                history_phenotypes: false,
+                breed,
+                evaluate,
             },
+            population: Vec::new(),
+            state: PhantomData,
+        }
+    }
+
+    /// Replace the random number generator, keeping everything else.
+    fn replace_rng<S: Rng>(self, rng: S) -> Simulator<T, S> {
+        Simulator {
+            population: self.population,
+            iter_limit: self.iter_limit,
+            selector: self.selector,
+            replacement: self.replacement,
+            fitness_function: self.fitness_function,
+            batch_evaluator: self.batch_evaluator,
+            violations: self.violations,
+            fitness_type: self.fitness_type,
+            earlystopper: self.earlystopper,
+            early_stop_metric: self.early_stop_metric,
+            steady_state: self.steady_state,
+            elitism: self.elitism,
+            duration: self.duration,
+            timed_steps: self.timed_steps,
+            max_duration: self.max_duration,
+            target_fitness: self.target_fitness,
+            error: self.error,
+            rng,
+            seed: None,
+            step_callback: self.step_callback,
+            repair: self.repair,
+            duplicates: self.duplicates,
+            observers: self.observers,
+            stopping_conditions: self.stopping_conditions,
+            condition_met: self.condition_met,
+            restart: self.restart,
+            restarts: self.restarts,
+            hypermutation: self.hypermutation,
+            hypermutations: self.hypermutations,
+            immigrants: self.immigrants,
+            hall_of_fame: self.hall_of_fame,
+            best_ever: self.best_ever,
+            finished: self.finished,
+            stats_history: self.stats_history,
+            diversity: self.diversity,
+            history: self.history,
+            history_phenotypes: self.history_phenotypes,
+            breed: self.breed,
+            evaluate: self.evaluate,
         }
     }
+}
+
+impl<T: Phenotype, R: FromEntropy> Simulation<T> for Simulator<T, R> {
+    type B = SimulatorBuilder<T, R>;
+
+    /// Create builder.
+    fn builder() -> SimulatorBuilder<T, R> {
+        Simulator::builder_with_rng(R::from_entropy())
+    }
 
     fn step(&mut self) -> StepResult {
-        if self.population.is_empty() {
-            self.error = Some(format!("Tried to run a simulator without a population, or the \
-                                       population was empty."));
+        if self.error.is_some() {
             return StepResult::Failure;
         }
+        if self.population.is_empty() {
+            return self.fail(Error::EmptyPopulation);
+        }
         let time_start = SteadyTime::now();
-        let should_stop = match self.earlystopper {
-            Some(ref x) => self.iter_limit.reached() || x.reached(),
-            None => self.iter_limit.reached(),
-        };
-        if should_stop {
+        let mut stop_reason = self.stop_reason();
+        if stop_reason == StopReason::EarlyStop {
+            // Try a burst of hypermutation first, then a restart if that did not help.
+            let restart = self.restart.is_some();
+            let burst = self.hypermutation.as_ref().is_some_and(|x| !(x.tried && restart));
+            if burst {
+                self.start_hypermutation();
+                stop_reason = StopReason::Running;
+            } else if restart {
+                if let Err(e) = self.restart() {
+                    return self.fail(e);
+                }
+                if let Some(ref mut hypermutation) = self.hypermutation {
+                    hypermutation.tried = false;
+                }
+                stop_reason = StopReason::Running;
+            }
+        }
+        if stop_reason != StopReason::Running {
+            if !self.finished {
+                self.finished = true;
+                let generations = self.iter_limit.get();
+                #[cfg(feature = "log")]
+                {
+                    match stop_reason {
+                        StopReason::MaxIterations => {
+                            info!("Reached the maximum of {} generations", generations)
+                        }
+                        StopReason::TimeLimit => {
+                            info!("Ran out of time after {} generations", generations)
+                        }
+                        StopReason::TargetReached => {
+                            info!("Reached the target fitness after {} generations", generations)
+                        }
+                        StopReason::Condition => {
+                            info!("Met a stopping condition after {} generations", generations)
+                        }
+                        _ => info!("Stopping early after {} generations", generations),
+                    }
+                }
+                notify(&mut self.observers, &Event::SimulationFinished { generations });
+            }
             return StepResult::Done;
         } else {
+            let generation = self.iter_limit.get() + 1;
+            #[cfg(feature = "tracing")]
+            let _span = ::tracing::debug_span!("step", generation).entered();
+            if self.earlystopper.as_ref().is_some_and(|x| !x.started()) {
+                // Record the initial population, to compare the first step against.
+                let fitness = self.early_stop_fitness();
+                if let Some(ref mut stopper) = self.earlystopper {
+                    stopper.update(fitness, self.fitness_type);
+                }
+            }
+            notify(&mut self.observers, &Event::GenerationStarted { generation });
             // Perform selection
-            let parents_tmp = (*self.selector).select(&self.population, self.fitness_type);
+            self.selector.set_generation(self.iter_limit.get());
+            if let Err(e) = self.selector.validate(self.population.len()) {
+                return self.fail(Error::InvalidSelectorParams(e));
+            }
+            let parents_tmp = phase!("selection",
+                                     (*self.selector).select(&self.population,
+                                                             self.fitness_type,
+                                                             &mut self.rng));
             if parents_tmp.is_err() {
-                self.error = Some(parents_tmp.err().unwrap());
-                return StepResult::Failure;
+                return self.fail(Error::SelectionFailed(parents_tmp.err().unwrap()));
             }
-            let parents = parents_tmp.ok().unwrap();
+            let mut parents = parents_tmp.ok().unwrap();
+            if self.steady_state && !parents.is_empty() {
+                // Only a single child is created in each step. Selectors such as stochastic
+                // universal sampling return their pairs in population order, so the first
+                // pair is not representative, but a random one is.
+                let pair = Rng::gen_range(&mut self.rng, 0, parents.len());
+                parents = vec![parents[pair]];
+            }
+            notify(&mut self.observers, &Event::SelectionCompleted { parents: &parents });
             // Create children from the selected parents and mutate them.
-            let mut children: Vec<Box<T>> = parents.iter()
-                                                   .map(|pair: &(Box<T>, Box<T>)| {
-                                                       pair.0.crossover(&*(pair.1))
-                                                   })
-                                                   .map(|c| Box::new(c.mutate()))
-                                                   .collect();
-            // Kill off parts of the population at random to make room for the children
-            self.kill_off(children.len());
-            self.population.append(&mut children);
-
-            if let Some(ref mut stopper) = self.earlystopper {
-                let mut cloned = self.population.clone();
-                cloned.sort_by(|x, y| {
-                    (*x).fitness().partial_cmp(&(*y).fitness()).unwrap_or(Ordering::Equal)
-                });
-                let highest_fitness = match self.fitness_type {
-                                          FitnessType::Maximize => cloned[cloned.len() - 1].clone(),
-                                          FitnessType::Minimize => cloned[0].clone(),
-                                      }
-                                      .fitness();
-                stopper.update(highest_fitness);
+            let mut children = phase!("breeding", (self.breed)(&parents, &mut self.rng));
+            if let Some(ref mut hypermutation) = self.hypermutation {
+                if hypermutation.remaining > 0 {
+                    hypermutation.remaining -= 1;
+                    // Children were mutated once while breeding.
+                    for _ in 1..hypermutation.mutations {
+                        for child in &mut children {
+                            *child = child.mutate_with_rng(&mut self.rng);
+                        }
+                    }
+                }
+            }
+            if let Some(ref repair) = self.repair {
+                for child in &mut children {
+                    (*repair.borrow_mut())(child);
+                }
+            }
+            self.eliminate_duplicates(&mut children);
+            let children = match phase!("evaluation", self.evaluate_all(children)) {
+                Ok(children) => children,
+                Err(e) => return self.fail(e),
+            };
+            #[cfg(feature = "metrics")]
+            ::metrics::counter!("rsgenetic_evaluations_total").increment(children.len() as u64);
+            notify(&mut self.observers, &Event::OffspringCreated { children: &children });
+            // Everyone who was already around survived another step
+            for individual in &mut self.population {
+                individual.grow_older();
+            }
+            // Keep the elite out of harm's way while making room for the children
+            let elite_count = cmp::min(self.elitism,
+                                       self.population.len().saturating_sub(children.len()));
+            let mut elite = self.take_elite(elite_count);
+            phase!("replacement", {
+                if self.steady_state {
+                    // The child takes the place of the worst performing phenotype
+                    WorstReplacement::new().replace(&mut self.population,
+                                                    children,
+                                                    self.fitness_type,
+                                                    &mut self.rng);
+                } else {
+                    // Kill off parts of the population to make room for the children
+                    self.replacement.replace(&mut self.population,
+                                             children,
+                                             self.fitness_type,
+                                             &mut self.rng);
+                }
+            });
+            self.population.append(&mut elite);
+            // Replace the worst performing phenotypes with new random ones
+            if self.immigrants.is_some() {
+                if let Err(e) = self.add_immigrants() {
+                    return self.fail(e);
+                }
+            }
+
+            if self.earlystopper.is_some() {
+                let fitness = self.early_stop_fitness();
+                if let Some(ref mut stopper) = self.earlystopper {
+                    stopper.update(fitness, self.fitness_type);
+                }
             }
 
             self.iter_limit.inc();
+            let entry = {
+                let best = self.best();
+                HistoryEntry {
+                    generation: self.iter_limit.get(),
+                    best_fitness: best.fitness().to_f64(),
+                    best: if self.history_phenotypes {
+                        Some(best.phenotype().clone())
+                    } else {
+                        None
+                    },
+                }
+            };
+            #[cfg(feature = "log")]
+            debug!("Generation {} completed, best fitness {}",
+                   entry.generation,
+                   entry.best_fitness);
+            #[cfg(feature = "metrics")]
+            {
+                ::metrics::counter!("rsgenetic_generations_total").increment(1);
+                ::metrics::gauge!("rsgenetic_best_fitness").set(entry.best_fitness);
+            }
+            self.history.push(entry);
         }
         let this_time = (SteadyTime::now() - time_start).num_nanoseconds();
-        self.duration = match self.duration {
-            Some(x) => {
-                match this_time {
-                    Some(y) => Some(x + y),
-                    None => None,
-                }
+        #[cfg(feature = "metrics")]
+        {
+            if let Some(x) = this_time {
+                ::metrics::histogram!("rsgenetic_step_duration_seconds").record(x as f64 / 1e9);
             }
+        }
+        self.duration = match self.duration {
+            Some(x) => this_time.map(|y| x + y),
             None => None,
         };
+        self.timed_steps += 1;
+        if self.stats_history.is_some() {
+            let stats = self.stats(this_time);
+            if let Some(ref mut history) = self.stats_history {
+                history.push(stats);
+            }
+        }
+        if self.step_callback.is_some() || !self.stopping_conditions.is_empty() {
+            let generation = self.iter_limit.get();
+            let max_generations = self.iter_limit.max();
+            let info = StepInfo {
+                generation,
+                max_generations,
+                elapsed: self.duration,
+                // Iterations restored by `resume` or `restore` may not have been timed.
+                eta: self.duration.map(|x| {
+                    x / self.timed_steps as i64 *
+                    max_generations.saturating_sub(generation) as i64
+                }),
+                fitness_type: self.fitness_type,
+                best_fitness: self.best().fitness().to_f64(),
+                mean_fitness: self.population.iter().map(|x| x.fitness().to_f64()).sum::<f64>() /
+                              self.population.len() as f64,
+                population: &self.population,
+            };
+            if let Some(ref mut callback) = self.step_callback {
+                callback(&info);
+            }
+            // Every condition is checked, so that each one sees every step.
+            for condition in &mut self.stopping_conditions {
+                if condition.should_stop(&info) {
+                    self.condition_met = true;
+                }
+            }
+        }
         StepResult::Success // Not done yet, but successful
     }
 
@@ -134,16 +514,7 @@ impl<T: Phenotype> Simulation<T> for Simulator<T> {
     fn get(&self) -> SimResult<T> {
         match self.error {
             Some(ref e) => Err(e.clone()),
-            None => {
-                let mut cloned = self.population.clone();
-                cloned.sort_by(|x, y| {
-                    (*x).fitness().partial_cmp(&(*y).fitness()).unwrap_or(Ordering::Equal)
-                });
-                Ok(match self.fitness_type {
-                    FitnessType::Maximize => cloned[cloned.len() - 1].clone(),
-                    FitnessType::Minimize => cloned[0].clone(),
-                })
-            }
+            None => Ok(self.best_ever().phenotype().clone()),
         }
     }
 
@@ -156,51 +527,724 @@ impl<T: Phenotype> Simulation<T> for Simulator<T> {
     }
 }
 
-impl<T: Phenotype> Simulator<T> {
-    /// Kill off phenotypes using stochastic universal sampling.
-    fn kill_off(&mut self, count: usize) {
-        let ratio = self.population.len() / count;
-        let mut i = ::rand::thread_rng().gen_range::<usize>(0, self.population.len());
-        let mut selected = 0;
-        while selected < count {
-            self.population.remove(i);
-            i += ratio - 1;
-            i = i % self.population.len();
+impl<T: Phenotype, R: Rng> Simulator<T, R> {
+    /// Get the current population.
+    pub fn population(&self) -> &[Individual<T>] {
+        &self.population
+    }
+
+    /// Consume this `Simulator` and move the phenotypes of the current population out,
+    /// without cloning them.
+    pub fn into_population(self) -> Vec<T> {
+        self.population.into_iter().map(Individual::into_phenotype).collect()
+    }
+
+    /// Get the phenotypes in the current population that are not dominated by any other
+    /// phenotype in it.
+    ///
+    /// For multi-objective fitness values, such as `Objectives`, these are the best
+    /// trade-offs between the objectives found, and take the place of `get()`. For totally
+    /// ordered fitness values, these are the best performing phenotypes.
+    pub fn pareto_front(&self) -> Vec<T> {
+        super::pareto::pareto_front(&self.population, self.fitness_type)
+            .into_iter()
+            .map(|x| x.phenotype().clone())
+            .collect()
+    }
+
+    /// Calculate the hypervolume indicator of the phenotypes returned by `pareto_front`,
+    /// relative to `reference`, a point that is worse than all of them in every objective.
+    /// See `pareto::hypervolume`.
+    ///
+    /// A larger hypervolume means a better front, so this can be used to compare runs.
+    pub fn hypervolume(&self, reference: &[f64]) -> f64 {
+        let front = super::pareto::pareto_front(&self.population, self.fitness_type);
+        super::pareto::hypervolume(&front, reference, self.fitness_type)
+    }
+
+    /// Get the statistics of every step so far, if the `Simulator` was built with
+    /// `set_collect_stats()`. Otherwise, this is empty.
+    pub fn stats_history(&self) -> &[GenerationStats] {
+        match self.stats_history {
+            Some(ref history) => history,
+            None => &[],
+        }
+    }
+
+    /// Write the statistics of every step so far to `writer` in CSV form, with the columns
+    /// `generation,best,mean,worst,duration_ns`. The `Simulator` must have been built with
+    /// `set_collect_stats()`, otherwise only the header row is written.
+    #[cfg(feature = "csv")]
+    pub fn write_stats_csv<W: Write>(&self, writer: W) -> Result<(), String> {
+        super::stats::write_csv(self.stats_history(), writer)
+    }
+
+    /// Get the best fitness value of every step so far, in order.
+    ///
+    /// If the `Simulator` was built with `set_history_phenotypes()`, the best
+    /// phenotype of every step is recorded as well.
+    pub fn history(&self) -> &[HistoryEntry<T>] {
+        &self.history
+    }
+
+    /// Get the best distinct phenotypes found so far, best performing first, if the
+    /// `Simulator` was built with `set_hall_of_fame`. Otherwise, this is empty.
+    ///
+    /// Unlike the population, this includes phenotypes that have been killed off.
+    pub fn hall_of_fame(&self) -> &[Individual<T>] {
+        match self.hall_of_fame {
+            Some(ref hall_of_fame) => hall_of_fame.members(),
+            None => &[],
+        }
+    }
+
+    /// Render the best fitness value of every step so far as a line chart to the file at
+    /// `path`. The file is an SVG image if `path` ends in `.svg`, and a PNG image otherwise.
+    #[cfg(feature = "plotting")]
+    pub fn plot_history<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        super::plot::plot_history(&self.history, path.as_ref())
+    }
+
+    /// Get the progress of early stopping, if it was set with `set_early_stop`, to carry it
+    /// over to a resumed run with `SimulatorBuilder::set_early_stop_state`.
+    pub fn early_stop_state(&self) -> Option<EarlyStopState> {
+        self.earlystopper.as_ref().map(|x| x.state())
+    }
+
+    /// Summarize the settings of this `Simulator` and the outcome of its run so far.
+    pub fn report(&self) -> Report {
+        Report {
+            population_size: self.population.len(),
+            max_iterations: self.iter_limit.max(),
+            max_duration: self.max_duration.map(|x| x.as_nanos() as NanoSecond),
+            target_fitness: self.target_fitness,
+            fitness_type: self.fitness_type,
+            elitism: self.elitism,
+            steady_state: self.steady_state,
+            early_stop_delta: self.earlystopper.as_ref().map(|x| x.delta()),
+            early_stop_iterations: self.earlystopper.as_ref().map(|x| x.n_iters()),
+            early_stop_relative: self.earlystopper.as_ref().is_some_and(|x| x.relative()),
+            seed: self.seed,
+            iterations: self.iter_limit.get(),
+            time: self.duration,
+            stop_reason: self.stop_reason(),
+            restarts: self.restarts,
+            hypermutations: self.hypermutations,
+            best_fitness: match self.error {
+                Some(_) => None,
+                None if self.population.is_empty() => None,
+                None => Some(self.best_ever().fitness().to_f64()),
+            },
+        }
+    }
+
+    /// Replace a fraction of the population, starting with the worst performing
+    /// phenotypes, with new phenotypes.
+    fn add_immigrants(&mut self) -> Result<(), Error> {
+        let size = self.population.len();
+        let rng = &mut self.rng;
+        let generated: Vec<T> = match self.immigrants {
+            Some(ref mut immigrants) => {
+                let count = (immigrants.fraction * size as f64).round() as usize;
+                let mut generator = immigrants.generator.borrow_mut();
+                (0..cmp::min(count, size)).map(|_| (*generator)(rng)).collect()
+            }
+            None => return Ok(()),
+        };
+        let immigrants = self.evaluate_all(generated)?;
+        WorstReplacement::new().replace(&mut self.population,
+                                        immigrants,
+                                        self.fitness_type,
+                                        &mut self.rng);
+        Ok(())
+    }
+
+    /// Replace the children that equal a member of the population or an earlier child,
+    /// if duplicates are eliminated. A replacement that is a duplicate as well is replaced
+    /// again, up to a maximum number of attempts.
+    fn eliminate_duplicates(&mut self, children: &mut [T]) {
+        const ATTEMPTS: usize = 10;
+        let duplicates = match self.duplicates {
+            Some(ref mut duplicates) => duplicates,
+            None => return,
+        };
+        let same = duplicates.same;
+        for i in 0..children.len() {
+            for _ in 0..ATTEMPTS {
+                let (earlier, rest) = children.split_at_mut(i);
+                let child = &mut rest[0];
+                if !self.population.iter().any(|x| same(x.phenotype(), child)) &&
+                   !earlier.iter().any(|x| same(x, child)) {
+                    break;
+                }
+                *child = match duplicates.generator {
+                    Some(ref generator) => (*generator.borrow_mut())(&mut self.rng),
+                    None => child.mutate_with_rng(&mut self.rng),
+                };
+                if let Some(ref repair) = self.repair {
+                    (*repair.borrow_mut())(child);
+                }
+            }
+        }
+    }
+
+    /// Start a burst of increased mutation, and start looking for stagnation again.
+    fn start_hypermutation(&mut self) {
+        if let Some(ref mut hypermutation) = self.hypermutation {
+            hypermutation.remaining = hypermutation.generations;
+            hypermutation.tried = true;
+        }
+        if let Some(ref mut stopper) = self.earlystopper {
+            stopper.reset();
+        }
+        self.hypermutations += 1;
+        #[cfg(feature = "log")]
+        info!("Increasing mutation after {} generations", self.iter_limit.get());
+    }
+
+    /// Replace all but the best performing phenotypes with new ones, and start
+    /// looking for stagnation again.
+    fn restart(&mut self) -> Result<(), Error> {
+        let size = self.population.len();
+        let rng = &mut self.rng;
+        let (keep, generated) = match self.restart {
+            Some(ref mut restart) => {
+                let keep = cmp::min(restart.keep, size);
+                let mut generator = restart.generator.borrow_mut();
+                let generated: Vec<T> = (keep..size).map(|_| (*generator)(rng)).collect();
+                (keep, generated)
+            }
+            None => return Ok(()),
+        };
+        // Evaluate first, so the population is left intact if this fails.
+        let generated = self.evaluate_all(generated)?;
+        let mut elite = self.take_elite(keep);
+        self.population = generated;
+        self.population.append(&mut elite);
+        if let Some(ref mut stopper) = self.earlystopper {
+            stopper.reset();
+        }
+        self.restarts += 1;
+        #[cfg(feature = "log")]
+        info!("Restarting after {} generations", self.iter_limit.get());
+        Ok(())
+    }
+
+    /// Calculate the fitness value of the population that is monitored for early stopping.
+    fn early_stop_fitness(&self) -> f64 {
+        match self.early_stop_metric {
+            EarlyStopMetric::Best => self.best().fitness().to_f64(),
+            EarlyStopMetric::Mean => {
+                self.population.iter().map(|x| x.fitness().to_f64()).sum::<f64>() /
+                self.population.len() as f64
+            }
+            EarlyStopMetric::Median => {
+                super::stats::median(self.population
+                                         .iter()
+                                         .map(|x| x.fitness().to_f64())
+                                         .collect())
+            }
+        }
+    }
+
+    /// Determine whether, and why, the `Simulator` has stopped.
+    fn stop_reason(&self) -> StopReason {
+        if let Some(ref e) = self.error {
+            return StopReason::Failed(e.clone());
+        }
+        if self.iter_limit.reached() {
+            return StopReason::MaxIterations;
+        }
+        let out_of_time = match (self.max_duration, self.duration) {
+            (Some(max), Some(x)) => Duration::from_nanos(x as u64) >= max,
+            // The running time overflowed, so any limit has been exceeded.
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if out_of_time {
+            return StopReason::TimeLimit;
+        }
+        if let Some(target) = self.target_fitness {
+            if !self.population.is_empty() {
+                let best = self.best_ever().fitness().to_f64();
+                let reached = match self.fitness_type {
+                    FitnessType::Maximize => best >= target,
+                    FitnessType::Minimize => best <= target,
+                };
+                if reached {
+                    return StopReason::TargetReached;
+                }
+            }
+        }
+        if self.condition_met {
+            return StopReason::Condition;
+        }
+        match self.earlystopper {
+            Some(ref x) if x.reached() => StopReason::EarlyStop,
+            _ => StopReason::Running,
+        }
+    }
+
+    /// Record `error` and notify the observers of the failure.
+    fn fail(&mut self, error: Error) -> StepResult {
+        #[cfg(feature = "log")]
+        error!("Simulation failed: {}", error);
+        notify(&mut self.observers, &Event::SimulationFailed { error: &error });
+        self.error = Some(error);
+        StepResult::Failure
+    }
+
+    /// Calculate the fitness values of new phenotypes, using the `BatchEvaluator`
+    /// if there is one, and add them to the hall of fame if they qualify.
+    fn evaluate_all(&mut self, phenotypes: Vec<T>) -> Result<Vec<Individual<T>>, Error> {
+        let individuals = self.evaluate_only(phenotypes).map_err(Error::EvaluationFailed)?;
+        self.record_best(&individuals);
+        Ok(individuals)
+    }
+
+    /// Remember the best performing of `individuals` if it is the best one so far,
+    /// and add them to the hall of fame if they qualify.
+    fn record_best(&mut self, individuals: &[Individual<T>]) {
+        if let Some(ref mut hall_of_fame) = self.hall_of_fame {
+            hall_of_fame.update(individuals, self.fitness_type);
+        }
+        for individual in individuals {
+            let better = match self.best_ever {
+                Some(ref best) => individual.is_better(best, self.fitness_type),
+                None => true,
+            };
+            if better {
+                self.best_ever = Some(individual.clone());
+            }
+        }
+    }
+
+    /// Find the best performing individual found so far, even if it has been killed off.
+    fn best_ever(&self) -> &Individual<T> {
+        match self.best_ever {
+            Some(ref best) => best,
+            None => self.best(),
+        }
+    }
+
+    /// Calculate the fitness values of new phenotypes, using the `BatchEvaluator`
+    /// if there is one, and the violations of their constraints.
+    fn evaluate_only(&mut self, phenotypes: Vec<T>) -> Result<Vec<Individual<T>>, String> {
+        let mut individuals = self.evaluate_fitness(phenotypes)?;
+        if let Some(violations) = self.violations {
+            for individual in &mut individuals {
+                let violation = violations(individual.phenotype());
+                individual.set_violation(violation);
+            }
+        }
+        Ok(individuals)
+    }
+
+    /// Calculate the fitness values of new phenotypes, using the `BatchEvaluator`
+    /// if there is one.
+    fn evaluate_fitness(&mut self, phenotypes: Vec<T>) -> Result<Vec<Individual<T>>, String> {
+        match self.batch_evaluator {
+            Some(ref evaluator) => {
+                let fitnesses = evaluator.borrow_mut().evaluate(&phenotypes)?;
+                if fitnesses.len() != phenotypes.len() {
+                    return Err(format!("The batch evaluator returned {} fitness values for {} \
+                                        phenotypes.",
+                                       fitnesses.len(),
+                                       phenotypes.len()));
+                }
+                Ok(phenotypes.into_iter()
+                             .zip(fitnesses)
+                             .map(|(x, fitness)| Individual::with_fitness(x, fitness))
+                             .collect())
+            }
+            None => Ok((self.evaluate)(phenotypes, &*self.fitness_function)),
+        }
+    }
+
+    /// Calculate the statistics of the population after the current step, which took
+    /// `duration` nanoseconds, including its diversity if it is tracked.
+    fn stats(&mut self, duration: Option<NanoSecond>) -> GenerationStats {
+        let mut stats = GenerationStats::new(self.iter_limit.get(),
+                                             &self.population,
+                                             self.fitness_type,
+                                             duration);
+        if let Some(ref diversity) = self.diversity {
+            stats.diversity = Some(super::stats::diversity(&self.population,
+                                                           diversity.distance,
+                                                           diversity.sample,
+                                                           &mut self.rng));
+        }
+        stats
+    }
+
+    /// Find the best performing individual in the population, in a single pass.
+    fn best(&self) -> &Individual<T> {
+        let fitness_type = self.fitness_type;
+        self.population.iter().max_by(|x, y| x.cmp_rank(y, fitness_type)).unwrap()
+    }
+
+    /// Remove the `count` best performing phenotypes from the population and return them.
+    fn take_elite(&mut self, count: usize) -> Vec<Individual<T>> {
+        if count == 0 {
+            return Vec::new();
+        }
+        let population = &self.population;
+        let fitness_type = self.fitness_type;
+        let mut indices: Vec<usize> = (0..population.len()).collect();
+        if count < indices.len() {
+            indices.select_nth_unstable_by(count - 1, |&x, &y| {
+                population[y].cmp_rank(&population[x], fitness_type)
+            });
+            indices.truncate(count);
+        }
+        // Split the population in a single pass, keeping the rest in order.
+        let mut chosen = vec![false; self.population.len()];
+        for &i in &indices {
+            chosen[i] = true;
+        }
+        let mut elite = Vec::with_capacity(indices.len());
+        let mut rest = Vec::with_capacity(chosen.len() - indices.len());
+        for (individual, chosen) in ::std::mem::take(&mut self.population).into_iter().zip(chosen) {
+            if chosen {
+                elite.push(individual);
+            } else {
+                rest.push(individual);
+            }
+        }
+        self.population = rest;
+        elite
+    }
+}
+
+impl<T: Phenotype, R: Rng + Clone> Simulator<T, R> {
+    /// Create a builder for a copy of this `Simulator`, to continue the run in two
+    /// directions from here.
+    ///
+    /// The copy starts with the settings and the state of this `Simulator`: the population,
+    /// a clone of the random number generator, a clone of the selector, the counters,
+    /// the early-stopping progress, the hall of fame and the collected history. The step
+    /// callback, the observers and the stopping conditions are not copied, so add them on
+    /// the builder again. The fitness function, batch evaluator, repair function,
+    /// generators and replacement policy are shared with this `Simulator`, which includes
+    /// the state of `set_chc`.
+    ///
+    /// Both continue with the same random numbers, so call `set_seed` or `set_rng` on the
+    /// builder to let them diverge.
+    ///
+    /// Returns a builder that can build a `Simulator`, for chaining purposes.
+    pub fn fork(&self) -> SimulatorBuilder<T, R, WithPopulation> {
+        SimulatorBuilder {
+            sim: Simulator {
+                population: self.population.clone(),
+                iter_limit: self.iter_limit.clone(),
+                selector: self.selector.clone(),
+                replacement: Rc::clone(&self.replacement),
+                fitness_function: Rc::clone(&self.fitness_function),
+                batch_evaluator: self.batch_evaluator.clone(),
+                violations: self.violations,
+                fitness_type: self.fitness_type,
+                earlystopper: self.earlystopper.clone(),
+                early_stop_metric: self.early_stop_metric,
+                steady_state: self.steady_state,
+                elitism: self.elitism,
+                duration: self.duration,
+                timed_steps: self.timed_steps,
+                max_duration: self.max_duration,
+                target_fitness: self.target_fitness,
+                error: self.error.clone(),
+                rng: self.rng.clone(),
+                seed: self.seed,
+                step_callback: None,
+                repair: self.repair.clone(),
+                duplicates: self.duplicates.clone(),
+                observers: Vec::new(),
+                stopping_conditions: Vec::new(),
+                condition_met: false,
+                restart: self.restart.clone(),
+                restarts: self.restarts,
+                hypermutation: self.hypermutation.clone(),
+                hypermutations: self.hypermutations,
+                immigrants: self.immigrants.clone(),
+                hall_of_fame: self.hall_of_fame.clone(),
+                best_ever: self.best_ever.clone(),
+                finished: false,
+                stats_history: self.stats_history.clone(),
+                diversity: self.diversity.clone(),
+                history: self.history.clone(),
+                history_phenotypes: self.history_phenotypes,
+                breed: self.breed,
+                evaluate: self.evaluate,
+            },
+            population: Vec::new(),
+            state: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Phenotype, R: Reseed> Simulator<T, R> {
+    /// Take a snapshot of the state of this `Simulator`, from which the run can be
+    /// resumed with `SimulatorBuilder::restore`. See `Checkpoint` for what it contains.
+    ///
+    /// This reseeds the random number generator with a seed drawn from it, which is
+    /// stored in the `Checkpoint`, so a restored run draws the same numbers as this one.
+    pub fn checkpoint(&mut self) -> Checkpoint<T> {
+        let seed = self.rng.gen();
+        Checkpoint {
+            population: self.population.clone(),
+            best: self.best_ever.clone(),
+            iterations: self.iter_limit.get(),
+            earlystopper: self.earlystopper.clone(),
+            duration: self.duration,
+            timed_steps: self.timed_steps,
+            rng_seed: if self.rng.reseed_with(seed) { Some(seed) } else { None },
+            seed: self.seed,
+            restarts: self.restarts,
+            hypermutations: self.hypermutations,
+            history: self.history.clone(),
+            stats_history: self.stats_history.clone(),
+            hall_of_fame: self.hall_of_fame().to_vec(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Phenotype + Serialize, R: Reseed> Simulator<T, R>
+    where T::Fitness: Serialize
+{
+    /// Save a snapshot of the state of this `Simulator` to the file at `path`,
+    /// from which the run can be resumed with `SimulatorBuilder::load_checkpoint`.
+    /// See `checkpoint` and `Checkpoint::save`.
+    pub fn save_checkpoint<P: AsRef<Path>>(&mut self, path: P) -> Result<(), String> {
+        self.checkpoint().save(path)
+    }
+}
+
+impl<T: Phenotype, R: FromEntropy> Simulator<T, R> {
+    /// Return an iterator that makes one step for every item, yielding the statistics of
+    /// that step. The iterator ends when the `Simulator` is done or has failed, so that
+    /// iterator adapters can be used for control flow:
+    ///
+    /// ```ignore
+    /// for stats in s.iter_steps().take(500) {
+    ///     println!("{}: {}", stats.generation, stats.best);
+    /// }
+    /// ```
+    pub fn iter_steps(&mut self) -> Steps<'_, T, R> {
+        Steps { sim: self }
+    }
+
+    /// Run the simulation until `predicate` returns `true` for the statistics of a step,
+    /// or until the `Simulator` is done or fails, whichever happens first.
+    ///
+    /// This allows custom stopping rules without new builder options.
+    pub fn run_until<F: FnMut(&GenerationStats) -> bool>(&mut self, mut predicate: F) -> RunResult {
+        for stats in self.iter_steps() {
+            if predicate(&stats) {
+                return RunResult::Done;
+            }
+        }
+        match self.error {
+            Some(_) => RunResult::Failure,
+            None => RunResult::Done,
+        }
+    }
+}
+
+/// An iterator over the steps of a `Simulator`. See `Simulator::iter_steps`.
+pub struct Steps<'a, T: Phenotype + 'a, R: FromEntropy + 'a> {
+    sim: &'a mut Simulator<T, R>,
+}
 
-            selected += 1;
+impl<'a, T: Phenotype, R: FromEntropy> Iterator for Steps<'a, T, R> {
+    type Item = GenerationStats;
+
+    fn next(&mut self) -> Option<GenerationStats> {
+        let before = self.sim.duration;
+        match self.sim.step() {
+            StepResult::Success => {
+                let duration = match (before, self.sim.duration) {
+                    (Some(x), Some(y)) => Some(y - x),
+                    _ => None,
+                };
+                Some(self.sim.stats(duration))
+            }
+            StepResult::Failure | StepResult::Done => None,
         }
     }
 }
 
+/// Notify all `observers` of `event`.
+fn notify<T: Phenotype>(observers: &mut [Box<dyn Observer<T>>], event: &Event<T>) {
+    for observer in observers {
+        observer.notify(event);
+    }
+}
+
+/// Create children from the selected parents and mutate them.
+fn breed<T: Phenotype>(parents: &Parents<'_, T>, mut rng: &mut dyn Rng) -> Vec<T> {
+    parents.iter()
+           .map(|pair: &(&T, &T)| {
+               let child = pair.0.crossover_with_rng(pair.1, &mut rng);
+               child.mutate_with_rng(&mut rng)
+           })
+           .collect()
+}
+
+/// Calculate the fitness values of `phenotypes`.
+fn evaluate<T: Phenotype>(phenotypes: Vec<T>,
+                          fitness_function: &dyn FitnessFunction<T>)
+                          -> Vec<Individual<T>> {
+    phenotypes.into_iter()
+              .map(|x| {
+                  let fitness = fitness_function.fitness(&x);
+                  Individual::with_fitness(x, fitness)
+              })
+              .collect()
+}
+
+/// The default `FitnessFunction`, which defers to `Phenotype::fitness()`.
+struct PhenotypeFitness;
+
+impl<T: Phenotype> FitnessFunction<T> for PhenotypeFitness {
+    fn fitness(&self, phenotype: &T) -> T::Fitness {
+        phenotype.fitness()
+    }
+}
+
 /// A `Builder` for the `Simulator` type.
-pub struct SimulatorBuilder<T: Phenotype> {
-    sim: Simulator<T>,
+///
+/// `P` tracks whether a population has been set. Only a `SimulatorBuilder` with a
+/// population, marked by `WithPopulation`, can build a `Simulator`.
+pub struct SimulatorBuilder<T: Phenotype, R: Rng = ThreadRng, P = NoPopulation> {
+    pub(super) sim: Simulator<T, R>,
+    population: Vec<T>,
+    state: PhantomData<P>,
 }
 
-impl<T: Phenotype> SimulatorBuilder<T> {
-    /// Set the population of the resulting `Simulator`.
+impl<T: Phenotype, R: Rng, P> SimulatorBuilder<T, R, P> {
+    /// Set the population of the resulting `Simulator`. The phenotypes are moved into
+    /// the `Simulator`, not cloned.
     ///
-    /// Returns itself for chaining purposes.
-    pub fn set_population(mut self, pop: &Vec<Box<T>>) -> Self {
-        self.sim.population = pop.clone();
-        self
+    /// Returns a builder that can build a `Simulator`, for chaining purposes.
+    pub fn set_population(self, pop: Vec<T>) -> SimulatorBuilder<T, R, WithPopulation> {
+        self.set_population_iter(pop)
+    }
+
+    /// Set the population of the resulting `Simulator` to the phenotypes yielded by
+    /// `pop`. This avoids collecting a large population into a `Vec` first.
+    ///
+    /// Returns a builder that can build a `Simulator`, for chaining purposes.
+    pub fn set_population_iter<I>(self, pop: I) -> SimulatorBuilder<T, R, WithPopulation>
+        where I: IntoIterator<Item = T>
+    {
+        let mut builder = self.populated();
+        builder.population = pop.into_iter().collect();
+        builder.sim.population = Vec::new();
+        builder
+    }
+
+    /// Mark this builder as having a population.
+    fn populated(self) -> SimulatorBuilder<T, R, WithPopulation> {
+        SimulatorBuilder {
+            sim: self.sim,
+            population: self.population,
+            state: PhantomData,
+        }
+    }
+
+    /// Continue a run that was interrupted after `iterations_done` iterations,
+    /// with `pop` as its population.
+    ///
+    /// The resulting `Simulator` starts counting from `iterations_done`, so
+    /// `iterations()` and the maximum number of iterations carry on where the
+    /// interrupted run left off. Early stopping starts a new patience window, unless its
+    /// progress is carried over with `set_early_stop_state`. To also keep the cached
+    /// fitness values and the other state of the run, use `restore` with a checkpoint
+    /// instead, which requires the `serde` feature.
+    ///
+    /// Returns a builder that can build a `Simulator`, for chaining purposes.
+    pub fn resume(self,
+                  pop: Vec<T>,
+                  iterations_done: u64)
+                  -> SimulatorBuilder<T, R, WithPopulation> {
+        let mut builder = self.set_population(pop);
+        builder.sim.iter_limit.set(iterations_done);
+        builder
     }
 
     /// Set the selector of the resulting `Simulator`.
     ///
     /// Returns itself for chaining purposes.
-    pub fn set_selector(mut self, sel: Box<Selector<T>>) -> Self {
+    pub fn set_selector(mut self, sel: Box<dyn Selector<T>>) -> Self {
         self.sim.selector = sel;
         self
     }
 
+    /// Set the replacement policy of the resulting `Simulator`, which decides
+    /// which phenotypes are killed off to make room for children.
+    ///
+    /// The default is a `StochasticReplacement`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_replacement(mut self, rep: Box<dyn ReplacementPolicy<T>>) -> Self {
+        self.sim.replacement = Rc::from(rep);
+        self
+    }
+
+    /// Set the fitness function of the resulting `Simulator`.
+    ///
+    /// The fitness values of all phenotypes will be calculated by `fitness_function`
+    /// instead of by `Phenotype::fitness()`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_fitness_function(mut self, fitness_function: Box<dyn FitnessFunction<T>>) -> Self {
+        self.sim.fitness_function = Rc::from(fitness_function);
+        self
+    }
+
+    /// Set the batch evaluator of the resulting `Simulator`.
+    ///
+    /// All phenotypes created in a step are passed to `evaluator` in a single call.
+    /// This takes precedence over the fitness function and over parallel evaluation.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_batch_evaluator(mut self, evaluator: Box<dyn BatchEvaluator<T>>) -> Self {
+        self.sim.batch_evaluator = Some(Rc::new(RefCell::new(evaluator)));
+        self
+    }
+
     /// Set the maximum number of iterations of the resulting `Simulator`.
     ///
     /// The `Simulator` will stop running after this number of iterations.
     ///
     /// Returns itself for chaining purposes.
     pub fn set_max_iters(mut self, i: u64) -> Self {
-        self.sim.iter_limit = IterLimit::new(i);
+        self.sim.iter_limit.set_max(i);
+        self
+    }
+
+    /// Set the maximum running time of the resulting `Simulator`.
+    ///
+    /// The `Simulator` will stop running once the time spent in steps exceeds `duration`,
+    /// keeping the best phenotype found so far. The step that exceeds it is completed.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_max_duration(mut self, duration: Duration) -> Self {
+        self.sim.max_duration = Some(duration);
+        self
+    }
+
+    /// Set the target fitness value of the resulting `Simulator`.
+    ///
+    /// The `Simulator` will stop running as soon as the fitness value of the best performing
+    /// phenotype reaches `target`: when it is at least `target` if the fitness type is
+    /// `Maximize`, or at most `target` if it is `Minimize`. Fitness values are compared
+    /// with `target` through `Fitness::to_f64`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_target_fitness(mut self, target: f64) -> Self {
+        self.sim.target_fitness = Some(target);
         self
     }
 
@@ -214,37 +1258,545 @@ impl<T: Phenotype> SimulatorBuilder<T> {
         self
     }
 
-    /// Set early stopping. If for `n_iters` iterations, the change in the highest fitness
-    /// is smaller than `delta`, the simulator will stop running.
+    /// Set early stopping. If for `n_iters` iterations, the best fitness value does not
+    /// improve on the best fitness value found so far by at least `delta`, the simulator
+    /// will stop running.
     ///
     /// Returns itself for chaining purposes.
     pub fn set_early_stop(mut self, delta: f64, n_iters: u64) -> Self {
         self.sim.earlystopper = Some(EarlyStopper::new(delta, n_iters));
         self
     }
-}
 
-impl<T: Phenotype> Builder<Box<Simulator<T>>> for SimulatorBuilder<T> {
-    fn build(self) -> Box<Simulator<T>> {
-        Box::new(self.sim)
+    /// Set early stopping with a relative `delta`. If for `n_iters` iterations, the best
+    /// fitness value does not improve by at least `delta` times the best fitness value found
+    /// so far, the simulator will stop running. For example, a `delta` of `0.01` stops when
+    /// the fitness improves by less than one percent, regardless of the scale of the fitness
+    /// values.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_early_stop_relative(mut self, delta: f64, n_iters: u64) -> Self {
+        self.sim.earlystopper = Some(EarlyStopper::new_relative(delta, n_iters));
+        self
+    }
+
+    /// Restart instead of stopping early. When early stopping set with `set_early_stop`
+    /// detects stagnation, the resulting `Simulator` keeps the `keep` best performing
+    /// phenotypes, replaces the rest of the population with phenotypes created by
+    /// `generator` and continues. Running several starts within one run helps on
+    /// deceptive fitness landscapes.
+    ///
+    /// `generator` receives the random number generator of the `Simulator`, so that
+    /// seeded runs stay reproducible.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_restart_on_stagnation<F>(mut self, keep: usize, generator: F) -> Self
+        where F: FnMut(&mut dyn Rng) -> T + 'static
+    {
+        self.sim.restart = Some(Restart {
+            keep,
+            generator: Rc::new(RefCell::new(generator)),
+        });
+        self
+    }
+
+    /// Add new phenotypes created by `generator` to the population in every step, replacing
+    /// the worst performing `fraction` of the population, such as `0.1` for ten percent.
+    /// These random immigrants keep the population diverse on dynamic and deceptive problems.
+    ///
+    /// `generator` receives the random number generator of the `Simulator`, so that
+    /// seeded runs stay reproducible.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_immigrants<F>(mut self, fraction: f64, generator: F) -> Self
+        where F: FnMut(&mut dyn Rng) -> T + 'static
+    {
+        self.sim.immigrants = Some(Immigrants {
+            fraction,
+            generator: Rc::new(RefCell::new(generator)),
+        });
+        self
+    }
+
+    /// Increase mutation instead of stopping early. When early stopping set with
+    /// `set_early_stop` detects stagnation, the resulting `Simulator` mutates every child
+    /// `mutations` times instead of once, for the next `generations` steps, and then
+    /// reverts. This helps to escape local optima.
+    ///
+    /// If `set_restart_on_stagnation` is used as well, the population is restarted when
+    /// it stagnates again after a burst of mutation.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_hypermutation(mut self, mutations: usize, generations: u64) -> Self {
+        self.sim.hypermutation = Some(Hypermutation {
+            mutations,
+            generations,
+            remaining: 0,
+            tried: false,
+        });
+        self
+    }
+
+    /// Continue early stopping from `state`, the progress of an earlier run as returned by
+    /// `Simulator::early_stop_state`, so a resumed run does not start a new patience
+    /// window. Call this after `set_early_stop` or `set_early_stop_relative`; without
+    /// early stopping, `state` is ignored.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_early_stop_state(mut self, state: EarlyStopState) -> Self {
+        if let Some(ref mut stopper) = self.sim.earlystopper {
+            stopper.set_state(state);
+        }
+        self
+    }
+
+    /// Set which fitness value of the population is monitored for early stopping.
+    /// By default, this is the fitness value of the best performing phenotype, which can
+    /// plateau while the rest of the population is still improving.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_early_stop_metric(mut self, metric: EarlyStopMetric) -> Self {
+        self.sim.early_stop_metric = metric;
+        self
+    }
+
+    /// Set the number of best performing phenotypes that are guaranteed to
+    /// survive each step of the resulting `Simulator`.
+    ///
+    /// Without elitism, the best phenotype found so far can be killed off to make
+    /// room for children. The default is 0.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_elitism(mut self, n: usize) -> Self {
+        self.sim.elitism = n;
+        self
+    }
+
+    /// Set a callback that the resulting `Simulator` calls after every successful step,
+    /// with the generation number, the best and mean fitness values and the population.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_step_callback<F>(mut self, callback: F) -> Self
+        where F: FnMut(&StepInfo<T>) + 'static
+    {
+        self.sim.step_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Set a repair function, which the resulting `Simulator` calls on every child after
+    /// crossover and mutation, before its fitness value is calculated.
+    ///
+    /// This can fix infeasible children, such as invalid permutations or genes that are
+    /// out of bounds, instead of penalizing them.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_repair<F>(mut self, repair: F) -> Self
+        where F: FnMut(&mut T) + 'static
+    {
+        self.sim.repair = Some(Rc::new(RefCell::new(repair)));
+        self
+    }
+
+    /// Make the resulting `Simulator` calculate `GenerationStats` after every step,
+    /// which can be retrieved by calling `stats_history()`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_collect_stats(mut self) -> Self {
+        if self.sim.stats_history.is_none() {
+            self.sim.stats_history = Some(Vec::new());
+        }
+        self
+    }
+
+    /// Make the resulting `Simulator` record a clone of the best performing phenotype
+    /// of every step in its `history()`, instead of only its fitness value.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_history_phenotypes(mut self) -> Self {
+        self.sim.history_phenotypes = true;
+        self
+    }
+
+    /// Add an observer, which the resulting `Simulator` notifies of every `Event`.
+    /// Observers are notified in the order in which they were added.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn add_observer(mut self, observer: Box<dyn Observer<T>>) -> Self {
+        self.sim.observers.push(observer);
+        self
+    }
+
+    /// Add a stopping condition. The resulting `Simulator` stops as soon as any of its
+    /// stopping conditions is met, in addition to the maximum number of iterations and
+    /// any other stopping criteria set on this builder.
+    /// Conditions can be combined with `stop::And` and `stop::Or`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn add_stopping_condition(mut self, condition: Box<dyn StoppingCondition<T>>) -> Self {
+        self.sim.stopping_conditions.push(condition);
+        self
+    }
+
+    /// Seed the random number generator of the resulting `Simulator`.
+    ///
+    /// Two runs with the same seed and the same settings select and kill off the same
+    /// phenotypes, which makes runs reproducible. The generator is an `Isaac64Rng`, which
+    /// produces the same numbers on every platform. By default, the thread-local random
+    /// number generator is used, which is seeded by the operating system.
+    ///
+    /// Returns a builder for a `Simulator` that uses an `Isaac64Rng`, for chaining purposes.
+    pub fn set_seed(self, seed: u64) -> SimulatorBuilder<T, Isaac64Rng, P> {
+        let mut builder = self.set_rng(Isaac64Rng::from_seed(&[seed]));
+        builder.sim.seed = Some(seed);
+        builder
+    }
+
+    /// Set the random number generator of the resulting `Simulator`.
+    /// See `set_seed`.
+    ///
+    /// Any type that implements `Rng` can be used, such as a fast `XorShiftRng`
+    /// or a counter-based generator for reproducible runs.
+    ///
+    /// Returns a builder for a `Simulator` that uses `rng`, for chaining purposes.
+    pub fn set_rng<S: Rng>(self, rng: S) -> SimulatorBuilder<T, S, P> {
+        SimulatorBuilder {
+            sim: self.sim.replace_rng(rng),
+            population: self.population,
+            state: PhantomData,
+        }
+    }
+
+    /// Use steady-state evolution instead of generational evolution.
+    ///
+    /// Instead of replacing a part of the population in every step, the resulting
+    /// `Simulator` breeds a single child from a random pair of selected parents
+    /// and lets it replace the worst performing phenotype. Each step evaluates a
+    /// single child, which suits expensive fitness functions and online use.
+    ///
+    /// The selector still selects all of its `count` parents in every step, and all
+    /// pairs but one are discarded, so selection costs as much as in a generational
+    /// step. Use a selector with `count` 2 to avoid this waste. Selectors that spread
+    /// their picks over the population, like the Stochastic and Remainder selectors,
+    /// only keep their guarantees over all of their picks, not over a single pair.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_steady_state(mut self) -> Self {
+        self.sim.steady_state = true;
+        self
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Phenotype, R: Reseed, P> SimulatorBuilder<T, R, P> {
+    /// Resume a run from `checkpoint`, restoring everything it contains. See `Checkpoint`.
+    ///
+    /// The random number generator is reseeded with the seed in the `Checkpoint`, so call
+    /// this after `set_seed` or `set_rng`, which would replace it. Call this after
+    /// `set_early_stop` and `set_hall_of_fame` as well, which would otherwise reset the
+    /// restored early-stopping state and hall of fame. The population replaces any
+    /// population set before.
+    ///
+    /// Returns a builder that can build a `Simulator`, for chaining purposes.
+    pub fn restore(self, checkpoint: Checkpoint<T>) -> SimulatorBuilder<T, R, WithPopulation> {
+        let mut builder = self.populated();
+        builder.population = Vec::new();
+        builder.sim.population = checkpoint.population;
+        builder.sim.best_ever = checkpoint.best;
+        builder.sim.iter_limit.set(checkpoint.iterations);
+        if checkpoint.earlystopper.is_some() {
+            builder.sim.earlystopper = checkpoint.earlystopper;
+        }
+        builder.sim.duration = checkpoint.duration;
+        builder.sim.timed_steps = checkpoint.timed_steps;
+        if let Some(seed) = checkpoint.rng_seed {
+            builder.sim.rng.reseed_with(seed);
+        }
+        if checkpoint.seed.is_some() {
+            builder.sim.seed = checkpoint.seed;
+        }
+        builder.sim.restarts = checkpoint.restarts;
+        builder.sim.hypermutations = checkpoint.hypermutations;
+        builder.sim.history = checkpoint.history;
+        if checkpoint.stats_history.is_some() {
+            builder.sim.stats_history = checkpoint.stats_history;
+        }
+        if let Some(ref mut hall_of_fame) = builder.sim.hall_of_fame {
+            hall_of_fame.restore(checkpoint.hall_of_fame);
+        }
+        builder
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Phenotype + DeserializeOwned, R: Reseed, P> SimulatorBuilder<T, R, P>
+    where T::Fitness: DeserializeOwned
+{
+    /// Resume a run from the checkpoint in the file at `path`, written by
+    /// `Simulator::save_checkpoint`. See `restore`.
+    ///
+    /// If the checkpoint can not be loaded, this function returns an `Err(String)`,
+    /// containing a message indicating the error.
+    pub fn load_checkpoint<Q>(self,
+                              path: Q)
+                              -> Result<SimulatorBuilder<T, R, WithPopulation>, String>
+        where Q: AsRef<Path>
+    {
+        Ok(self.restore(Checkpoint::load(path)?))
+    }
+}
+
+impl<T: Phenotype + PartialEq, R: Rng> Simulator<T, R> {
+    /// Get the `k` best performing distinct phenotypes, best performing first, or the
+    /// `Error` that occurred. Phenotypes that are equal are only returned once.
+    ///
+    /// The phenotypes are taken from the population, the best phenotype found so far and
+    /// the hall of fame, if there is one. Fewer than `k` phenotypes are returned if there
+    /// are not enough distinct ones.
+    pub fn get_n_best(&self, k: usize) -> Result<Vec<T>, Error> {
+        if let Some(ref e) = self.error {
+            return Err(e.clone());
+        }
+        let mut candidates: Vec<&Individual<T>> = self.hall_of_fame()
+                                                      .iter()
+                                                      .chain(self.best_ever.iter())
+                                                      .chain(self.population.iter())
+                                                      .collect();
+        let fitness_type = self.fitness_type;
+        candidates.sort_by(|x, y| y.cmp_rank(x, fitness_type));
+        let mut best: Vec<T> = Vec::new();
+        for candidate in candidates {
+            if best.len() == k {
+                break;
+            }
+            if !best.iter().any(|x| x == candidate.phenotype()) {
+                best.push(candidate.phenotype().clone());
+            }
+        }
+        Ok(best)
+    }
+}
+
+impl<T: Phenotype + PartialEq, R: Rng, P> SimulatorBuilder<T, R, P> {
+    /// Keep an archive of the `size` best distinct phenotypes found during the run,
+    /// which can be retrieved by calling `hall_of_fame()` on the resulting `Simulator`.
+    /// Phenotypes that are equal are only added once.
+    ///
+    /// Because phenotypes are killed off at random, the best phenotype found can be
+    /// missing from the final population, but not from the hall of fame.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_hall_of_fame(mut self, size: usize) -> Self {
+        self.sim.hall_of_fame = Some(HallOfFame::new(size));
+        self
+    }
+
+    /// Replace children that equal a member of the population or another child by mutating
+    /// them again, before their fitness values are calculated. Duplicates waste evaluations
+    /// and reduce the diversity of the population.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_eliminate_duplicates(mut self) -> Self {
+        self.sim.duplicates = Some(Duplicates {
+            same: <T as PartialEq>::eq,
+            generator: None,
+        });
+        self
+    }
+
+    /// Replace children that equal a member of the population or another child by new
+    /// phenotypes created by `generator`, before their fitness values are calculated.
+    /// See `set_eliminate_duplicates`.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_eliminate_duplicates_with<F>(mut self, generator: F) -> Self
+        where F: FnMut(&mut dyn Rng) -> T + 'static
+    {
+        self.sim.duplicates = Some(Duplicates {
+            same: <T as PartialEq>::eq,
+            generator: Some(Rc::new(RefCell::new(generator))),
+        });
+        self
+    }
+}
+
+impl<T: Phenotype + Distance + 'static, R: Rng, P> SimulatorBuilder<T, R, P> {
+    /// Run the CHC evolutionary algorithm, with an initial incest `threshold` and the
+    /// `cataclysm` mutation that restarts the population around its best phenotype. The
+    /// distance between phenotypes is calculated by `Distance::distance`. See the
+    /// `sim::chc` module.
+    ///
+    /// This replaces the selector and the replacement policy. Crossover should be HUX, and
+    /// `Phenotype::mutate` should not change the phenotype, as CHC only mutates in its
+    /// cataclysmic restarts.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_chc(mut self, threshold: f64, cataclysm: fn(&T, &mut dyn Rng) -> T) -> Self {
+        let chc = Chc::new(threshold, T::distance, cataclysm);
+        self.sim.selector = Box::new(chc.clone());
+        self.sim.replacement = Rc::new(chc);
+        self
+    }
+}
+
+impl<T: Phenotype + Distance, R: Rng, P> SimulatorBuilder<T, R, P> {
+    /// Measure the diversity of the population after every step: the mean distance between
+    /// its phenotypes, as calculated by `Distance::distance`. It is reported in the
+    /// `diversity` of the `GenerationStats`, so collect them with `set_collect_stats` or
+    /// iterate over `iter_steps()`.
+    ///
+    /// * `sample`: the number of random pairs of phenotypes to measure, or `None` to
+    ///   measure every pair, which takes quadratic time.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_track_diversity(mut self, sample: Option<usize>) -> Self {
+        self.sim.diversity = Some(Diversity {
+            distance: T::distance,
+            sample,
+        });
+        self
+    }
+}
+
+impl<T: Constrained, R: Rng, P> SimulatorBuilder<T, R, P> {
+    /// Take the constraints of the phenotypes into account, as calculated by
+    /// `Constrained::violations`.
+    ///
+    /// Selection, replacement and elitism then prefer feasible phenotypes, falling back
+    /// to the ones that violate their constraints the least. The best phenotype of the
+    /// resulting `Simulator` is the best feasible one, if there is any. Selectors that
+    /// rank by Pareto dominance, and the Roulette selector, ignore the constraints.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_constrained(mut self) -> Self {
+        self.sim.violations = Some(T::violations);
+        self
+    }
+}
+
+impl<T: Phenotype + Send + Sync, R: Rng, P> SimulatorBuilder<T, R, P> {
+    /// Calculate the fitness values of new phenotypes in parallel, on a thread pool.
+    ///
+    /// Selection and offspring creation still run on a single thread, but fitness
+    /// evaluation, which dominates the running time for most problems, does not.
+    /// To parallelize offspring creation as well, use `par::Simulator` instead.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_parallel_evaluation(mut self) -> Self {
+        self.sim.evaluate = super::par::evaluate;
+        self
+    }
+}
+
+impl<T: Phenotype, R: Rng> SimulatorBuilder<T, R, WithPopulation> {
+    /// Run NSGA-II, the multi-objective genetic algorithm: every step creates as many
+    /// children as there are phenotypes in the population, from parents chosen by
+    /// `CrowdedTournamentSelector`, and `CrowdingReplacement` keeps the best non-dominated
+    /// fronts of both. Use a multi-objective fitness value such as `Objectives`, and call
+    /// `pareto_front()` on the `Simulator` instead of `get()`.
+    ///
+    /// This replaces the selector and the replacement policy, based on the size of the
+    /// population that was set, so call this after setting the population.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_nsga2(mut self) -> Self {
+        let size = self.population.len() + self.sim.population.len();
+        self.sim.selector = Box::new(CrowdedTournamentSelector::new(2 * size));
+        self.sim.replacement = Rc::new(CrowdingReplacement::new());
+        self
+    }
+
+    /// Run SPEA2, the multi-objective genetic algorithm: the population serves as the
+    /// archive, from which parents are chosen by `StrengthSelector`. Every step creates as
+    /// many children as there are phenotypes in the archive, and `TruncationReplacement`
+    /// keeps the best non-dominated phenotypes of both. Use a multi-objective fitness
+    /// value such as `Objectives`, and call `pareto_front()` on the `Simulator` instead
+    /// of `get()`.
+    ///
+    /// This replaces the selector and the replacement policy, based on the size of the
+    /// population that was set, so call this after setting the population.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_spea2(mut self) -> Self {
+        let size = self.population.len() + self.sim.population.len();
+        self.sim.selector = Box::new(StrengthSelector::new(2 * size));
+        self.sim.replacement = Rc::new(TruncationReplacement::new());
+        self
+    }
+
+    /// Run MOEA/D, the multi-objective genetic algorithm, which solves the scalar
+    /// subproblems of `decomposition` together: the phenotype at index `i` of the
+    /// population solves subproblem `i`, so the population size must equal the number
+    /// of subproblems. Every step, `DecompositionSelector` picks parents for every
+    /// subproblem from its neighbourhood, and `DecompositionReplacement` lets their child
+    /// replace the solutions of neighbouring subproblems it improves on.
+    ///
+    /// This scales to many objectives better than `set_nsga2()` and `set_spea2()`. Use a
+    /// multi-objective fitness value such as `Objectives`, and call `pareto_front()` on the
+    /// `Simulator` instead of `get()`. Elitism, steady-state evolution and immigrants
+    /// reorder the population, so they should not be used with MOEA/D.
+    ///
+    /// This replaces the selector and the replacement policy.
+    ///
+    /// Returns itself for chaining purposes.
+    pub fn set_moead(mut self, decomposition: Decomposition) -> Self {
+        self.sim.selector = Box::new(DecompositionSelector::new(decomposition.clone()));
+        self.sim.replacement = Rc::new(DecompositionReplacement::new(decomposition));
+        self
+    }
+}
+
+impl<T: Phenotype, R: Rng> Builder<Simulator<T, R>> for SimulatorBuilder<T, R, WithPopulation> {
+    fn build(mut self) -> Result<Simulator<T, R>, BuildError> {
+        if !self.population.is_empty() {
+            self.sim.population = self.sim
+                                      .evaluate_only(self.population)
+                                      .map_err(BuildError::EvaluationFailed)?;
+        }
+        // The population may also have been restored from a checkpoint.
+        let size = self.sim.population.len();
+        if size == 0 {
+            return Err(BuildError::EmptyPopulation);
+        }
+        self.sim.selector.validate(size).map_err(BuildError::InvalidSelector)?;
+        if self.sim.elitism >= size {
+            return Err(BuildError::InvalidElitism {
+                elitism: self.sim.elitism,
+                population_size: size,
+            });
+        }
+        let population = ::std::mem::take(&mut self.sim.population);
+        self.sim.record_best(&population);
+        self.sim.population = population;
+        Ok(self.sim)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use ::sim::*;
+    use pheno::{Constrained, Lexicographic, Objectives, WeightedSum};
     use ::sim::select::*;
-    use ::pheno::*;
+    use ::sim::replace::*;
+    use ::sim::stop::*;
+    use rand::{Rng, XorShiftRng};
+    use std::cell::{Cell, RefCell};
     use std::cmp;
+    use std::rc::Rc;
+    use std::time::Duration;
 
-    #[derive(Clone)]
+    thread_local!(static EVALUATIONS: Cell<usize> = const { Cell::new(0) });
+
+    #[derive(Clone, PartialEq)]
     struct Test {
         f: i64,
     }
 
     impl Phenotype for Test {
+        type Fitness = f64;
+
         fn fitness(&self) -> f64 {
-            (self.f - 0).abs() as f64
+            EVALUATIONS.with(|e| e.set(e.get() + 1));
+            self.f.abs() as f64
         }
 
         fn crossover(&self, t: &Test) -> Test {
@@ -262,53 +1814,906 @@ mod tests {
         }
     }
 
+    /// A `Selector` that accepts any population, but always fails to select.
+    #[derive(Clone)]
+    struct FailingSelector;
+
+    impl Selector<Test> for FailingSelector {
+        fn select<'a>(&self,
+                      _: &'a [Individual<Test>],
+                      _: FitnessType,
+                      _: &mut dyn Rng)
+                      -> Result<Parents<'a, Test>, String> {
+            Err(String::from("Selection failed."))
+        }
+    }
+
     #[test]
-    fn test_kill_off_count() {
+    fn test_take_elite() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .build().unwrap();
+        let mut elite: Vec<i64> = s.take_elite(3).iter().map(|x| x.f).collect();
+        elite.sort();
+        assert_eq!(elite, vec![97, 98, 99]);
+        assert_eq!(s.population.len(), 97);
+    }
+
+    #[test]
+    fn test_elitism() {
+        let selector = MaximizeSelector::new(20);
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(selector))
+                        .set_elitism(1)
+                        .set_max_iters(10)
+                        .build().unwrap();
+        s.run();
+        assert_eq!(s.population.len(), 100);
+        assert!(s.population.iter().any(|x| x.f == 99));
+    }
+
+    #[test]
+    fn test_aging() {
         let selector = MaximizeSelector::new(2);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        let mut s = *seq::Simulator::builder()
-                         .set_population(&population)
-                         .set_selector(Box::new(selector))
-                         .build();
-        s.kill_off(10);
-        assert_eq!(s.population.len(), 90);
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(selector))
+                        .set_replacement(Box::new(AgeReplacement::new()))
+                        .set_max_iters(3)
+                        .build().unwrap();
+        s.run();
+        assert_eq!(s.population.iter().filter(|x| x.age() == 0).count(), 1);
+        assert!(s.population.iter().all(|x| x.age() <= 3));
+        assert!(s.population.iter().any(|x| x.age() == 3));
+    }
+
+    #[test]
+    fn test_steady_state() {
+        let selector = MaximizeSelector::new(2);
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(selector))
+                        .set_fitness_type(FitnessType::Minimize)
+                        .set_steady_state()
+                        .build().unwrap();
+        assert_eq!(s.step(), StepResult::Success);
+        assert_eq!(s.population.len(), 100);
+        assert!(s.population.iter().all(|x| x.f != 99));
     }
 
     #[test]
     fn test_max_iters() {
         let selector = MaximizeSelector::new(2);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        let mut s = *seq::Simulator::builder()
-                         .set_population(&population)
-                         .set_selector(Box::new(selector))
-                         .set_max_iters(2)
-                         .build();
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(selector))
+                        .set_max_iters(2)
+                        .build().unwrap();
         s.run();
         assert!(s.iterations() <= 2);
     }
 
+    #[test]
+    fn test_seed() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let run = |seed| {
+            let mut s = seq::Simulator::builder()
+                            .set_population(population.clone())
+                            .set_selector(Box::new(TournamentSelector::new(10, 4)))
+                            .set_replacement(Box::new(RandomReplacement::new()))
+                            .set_max_iters(10)
+                            .set_seed(seed)
+                            .build().unwrap();
+            s.run();
+            s.population.iter().map(|x| x.f).collect::<Vec<i64>>()
+        };
+        assert_eq!(run(42), run(42));
+    }
+
+    #[test]
+    fn test_generic_rng() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = <seq::Simulator<Test, XorShiftRng> as Simulation<Test>>::builder()
+                        .set_population(population.clone())
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(10)
+                        .build().unwrap();
+        assert_eq!(s.run(), RunResult::Done);
+        let run = || {
+            let mut s = seq::Simulator::builder()
+                            .set_population(population.clone())
+                            .set_selector(Box::new(MaximizeSelector::new(10)))
+                            .set_max_iters(10)
+                            .set_rng(XorShiftRng::new_unseeded())
+                            .build().unwrap();
+            s.run();
+            s.population.iter().map(|x| x.f).collect::<Vec<i64>>()
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn test_step_callback() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i + 10 }).collect();
+        let generations = Rc::new(RefCell::new(Vec::new()));
+        let seen = generations.clone();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_fitness_type(FitnessType::Minimize)
+                        .set_max_iters(5)
+                        .set_step_callback(move |info: &StepInfo<Test>| {
+                             assert_eq!(info.population.len(), 100);
+                             assert_eq!(info.max_generations, 5);
+                             assert!(info.eta.unwrap() >= 0);
+                             assert!(info.best_fitness <= info.mean_fitness);
+                             seen.borrow_mut().push(info.generation);
+                         })
+                         .build().unwrap();
+        s.run();
+        assert_eq!(*generations.borrow(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_iter_steps() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(5)
+                        .build().unwrap();
+        let generations: Vec<u64> = s.iter_steps().take(3).map(|x| x.generation).collect();
+        assert_eq!(generations, vec![1, 2, 3]);
+        assert_eq!(s.iter_steps().count(), 2);
+        assert_eq!(s.iterations(), 5);
+    }
+
+    #[test]
+    fn test_run_until() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population.clone())
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(10)
+                        .build().unwrap();
+        assert_eq!(s.run_until(|stats| stats.generation == 4), RunResult::Done);
+        assert_eq!(s.iterations(), 4);
+        assert_eq!(s.run_until(|_| false), RunResult::Done);
+        assert_eq!(s.iterations(), 10);
+
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(FailingSelector))
+                        .build().unwrap();
+        assert_eq!(s.run_until(|_| false), RunResult::Failure);
+    }
+
+    #[test]
+    fn test_max_duration() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(1000)
+                        .set_max_duration(Duration::from_nanos(1))
+                        .build().unwrap();
+        assert_eq!(s.run(), RunResult::Done);
+        assert_eq!(s.iterations(), 1);
+        assert_eq!(s.report().stop_reason, StopReason::TimeLimit);
+    }
+
+    #[test]
+    fn test_target_fitness() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i + 10 }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_fitness_type(FitnessType::Minimize)
+                        .set_max_iters(1000)
+                        .set_target_fitness(5.0)
+                        .build().unwrap();
+        assert_eq!(s.run(), RunResult::Done);
+        assert!(s.iterations() < 1000);
+        assert!(s.get().unwrap().fitness() <= 5.0);
+        assert_eq!(s.report().stop_reason, StopReason::TargetReached);
+    }
+
+    #[test]
+    fn test_stopping_conditions() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(1000)
+                        .add_stopping_condition(Box::new(MaxGenerations::new(50)))
+                        .add_stopping_condition(Box::new(Or::new(MaxGenerations::new(8),
+                                                                  TargetFitness::new(1000.0))))
+                         .build().unwrap();
+        assert_eq!(s.run(), RunResult::Done);
+        assert_eq!(s.iterations(), 8);
+        assert_eq!(s.report().stop_reason, StopReason::Condition);
+    }
+
+    #[test]
+    fn test_early_stop_metric() {
+        // The best phenotype is optimal from the start, but the mean keeps improving.
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let run = |metric| {
+            let mut s = seq::Simulator::builder()
+                            .set_population(population.clone())
+                            .set_selector(Box::new(MaximizeSelector::new(10)))
+                            .set_fitness_type(FitnessType::Minimize)
+                            .set_max_iters(1000)
+                            .set_early_stop(0.5, 5)
+                            .set_early_stop_metric(metric)
+                            .build().unwrap();
+            s.run();
+            s.iterations()
+        };
+        assert_eq!(run(EarlyStopMetric::Best), 5);
+        assert!(run(EarlyStopMetric::Mean) > 5);
+    }
+
+    #[test]
+    fn test_restart_on_stagnation() {
+        let population: Vec<Test> = (0..100).map(|_| Test { f: 0 }).collect();
+        let generated = Rc::new(Cell::new(0));
+        let counter = generated.clone();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_fitness_type(FitnessType::Minimize)
+                        .set_max_iters(20)
+                        .set_early_stop(1.0, 3)
+                        .set_restart_on_stagnation(2, move |rng: &mut dyn Rng| {
+                             counter.set(counter.get() + 1);
+                             Test { f: 50 + (rng.next_u32() % 50) as i64 }
+                         })
+                         .build().unwrap();
+        assert_eq!(s.run(), RunResult::Done);
+        assert_eq!(s.iterations(), 20);
+        let report = s.report();
+        assert_eq!(report.stop_reason, StopReason::MaxIterations);
+        assert!(report.restarts > 0);
+        assert_eq!(generated.get() as u64, report.restarts * 98);
+        assert_eq!(s.get().unwrap().f, 0);
+    }
+
+    #[test]
+    fn test_restart_evaluation_error() {
+        let population: Vec<Test> = (0..100).map(|_| Test { f: 0 }).collect();
+        // Fails to evaluate the phenotypes generated by a restart.
+        let evaluator = |batch: &[Test]| {
+            if batch.len() == 98 {
+                return Err(String::from("Evaluation failed."));
+            }
+            Ok(batch.iter().map(|x| x.f as f64).collect())
+        };
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(2)))
+                        .set_fitness_type(FitnessType::Minimize)
+                        .set_batch_evaluator(Box::new(evaluator))
+                        .set_early_stop(1.0, 3)
+                        .set_restart_on_stagnation(2, |_: &mut dyn Rng| Test { f: 1 })
+                        .build().unwrap();
+        assert_eq!(s.run(), RunResult::Failure);
+        // The elite is not lost.
+        assert_eq!(s.population().len(), 100);
+    }
+
+    #[test]
+    fn test_hypermutation() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i + 100 }).collect();
+        let run = |mutations| {
+            let mut s = seq::Simulator::builder()
+                            .set_population(population.clone())
+                            .set_selector(Box::new(MaximizeSelector::new(10)))
+                            .set_fitness_type(FitnessType::Minimize)
+                            .set_elitism(10)
+                            .set_max_iters(20)
+                            .set_early_stop(100.0, 2)
+                            .set_hypermutation(mutations, 20)
+                            .build().unwrap();
+            assert_eq!(s.run(), RunResult::Done);
+            assert_eq!(s.iterations(), 20);
+            assert!(s.report().hypermutations > 0);
+            s.get().unwrap().f
+        };
+        // Every child moves one step towards zero for every mutation.
+        assert_eq!(run(1), 100 - 20);
+        assert_eq!(run(5), 100 - 2 - 5 * 18);
+    }
+
+    #[test]
+    fn test_immigrants() {
+        let population: Vec<Test> = (0..100).map(|_| Test { f: 50 }).collect();
+        let generated = Rc::new(Cell::new(0));
+        let counter = generated.clone();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_fitness_type(FitnessType::Minimize)
+                        .set_max_iters(3)
+                        .set_immigrants(0.1, move |_: &mut dyn Rng| {
+                             counter.set(counter.get() + 1);
+                             Test { f: 0 }
+                         })
+                         .build().unwrap();
+        assert_eq!(s.run(), RunResult::Done);
+        assert_eq!(generated.get(), 30);
+        assert_eq!(s.get().unwrap().f, 0);
+    }
+
+    #[test]
+    fn test_hall_of_fame() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population.clone())
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_fitness_type(FitnessType::Minimize)
+                        .set_max_iters(5)
+                        .build().unwrap();
+        s.run();
+        assert!(s.hall_of_fame().is_empty());
+
+        // Children equal to archived phenotypes are not added again.
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_replacement(Box::new(WorstReplacement::new()))
+                        .set_fitness_type(FitnessType::Maximize)
+                        .set_max_iters(5)
+                        .set_hall_of_fame(3)
+                        .build().unwrap();
+        s.run();
+        let best: Vec<i64> = s.hall_of_fame().iter().map(|x| x.f).collect();
+        assert_eq!(best, vec![99, 98, 97]);
+    }
+
+    #[test]
+    fn test_get_best_ever() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        // The immigrants replace the entire population with worse phenotypes.
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_fitness_type(FitnessType::Minimize)
+                        .set_max_iters(1)
+                        .set_immigrants(1.0, |_: &mut dyn Rng| Test { f: 1000 })
+                        .build().unwrap();
+        assert_eq!(s.run(), RunResult::Done);
+        assert_eq!(s.history()[0].best_fitness, 1000.0);
+        assert_eq!(s.get().unwrap().f, 0);
+        assert_eq!(s.report().best_fitness, Some(0.0));
+    }
+
+    #[test]
+    fn test_get_n_best() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i % 10 }).collect();
+        let s = seq::Simulator::builder()
+                    .set_population(population)
+                    .set_selector(Box::new(MaximizeSelector::new(10)))
+                    .set_fitness_type(FitnessType::Minimize)
+                    .build().unwrap();
+        let best: Vec<i64> = s.get_n_best(3).unwrap().iter().map(|x| x.f).collect();
+        assert_eq!(best, vec![0, 1, 2]);
+        assert_eq!(s.get_n_best(20).unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_pareto_front_single_objective() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i % 10 }).collect();
+        let s = seq::Simulator::builder()
+                    .set_population(population)
+                    .set_selector(Box::new(MaximizeSelector::new(10)))
+                    .set_fitness_type(FitnessType::Minimize)
+                    .build().unwrap();
+        // With a single objective, the front consists of the best phenotypes.
+        let front = s.pareto_front();
+        assert_eq!(front.len(), 10);
+        assert!(front.iter().all(|x| x.f == 0));
+        assert!((s.hypervolume(&[5.0]) - 5.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_set_population_iter() {
+        let mut s = seq::Simulator::builder()
+                        .set_population_iter((0..100).map(|i| Test { f: i }))
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(5)
+                        .build().unwrap();
+        assert_eq!(s.population().len(), 100);
+        assert_eq!(s.run(), RunResult::Done);
+    }
+
+    #[test]
+    fn test_into_population() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(20)))
+                        .set_max_iters(5)
+                        .build().unwrap();
+        s.run();
+        assert_eq!(s.population().len(), 100);
+        let fittest = s.population().iter().map(|x| x.f).max();
+        let population = s.into_population();
+        assert_eq!(population.len(), 100);
+        assert_eq!(population.iter().map(|x| x.f).max(), fittest);
+    }
+
+    #[test]
+    fn test_step_n() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(5)
+                        .build().unwrap();
+        assert_eq!(s.step_n(0), StepResult::Success);
+        assert_eq!(s.step_n(3), StepResult::Success);
+        assert_eq!(s.iterations(), 3);
+        assert_eq!(s.step_n(10), StepResult::Done);
+        assert_eq!(s.iterations(), 5);
+    }
+
+    #[test]
+    fn test_resume() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_selector(Box::new(MaximizeSelector::new(2)))
+                        .set_max_iters(5)
+                        .resume(population, 3)
+                        .build().unwrap();
+        assert_eq!(s.iterations(), 3);
+        assert_eq!(s.run(), RunResult::Done);
+        assert_eq!(s.iterations(), 5);
+    }
+
+    #[test]
+    fn test_resume_early_stop() {
+        let population = || (0..100).map(|_| Test { f: 0 }).collect::<Vec<Test>>();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population())
+                        .set_selector(Box::new(MaximizeSelector::new(2)))
+                        .set_early_stop(10.0, 5)
+                        .set_max_iters(3)
+                        .build().unwrap();
+        s.run();
+        let state = s.early_stop_state().unwrap();
+        assert_eq!(state.stale_iterations, 3);
+        let mut resumed = seq::Simulator::builder()
+                              .set_selector(Box::new(MaximizeSelector::new(2)))
+                              .set_early_stop(10.0, 5)
+                              .set_early_stop_state(state)
+                              .set_max_iters(10)
+                              .resume(population(), s.iterations())
+                              .build().unwrap();
+        resumed.run();
+        assert_eq!(resumed.iterations(), 5);
+        assert_eq!(resumed.report().stop_reason, StopReason::EarlyStop);
+    }
+
+    #[test]
+    fn test_resume_eta() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_selector(Box::new(MaximizeSelector::new(2)))
+                        .set_max_iters(1000)
+                        .set_step_callback(|info: &StepInfo<Test>| {
+                            // Only the step run since resuming was timed.
+                            assert_eq!(info.eta, info.elapsed.map(|x| x * 99));
+                        })
+                        .resume(population, 900)
+                        .build().unwrap();
+        assert_eq!(s.step(), StepResult::Success);
+    }
+
+    #[test]
+    fn test_selector_generation() {
+        // Cools down too far from the 5th generation on.
+        let temperature = Temperature::Custom(|g| if g < 5 { 1.0 } else { 0.0 });
+        let population = || (0..100).map(|i| Test { f: i }).collect::<Vec<Test>>();
+        let mut s = seq::Simulator::builder()
+                        .set_selector(Box::new(BoltzmannSelector::new(10, temperature)))
+                        .set_population(population())
+                        .build().unwrap();
+        assert_eq!(s.step(), StepResult::Success);
+        let mut resumed = seq::Simulator::builder()
+                              .set_selector(Box::new(BoltzmannSelector::new(10, temperature)))
+                              .resume(population(), 5)
+                              .build().unwrap();
+        assert_eq!(resumed.step(), StepResult::Failure);
+    }
+
+    #[test]
+    fn test_fork() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_seed(7)
+                        .set_population(population)
+                        .set_selector(Box::new(TournamentSelector::new(10, 4)))
+                        .set_max_iters(20)
+                        .add_stopping_condition(Box::new(MaxGenerations::new(5)))
+                        .build().unwrap();
+        s.run();
+        assert_eq!(s.iterations(), 5);
+        // The stopping condition is not copied.
+        let mut fork = s.fork().build().unwrap();
+        assert_eq!(fork.iterations(), 5);
+        fork.run();
+        assert_eq!(fork.iterations(), 20);
+        // Both continue with the same random numbers.
+        let mut other = s.fork().build().unwrap();
+        other.run();
+        let phenotypes = |x: &seq::Simulator<Test, _>| {
+            x.population().iter().map(|x| x.phenotype().f).collect::<Vec<i64>>()
+        };
+        assert_eq!(phenotypes(&fork), phenotypes(&other));
+        assert_eq!(s.iterations(), 5);
+    }
+
     #[test]
     fn test_early_stopping() {
         let selector = MaximizeSelector::new(2);
-        let population: Vec<Box<Test>> = (0..100).map(|_| Box::new(Test { f: 0 })).collect();
-        let mut s = *seq::Simulator::builder()
-                         .set_population(&population)
-                         .set_early_stop(10.0, 5)
-                         .set_max_iters(10)
-                         .build();
+        let population: Vec<Test> = (0..100).map(|_| Test { f: 0 }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(selector))
+                        .set_early_stop(10.0, 5)
+                        .set_max_iters(10)
+                        .build().unwrap();
         s.run();
         assert!(s.iterations() <= 5);
     }
 
+    #[test]
+    fn test_fitness_evaluated_once() {
+        let selector = TournamentSelector::new(2, 5);
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        EVALUATIONS.with(|e| e.set(0));
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(selector))
+                        .set_early_stop(0.5, 100)
+                        .set_elitism(1)
+                        .set_max_iters(5)
+                        .build().unwrap();
+        s.run();
+        s.get().unwrap();
+        // Only the initial population and one child per step are evaluated.
+        assert_eq!(EVALUATIONS.with(|e| e.get()), 105);
+    }
+
+    #[test]
+    fn test_fitness_function() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let s = seq::Simulator::builder()
+                    .set_population(population)
+                    .set_selector(Box::new(MaximizeSelector::new(10)))
+                    .set_fitness_function(Box::new(|x: &Test| -x.f as f64))
+                    .build().unwrap();
+        assert_eq!(s.get().unwrap().f, 0);
+    }
+
+    #[test]
+    fn test_tuple_fitness() {
+        #[derive(Clone)]
+        struct Exact {
+            f: i64,
+        }
+
+        impl Phenotype for Exact {
+            // Values close to zero are best, ties are broken by the largest value.
+            type Fitness = (i64, i64);
+
+            fn fitness(&self) -> (i64, i64) {
+                (-(self.f.abs() / 10), self.f)
+            }
+
+            fn crossover(&self, t: &Exact) -> Exact {
+                Exact { f: (self.f + t.f) / 2 }
+            }
+
+            fn mutate(&self) -> Exact {
+                self.clone()
+            }
+        }
+
+        let population: Vec<Exact> = (-50..50).map(|i| Exact { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(10)
+                        .build().unwrap();
+        assert_eq!(s.get().unwrap().f, 9);
+        s.run();
+        assert_eq!(s.get().unwrap().f, 9);
+        assert!((s.report().best_fitness.unwrap() - 0.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_lexicographic_fitness() {
+        #[derive(Clone)]
+        struct Program {
+            errors: i64,
+            size: i64,
+        }
+
+        impl Phenotype for Program {
+            type Fitness = Lexicographic;
+
+            fn fitness(&self) -> Lexicographic {
+                Lexicographic(vec![self.errors as f64, self.size as f64])
+            }
+
+            fn crossover(&self, t: &Program) -> Program {
+                Program {
+                    errors: self.errors.min(t.errors),
+                    size: self.size.max(t.size),
+                }
+            }
+
+            fn mutate(&self) -> Program {
+                self.clone()
+            }
+        }
+
+        let population: Vec<Program> = (0..100).map(|i| Program { errors: i % 7, size: 100 - i })
+                                               .collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_fitness_type(FitnessType::Minimize)
+                        .set_max_iters(10)
+                        .build().unwrap();
+        s.run();
+        // The fewest errors come first, and the smallest size breaks the tie.
+        let best = s.get().unwrap();
+        assert_eq!((best.errors, best.size), (0, 2));
+        assert!((s.report().best_fitness.unwrap() - 0.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_weighted_sum() {
+        #[derive(Clone)]
+        struct Design {
+            cost: i64,
+            quality: i64,
+        }
+
+        impl Phenotype for Design {
+            type Fitness = Objectives;
+
+            fn fitness(&self) -> Objectives {
+                Objectives(vec![-self.cost as f64, self.quality as f64])
+            }
+
+            fn crossover(&self, t: &Design) -> Design {
+                Design {
+                    cost: (self.cost + t.cost) / 2,
+                    quality: (self.quality + t.quality) / 2,
+                }
+            }
+
+            fn mutate(&self) -> Design {
+                self.clone()
+            }
+        }
+
+        let population: Vec<Design> = (0..100).map(|i| Design { cost: i, quality: i % 10 })
+                                              .collect();
+        let weighting = WeightedSum::new(vec![1.0, 1.0]).normalize(vec![(-100.0, 0.0),
+                                                                        (0.0, 10.0)]);
+        let mut s = seq::Simulator::builder()
+                        .set_population(weighting.wrap(population))
+                        .set_selector(Box::new(TournamentSelector::new(10, 4)))
+                        .set_max_iters(10)
+                        .build().unwrap();
+        s.run();
+        // Quality weighs ten times as much as cost per unit after normalization.
+        let best = s.get().unwrap().into_inner();
+        assert_eq!((best.cost, best.quality), (9, 9));
+        assert!((s.report().best_fitness.unwrap() - 1.81).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_constrained() {
+        #[derive(Clone)]
+        struct Bounded {
+            f: i64,
+        }
+
+        impl Phenotype for Bounded {
+            type Fitness = f64;
+
+            fn fitness(&self) -> f64 {
+                self.f as f64
+            }
+
+            fn crossover(&self, t: &Bounded) -> Bounded {
+                Bounded { f: self.f.max(t.f) }
+            }
+
+            fn mutate(&self) -> Bounded {
+                self.clone()
+            }
+        }
+
+        impl Constrained for Bounded {
+            fn violations(&self) -> f64 {
+                cmp::max(self.f - 50, 0) as f64
+            }
+        }
+
+        let population: Vec<Bounded> = (0..100).map(|i| Bounded { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(TournamentSelector::new(10, 4)))
+                        .set_replacement(Box::new(WorstReplacement::new()))
+                        .set_elitism(2)
+                        .set_max_iters(20)
+                        .set_constrained()
+                        .build().unwrap();
+        s.run();
+        // The largest feasible phenotype wins, even though infeasible ones are fitter.
+        assert_eq!(s.get().unwrap().f, 50);
+        assert!(s.population().iter().filter(|x| x.violation() == 0.0).count() > 0);
+    }
+
+    #[test]
+    fn test_repair() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_replacement(Box::new(WorstReplacement::new()))
+                        .set_max_iters(10)
+                        .set_repair(|x: &mut Test| x.f = cmp::min(x.f, 50))
+                        .build().unwrap();
+        s.run();
+        // Without repair, the children of the best phenotypes would be above the bound.
+        let children: Vec<&Individual<Test>> = s.population()
+                                                .iter()
+                                                .filter(|x| x.age() == 0)
+                                                .collect();
+        assert_eq!(children.len(), 5);
+        assert!(children.iter().all(|x| x.f == 50));
+    }
+
+    #[test]
+    fn test_eliminate_duplicates() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: if i < 50 { 0 } else { 50 } })
+                                            .collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population.clone())
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_replacement(Box::new(WorstReplacement::new()))
+                        .set_max_iters(1)
+                        .set_eliminate_duplicates()
+                        .build().unwrap();
+        s.run();
+        // Without elimination, every child would be 49.
+        let mut children: Vec<i64> = s.population()
+                                      .iter()
+                                      .filter(|x| x.age() == 0)
+                                      .map(|x| x.f)
+                                      .collect();
+        children.sort();
+        assert_eq!(children, vec![45, 46, 47, 48, 49]);
+
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_replacement(Box::new(WorstReplacement::new()))
+                        .set_max_iters(1)
+                        .set_eliminate_duplicates_with(|_: &mut dyn Rng| Test { f: -1 })
+                        .build().unwrap();
+        s.run();
+        // The first child is kept. The others are replaced, until the attempts run out.
+        let mut children: Vec<i64> = s.population()
+                                      .iter()
+                                      .filter(|x| x.age() == 0)
+                                      .map(|x| x.f)
+                                      .collect();
+        children.sort();
+        assert_eq!(children, vec![-1, -1, -1, -1, 49]);
+    }
+
+    #[test]
+    fn test_parallel_evaluation() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i + 10 }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(TournamentSelector::new(4, 4)))
+                        .set_max_iters(1000)
+                        .set_fitness_type(FitnessType::Minimize)
+                        .set_parallel_evaluation()
+                        .build().unwrap();
+        s.run();
+        assert_eq!(s.get().unwrap().f, 0);
+    }
+
+    #[test]
+    fn test_batch_evaluator() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let evaluator = move |batch: &[Test]| {
+            counter.set(counter.get() + 1);
+            Ok(batch.iter().map(|x| -x.f as f64).collect())
+        };
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(2)))
+                        .set_batch_evaluator(Box::new(evaluator))
+                        .set_max_iters(3)
+                        .build().unwrap();
+        assert_eq!(s.get().unwrap().f, 0);
+        s.run();
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn test_batch_evaluator_error() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let evaluator = |_: &[Test]| Ok(vec![0.0]);
+        let result = seq::Simulator::builder()
+                         .set_population(population)
+                         .set_selector(Box::new(MaximizeSelector::new(2)))
+                         .set_batch_evaluator(Box::new(evaluator))
+                         .build();
+        match result {
+            Err(BuildError::EvaluationFailed(_)) => {}
+            _ => panic!("Expected the evaluation to fail"),
+        }
+    }
+
     #[test]
     fn test_selector_error_propagate() {
-        let selector = MaximizeSelector::new(0);
-        let population: Vec<Box<Test>> = (0..100).map(|i| Box::new(Test { f: i })).collect();
-        let mut s = *seq::Simulator::builder()
-                         .set_population(&population)
-                         .set_selector(Box::new(selector))
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(FailingSelector))
+                        .build().unwrap();
+        assert_eq!(s.run(), RunResult::Failure);
+        assert_eq!(s.get().err(),
+                   Some(Error::SelectionFailed(String::from("Selection failed."))));
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let result = seq::Simulator::builder()
+                         .set_population(Vec::<Test>::new())
+                         .set_selector(Box::new(MaximizeSelector::new(10)))
                          .build();
-        s.run();
-        assert!(s.get().is_err());
+        assert_eq!(result.err(), Some(BuildError::EmptyPopulation));
+        let result = seq::Simulator::builder()
+                         .set_population(population.clone())
+                         .set_selector(Box::new(MaximizeSelector::new(0)))
+                         .build();
+        match result {
+            Err(BuildError::InvalidSelector(_)) => {}
+            _ => panic!("Expected the selector to be invalid"),
+        }
+        let result = seq::Simulator::builder()
+                         .set_population(population)
+                         .set_selector(Box::new(MaximizeSelector::new(10)))
+                         .set_elitism(100)
+                         .build();
+        assert_eq!(result.err(),
+                   Some(BuildError::InvalidElitism {
+                       elitism: 100,
+                       population_size: 100,
+                   }));
     }
 }
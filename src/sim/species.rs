@@ -0,0 +1,177 @@
+// file: species.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The species module clusters the population into species, for speciation as in NEAT.
+//!
+//! Phenotypes belong to the same species if they are compatible: if the distance between
+//! them, as calculated by a user-provided compatibility distance, is below a threshold.
+//! Fitness values are shared within every species, so a large species can not take over
+//! the population, and offspring are allocated per species according to the shared
+//! fitness values of their members.
+
+use pheno::Phenotype;
+use super::Individual;
+
+/// A species: a cluster of compatible members of a population.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Species {
+    /// The index of the member the other members are compatible with.
+    pub representative: usize,
+    /// The indices of the members, including the representative.
+    pub members: Vec<usize>,
+}
+
+/// Cluster `population` into species, and return them.
+///
+/// Going through the population in order, every phenotype joins the first species of
+/// which the representative is closer than `threshold` to it, according to `distance`.
+/// If there is no such species, the phenotype founds a new species, of which it is the
+/// representative.
+pub fn speciate<T: Phenotype>(population: &[Individual<T>],
+                              threshold: f64,
+                              distance: fn(&T, &T) -> f64)
+                              -> Vec<Species> {
+    let mut species: Vec<Species> = Vec::new();
+    for (i, individual) in population.iter().enumerate() {
+        let compatible = species.iter().position(|s| {
+            distance(population[s.representative].phenotype(), individual.phenotype()) <
+            threshold
+        });
+        match compatible {
+            Some(s) => species[s].members.push(i),
+            None => {
+                species.push(Species {
+                    representative: i,
+                    members: vec![i],
+                })
+            }
+        }
+    }
+    species
+}
+
+/// Share the fitness values of `population` within `species`: divide the fitness value of
+/// every member by the size of its species. Fitness values must not be negative.
+pub fn shared_fitness<T: Phenotype<Fitness = f64>>(population: &[Individual<T>],
+                                                   species: &[Species])
+                                                   -> Vec<f64> {
+    let mut shared = vec![0.0; population.len()];
+    for s in species {
+        for &i in &s.members {
+            shared[i] = population[i].fitness() / s.members.len() as f64;
+        }
+    }
+    shared
+}
+
+/// Allocate `count` offspring to `species`, in proportion to the sum of the `shared`
+/// fitness values of their members, and return the number of offspring of every species.
+///
+/// Rounding is done by the largest remainder, so the numbers add up to `count`. If all
+/// shared fitness values are zero, offspring are allocated in proportion to the sizes of
+/// the species instead.
+pub fn allocate_offspring(species: &[Species], shared: &[f64], count: usize) -> Vec<usize> {
+    let mut totals: Vec<f64> = species.iter()
+                                      .map(|s| s.members.iter().map(|&i| shared[i]).sum())
+                                      .collect();
+    if totals.iter().all(|&x| x <= 0.0) {
+        totals = species.iter().map(|s| s.members.len() as f64).collect();
+    }
+    let sum: f64 = totals.iter().sum();
+    if species.is_empty() || sum <= 0.0 {
+        return vec![0; species.len()];
+    }
+    let quotas: Vec<f64> = totals.iter().map(|x| x / sum * count as f64).collect();
+    let mut offspring: Vec<usize> = quotas.iter().map(|x| x.floor() as usize).collect();
+    let mut remaining = count - offspring.iter().sum::<usize>();
+    let mut order: Vec<usize> = (0..species.len()).collect();
+    order.sort_by(|&x, &y| {
+        let remainder = |i: usize| quotas[i] - quotas[i].floor();
+        remainder(y).partial_cmp(&remainder(x)).unwrap_or(::std::cmp::Ordering::Equal)
+    });
+    for &i in order.iter().cycle() {
+        if remaining == 0 {
+            break;
+        }
+        offspring[i] += 1;
+        remaining -= 1;
+    }
+    offspring
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::sim::*;
+
+    #[derive(Clone)]
+    struct Test {
+        x: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.x as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { x: (self.x + t.x) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn distance(x: &Test, y: &Test) -> f64 {
+        (x.x - y.x).abs() as f64
+    }
+
+    fn population() -> Vec<Individual<Test>> {
+        vec![10, 50, 12, 11, 52, 90].into_iter().map(|x| Individual::new(Test { x })).collect()
+    }
+
+    #[test]
+    fn test_speciate() {
+        let species = speciate(&population(), 5.0, distance);
+        let members: Vec<Vec<usize>> = species.iter().map(|s| s.members.clone()).collect();
+        assert_eq!(members, vec![vec![0, 2, 3], vec![1, 4], vec![5]]);
+        assert_eq!(species[1].representative, 1);
+    }
+
+    #[test]
+    fn test_shared_fitness() {
+        let population = population();
+        let species = speciate(&population, 5.0, distance);
+        let shared = shared_fitness(&population, &species);
+        assert!((shared[0] - 10.0 / 3.0).abs() < 0.001);
+        assert!((shared[4] - 26.0).abs() < 0.001);
+        assert!((shared[5] - 90.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_allocate_offspring() {
+        let population = population();
+        let species = speciate(&population, 5.0, distance);
+        let shared = shared_fitness(&population, &species);
+        // The species have shared fitness totals of 11, 51 and 90.
+        assert_eq!(allocate_offspring(&species, &shared, 10), vec![1, 3, 6]);
+        assert_eq!(allocate_offspring(&species, &shared, 0), vec![0, 0, 0]);
+        assert_eq!(allocate_offspring(&species, &[0.0; 6], 6), vec![3, 2, 1]);
+    }
+}
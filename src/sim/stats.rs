@@ -0,0 +1,325 @@
+// file: stats.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::{Fitness, Phenotype};
+#[cfg(feature = "csv")]
+use csv;
+use rand::Rng;
+use std::cmp::Ordering;
+#[cfg(feature = "csv")]
+use std::io::Write;
+use super::{FitnessType, Individual, NanoSecond};
+
+/// Find the median of the non-empty `fitnesses` in linear time, without sorting them.
+pub(super) fn median(mut fitnesses: Vec<f64>) -> f64 {
+    let n = fitnesses.len();
+    let compare = |x: &f64, y: &f64| x.partial_cmp(y).unwrap_or(Ordering::Equal);
+    let (lower, &mut middle, _) = fitnesses.select_nth_unstable_by(n / 2, compare);
+    if n.is_multiple_of(2) {
+        let below = lower.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        (below + middle) / 2.0
+    } else {
+        middle
+    }
+}
+
+/// Calculate the mean distance between the members of `population`, over every pair of
+/// members, or over `sample` random pairs if it is set.
+pub(super) fn diversity<T: Phenotype>(population: &[Individual<T>],
+                                      distance: fn(&T, &T) -> f64,
+                                      sample: Option<usize>,
+                                      mut rng: &mut dyn Rng)
+                                      -> f64 {
+    let n = population.len();
+    let pair = |i: usize, j: usize| distance(population[i].phenotype(), population[j].phenotype());
+    match sample {
+        Some(count) if count > 0 && n > 1 => {
+            let mut total = 0.0;
+            for _ in 0..count {
+                let i = Rng::gen_range(&mut rng, 0, n);
+                // Pick another member, by skipping `i`.
+                let j = (i + Rng::gen_range(&mut rng, 1, n)) % n;
+                total += pair(i, j);
+            }
+            total / count as f64
+        }
+        _ if n > 1 => {
+            let mut total = 0.0;
+            for i in 0..n {
+                for j in (i + 1)..n {
+                    total += pair(i, j);
+                }
+            }
+            total / (n * (n - 1) / 2) as f64
+        }
+        _ => 0.0,
+    }
+}
+
+/// Statistics about the fitness values of a population after a step of a `Simulation`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GenerationStats {
+    /// The number of steps completed so far, including this one.
+    pub generation: u64,
+    /// The fitness value of the best performing phenotype.
+    pub best: f64,
+    /// The fitness value of the worst performing phenotype.
+    pub worst: f64,
+    /// The mean fitness value.
+    pub mean: f64,
+    /// The median fitness value.
+    pub median: f64,
+    /// The standard deviation of the fitness values.
+    pub std_dev: f64,
+    /// The number of nanoseconds spent on this step, or `None` in case of an overflow.
+    pub duration: Option<NanoSecond>,
+    /// The mean distance between phenotypes, if the `Simulation` tracks diversity.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub diversity: Option<f64>,
+}
+
+impl GenerationStats {
+    /// Calculate the statistics of a non-empty `population` after step `generation`,
+    /// which took `duration` nanoseconds. `fitness_type` decides which fitness values
+    /// are best and worst.
+    pub fn new<T: Phenotype>(generation: u64,
+                             population: &[Individual<T>],
+                             fitness_type: FitnessType,
+                             duration: Option<NanoSecond>)
+                             -> GenerationStats {
+        let mut fitnesses: Vec<f64> = population.iter().map(|x| x.fitness().to_f64()).collect();
+        fitnesses.sort_by(|x, y| x.partial_cmp(y).unwrap_or(Ordering::Equal));
+        let n = fitnesses.len();
+        let (lowest, highest) = (fitnesses[0], fitnesses[n - 1]);
+        let (best, worst) = match fitness_type {
+            FitnessType::Maximize => (highest, lowest),
+            FitnessType::Minimize => (lowest, highest),
+        };
+        let median = if n.is_multiple_of(2) {
+            (fitnesses[n / 2 - 1] + fitnesses[n / 2]) / 2.0
+        } else {
+            fitnesses[n / 2]
+        };
+        let mean = fitnesses.iter().sum::<f64>() / n as f64;
+        let variance = fitnesses.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n as f64;
+        GenerationStats {
+            generation,
+            best,
+            worst,
+            mean,
+            median,
+            std_dev: variance.sqrt(),
+            duration,
+            diversity: None,
+        }
+    }
+}
+
+/// Write `stats` to `writer` in CSV form, with the columns
+/// `generation,best,mean,worst,duration_ns` and a header row.
+///
+/// The `duration_ns` column is empty for steps whose duration overflowed.
+#[cfg(feature = "csv")]
+pub fn write_csv<W: Write>(stats: &[GenerationStats], writer: W) -> Result<(), String> {
+    let write = || -> Result<(), csv::Error> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(["generation", "best", "mean", "worst", "duration_ns"])?;
+        for x in stats {
+            let duration = x.duration.map(|d| d.to_string()).unwrap_or_default();
+            writer.write_record(&[x.generation.to_string(),
+                                  x.best.to_string(),
+                                  x.mean.to_string(),
+                                  x.worst.to_string(),
+                                  duration])?;
+        }
+        writer.flush()?;
+        Ok(())
+    };
+    write().map_err(|e| format!("Could not write statistics: {}", e))
+}
+
+/// The best performing phenotype after a step of a `Simulation`, as recorded in its history.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HistoryEntry<T: Phenotype> {
+    /// The number of steps completed so far, including this one.
+    pub generation: u64,
+    /// The fitness value of the best performing phenotype.
+    pub best_fitness: f64,
+    /// The best performing phenotype, if the `Simulation` was asked to record it.
+    pub best: Option<T>,
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use pheno::Distance;
+    use ::sim::select::*;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    impl Distance for Test {
+        fn distance(&self, other: &Test) -> f64 {
+            (self.f - other.f).abs() as f64
+        }
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(super::median(vec![9.0, 2.0, 7.0]), 7.0);
+        assert_eq!(super::median(vec![5.0, 4.0, 9.0, 2.0, 4.0, 7.0, 4.0, 5.0]), 4.5);
+        assert_eq!(super::median(vec![3.0]), 3.0);
+    }
+
+    #[test]
+    fn test_stats() {
+        let population: Vec<Individual<Test>> = [2, 4, 4, 4, 5, 5, 7, 9]
+                                                    .iter()
+                                                    .map(|&f| Individual::new(Test { f }))
+                                                    .collect();
+        let stats = GenerationStats::new(3, &population, FitnessType::Minimize, Some(10));
+        assert_eq!(stats.generation, 3);
+        assert!((stats.best - 2.0).abs() < 0.001);
+        assert!((stats.worst - 9.0).abs() < 0.001);
+        assert!((stats.mean - 5.0).abs() < 0.001);
+        assert!((stats.median - 4.5).abs() < 0.001);
+        assert!((stats.std_dev - 2.0).abs() < 0.001);
+        let stats = GenerationStats::new(3, &population[..3], FitnessType::Maximize, None);
+        assert!((stats.best - 4.0).abs() < 0.001);
+        assert!((stats.median - 4.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_stats_history() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population.clone())
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(5)
+                        .set_collect_stats()
+                        .build().unwrap();
+        s.run();
+        let history = s.stats_history();
+        assert_eq!(history.len(), 5);
+        assert_eq!(history[4].generation, 5);
+        assert!(history.iter().all(|x| x.best >= x.mean && x.mean >= x.worst));
+
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(5)
+                        .build().unwrap();
+        s.run();
+        assert!(s.stats_history().is_empty());
+    }
+
+    #[test]
+    fn test_diversity() {
+        let population: Vec<Individual<Test>> = [1, 3, 3, 7].iter()
+                                                             .map(|&f| Individual::new(Test { f }))
+                                                             .collect();
+        let mut rng = ::rand::thread_rng();
+        // The distances between all pairs are 2, 2, 6, 0, 4 and 4.
+        let all = super::diversity(&population, Test::distance, None, &mut rng);
+        assert!((all - 3.0).abs() < 0.001);
+        let sampled = super::diversity(&population, Test::distance, Some(10), &mut rng);
+        assert!((0.0..=6.0).contains(&sampled));
+        assert_eq!(super::diversity(&population[..1], Test::distance, None, &mut rng), 0.0);
+    }
+
+    #[test]
+    fn test_track_diversity() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population.clone())
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(5)
+                        .set_collect_stats()
+                        .set_track_diversity(Some(50))
+                        .build().unwrap();
+        s.run();
+        assert!(s.stats_history().iter().all(|x| x.diversity.unwrap() > 0.0));
+
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_track_diversity(None)
+                        .build().unwrap();
+        assert!(s.iter_steps().take(3).all(|x| x.diversity.unwrap() > 0.0));
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn test_write_csv() {
+        let population: Vec<Individual<Test>> = (1..5).map(|f| Individual::new(Test { f }))
+                                                      .collect();
+        let stats = vec![GenerationStats::new(1, &population, FitnessType::Maximize, Some(10)),
+                         GenerationStats::new(2, &population, FitnessType::Minimize, None)];
+        let mut output = Vec::new();
+        super::write_csv(&stats, &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "generation,best,mean,worst,duration_ns\n1,4,2.5,1,10\n2,1,2.5,4,\n");
+    }
+
+    #[test]
+    fn test_history() {
+        let population: Vec<Test> = (0..100).map(|i| Test { f: i + 10 }).collect();
+        let mut s = seq::Simulator::builder()
+                        .set_population(population.clone())
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_fitness_type(FitnessType::Minimize)
+                        .set_elitism(1)
+                        .set_max_iters(5)
+                        .build().unwrap();
+        s.run();
+        let history = s.history();
+        assert_eq!(history.len(), 5);
+        assert!(history.iter().all(|x| x.best.is_none()));
+        assert!(history.windows(2).all(|x| x[1].best_fitness <= x[0].best_fitness));
+
+        let mut s = seq::Simulator::builder()
+                        .set_population(population)
+                        .set_selector(Box::new(MaximizeSelector::new(10)))
+                        .set_max_iters(5)
+                        .set_history_phenotypes()
+                        .build().unwrap();
+        s.run();
+        for entry in s.history() {
+            let best = entry.best.as_ref().unwrap();
+            assert!((best.fitness() - entry.best_fitness).abs() < 0.001);
+        }
+    }
+}
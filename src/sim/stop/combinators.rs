@@ -0,0 +1,134 @@
+// file: combinators.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::StoppingCondition;
+use super::super::StepInfo;
+
+/// Stops when both conditions are met.
+///
+/// Both conditions are checked after every step, so that they can keep track of it.
+pub struct And<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A, B> And<A, B> {
+    /// Create a condition that is met when both `a` and `b` are met.
+    pub fn new(a: A, b: B) -> And<A, B> {
+        And { a, b }
+    }
+}
+
+impl<T, A, B> StoppingCondition<T> for And<A, B>
+    where T: Phenotype,
+          A: StoppingCondition<T>,
+          B: StoppingCondition<T>
+{
+    fn should_stop(&mut self, info: &StepInfo<T>) -> bool {
+        let a = self.a.should_stop(info);
+        let b = self.b.should_stop(info);
+        a && b
+    }
+}
+
+/// Stops when either condition is met.
+///
+/// Both conditions are checked after every step, so that they can keep track of it.
+pub struct Or<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A, B> Or<A, B> {
+    /// Create a condition that is met when `a` or `b` is met.
+    pub fn new(a: A, b: B) -> Or<A, B> {
+        Or { a, b }
+    }
+}
+
+impl<T, A, B> StoppingCondition<T> for Or<A, B>
+    where T: Phenotype,
+          A: StoppingCondition<T>,
+          B: StoppingCondition<T>
+{
+    fn should_stop(&mut self, info: &StepInfo<T>) -> bool {
+        let a = self.a.should_stop(info);
+        let b = self.b.should_stop(info);
+        a || b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::stop::*;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    fn info(population: &[Individual<Test>], generation: u64) -> StepInfo<'_, Test> {
+        StepInfo {
+            generation,
+            max_generations: 100,
+            elapsed: Some(0),
+            eta: Some(0),
+            fitness_type: FitnessType::Maximize,
+            best_fitness: 10.0,
+            mean_fitness: 5.0,
+            population,
+        }
+    }
+
+    #[test]
+    fn test_and() {
+        let population = vec![Individual::new(Test { f: 10 })];
+        let mut condition = And::new(MaxGenerations::new(5), TargetFitness::new(10.0));
+        assert!(!condition.should_stop(&info(&population, 4)));
+        assert!(condition.should_stop(&info(&population, 5)));
+        let mut condition = And::new(MaxGenerations::new(5), TargetFitness::new(20.0));
+        assert!(!condition.should_stop(&info(&population, 5)));
+    }
+
+    #[test]
+    fn test_or() {
+        let population = vec![Individual::new(Test { f: 10 })];
+        let mut condition = Or::new(MaxGenerations::new(5), TargetFitness::new(20.0));
+        assert!(!condition.should_stop(&info(&population, 4)));
+        assert!(condition.should_stop(&info(&population, 5)));
+        let low_mean = |i: &StepInfo<Test>| i.mean_fitness < 6.0;
+        let mut condition = Or::new(MaxGenerations::new(5), low_mean);
+        assert!(condition.should_stop(&info(&population, 1)));
+    }
+}
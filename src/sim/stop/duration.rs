@@ -0,0 +1,42 @@
+// file: duration.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use std::time::Duration;
+use super::StoppingCondition;
+use super::super::StepInfo;
+
+/// Stops once the time spent running exceeds a budget.
+pub struct MaxDuration {
+    max: Duration,
+}
+
+impl MaxDuration {
+    /// Create a condition that is met once the time spent running is at least `max`.
+    pub fn new(max: Duration) -> MaxDuration {
+        MaxDuration { max }
+    }
+}
+
+impl<T: Phenotype> StoppingCondition<T> for MaxDuration {
+    fn should_stop(&mut self, info: &StepInfo<T>) -> bool {
+        match info.elapsed {
+            Some(x) => Duration::from_nanos(x as u64) >= self.max,
+            // The running time overflowed, so any budget has been exceeded.
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,37 @@
+// file: generations.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::StoppingCondition;
+use super::super::StepInfo;
+
+/// Stops after a number of steps.
+pub struct MaxGenerations {
+    max: u64,
+}
+
+impl MaxGenerations {
+    /// Create a condition that is met once `max` steps have been completed.
+    pub fn new(max: u64) -> MaxGenerations {
+        MaxGenerations { max }
+    }
+}
+
+impl<T: Phenotype> StoppingCondition<T> for MaxGenerations {
+    fn should_stop(&mut self, info: &StepInfo<T>) -> bool {
+        info.generation >= self.max
+    }
+}
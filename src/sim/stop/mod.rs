@@ -0,0 +1,58 @@
+// file: mod.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The stop module provides a trait that can be implemented to decide when a
+//! `Simulation` should stop, along with combinators and a couple of useful conditions.
+//!
+//! Stopping conditions can be added in any number by calling `add_stopping_condition` on a
+//! `SimulatorBuilder`. The `Simulation` stops as soon as any of them is met, so that
+//! "at most 10000 generations, or 5 minutes, or until the target is reached" can be
+//! expressed declaratively.
+
+mod combinators;
+mod duration;
+mod generations;
+mod stagnation;
+mod target;
+
+use pheno::Phenotype;
+use super::StepInfo;
+
+pub use self::combinators::{And, Or};
+pub use self::duration::MaxDuration;
+pub use self::generations::MaxGenerations;
+pub use self::stagnation::Stagnation;
+pub use self::target::TargetFitness;
+
+/// A `StoppingCondition` decides whether a `Simulation` should stop.
+///
+/// Closures of the form `FnMut(&StepInfo<T>) -> bool` are `StoppingCondition`s as well.
+/// Conditions can be combined with `And` and `Or`.
+pub trait StoppingCondition<T: Phenotype> {
+    /// Return whether the `Simulation` should stop, given the `info` of the step that
+    /// was just completed. This is called exactly once after every step, so conditions
+    /// can keep track of previous steps.
+    fn should_stop(&mut self, info: &StepInfo<T>) -> bool;
+}
+
+impl<T, F> StoppingCondition<T> for F
+    where T: Phenotype,
+          F: FnMut(&StepInfo<T>) -> bool
+{
+    fn should_stop(&mut self, info: &StepInfo<T>) -> bool {
+        self(info)
+    }
+}
@@ -0,0 +1,102 @@
+// file: stagnation.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::StoppingCondition;
+use super::super::StepInfo;
+
+/// Stops when the best fitness value has not changed by at least `delta`
+/// for a number of consecutive steps.
+pub struct Stagnation {
+    delta: f64,
+    n_iters: u64,
+    previous: Option<f64>,
+    stagnant: u64,
+}
+
+impl Stagnation {
+    /// Create a condition that is met once the best fitness value has changed by less than
+    /// `delta` for `n_iters` consecutive steps.
+    pub fn new(delta: f64, n_iters: u64) -> Stagnation {
+        Stagnation {
+            delta,
+            n_iters,
+            previous: None,
+            stagnant: 0,
+        }
+    }
+}
+
+impl<T: Phenotype> StoppingCondition<T> for Stagnation {
+    fn should_stop(&mut self, info: &StepInfo<T>) -> bool {
+        match self.previous {
+            Some(x) if (info.best_fitness - x).abs() < self.delta => self.stagnant += 1,
+            _ => {
+                self.previous = Some(info.best_fitness);
+                self.stagnant = 0;
+            }
+        }
+        self.stagnant >= self.n_iters
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::sim::*;
+    use ::sim::stop::*;
+
+    #[derive(Clone)]
+    struct Test {
+        f: i64,
+    }
+
+    impl Phenotype for Test {
+        type Fitness = f64;
+
+        fn fitness(&self) -> f64 {
+            self.f.abs() as f64
+        }
+
+        fn crossover(&self, t: &Test) -> Test {
+            Test { f: (self.f + t.f) / 2 }
+        }
+
+        fn mutate(&self) -> Test {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_stagnation() {
+        let population = vec![Individual::new(Test { f: 1 })];
+        let mut condition = Stagnation::new(0.5, 2);
+        let mut stops = Vec::new();
+        for &best_fitness in &[1.0, 1.1, 2.0, 2.1, 2.2, 2.3] {
+            let info = StepInfo {
+                generation: 1,
+                max_generations: 100,
+                elapsed: Some(0),
+                eta: Some(0),
+                fitness_type: FitnessType::Maximize,
+                best_fitness,
+                mean_fitness: best_fitness,
+                population: &population,
+            };
+            stops.push(condition.should_stop(&info));
+        }
+        assert_eq!(stops, vec![false, false, false, false, true, true]);
+    }
+}
@@ -0,0 +1,41 @@
+// file: target.rs
+//
+// Copyright 2015-2016 The RsGenetic Developers
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pheno::Phenotype;
+use super::StoppingCondition;
+use super::super::{FitnessType, StepInfo};
+
+/// Stops once the best fitness value reaches a target.
+pub struct TargetFitness {
+    target: f64,
+}
+
+impl TargetFitness {
+    /// Create a condition that is met once the best fitness value is at least `target`
+    /// when maximizing, or at most `target` when minimizing.
+    pub fn new(target: f64) -> TargetFitness {
+        TargetFitness { target }
+    }
+}
+
+impl<T: Phenotype> StoppingCondition<T> for TargetFitness {
+    fn should_stop(&mut self, info: &StepInfo<T>) -> bool {
+        match info.fitness_type {
+            FitnessType::Maximize => info.best_fitness >= self.target,
+            FitnessType::Minimize => info.best_fitness <= self.target,
+        }
+    }
+}